@@ -0,0 +1,8 @@
+-- How tags are normalized when they are written, tags are always trimmed.
+ALTER TABLE user_settings ADD COLUMN tag_lowercase BOOLEAN NOT NULL DEFAULT false;
+-- Characters tags may contain besides letters, digits and spaces, NULL allows
+-- any character.
+ALTER TABLE user_settings ADD COLUMN tag_allowed_chars TEXT;
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('10', NOW());
@@ -0,0 +1,13 @@
+-- Synonyms of a user's tags, replaced with the canonical tag when tags are
+-- written.
+CREATE TABLE tag_alias (
+    user_id UUID NOT NULL,
+    alias TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, alias),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES "user"(user_id) ON DELETE CASCADE
+);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('11', NOW());
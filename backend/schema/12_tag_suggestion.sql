@@ -0,0 +1,25 @@
+-- The keywords the page declares in its meta tags.
+ALTER TABLE bookmark ADD COLUMN keywords TEXT[] NOT NULL DEFAULT '{}';
+
+-- Suggestions at least this confident are applied right away, NULL never
+-- applies them.
+ALTER TABLE user_settings ADD COLUMN tag_suggestion_threshold REAL;
+
+CREATE TYPE tag_suggestion_status AS ENUM ('pending', 'accepted', 'dismissed');
+
+-- Tags suggested for a user's bookmark, a dismissed tag is never suggested
+-- again for that bookmark.
+CREATE TABLE tag_suggestion (
+    bookmark_user_id UUID NOT NULL,
+    tag TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    sources TEXT[] NOT NULL,
+    status tag_suggestion_status NOT NULL DEFAULT 'pending',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (bookmark_user_id, tag),
+    CONSTRAINT fk_bookmark_user FOREIGN KEY(bookmark_user_id) REFERENCES bookmark_user(bookmark_user_id) ON DELETE CASCADE
+);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('12', NOW());
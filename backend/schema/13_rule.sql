@@ -0,0 +1,22 @@
+-- The media type the page was served with, NULL for the bookmarks saved
+-- before it was recorded.
+ALTER TABLE bookmark ADD COLUMN content_type TEXT;
+
+-- Applied to the user's bookmarks matching every condition when they are
+-- saved.
+CREATE TABLE rule (
+    rule_id UUID DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    conditions JSONB NOT NULL,
+    actions JSONB NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (rule_id),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES "user"(user_id) ON DELETE CASCADE,
+    CONSTRAINT rule_name_unique UNIQUE (user_id, name)
+);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('13', NOW());
@@ -0,0 +1,23 @@
+-- A passage of the archived page a user highlighted, anchored by its text
+-- and, as long as the page doesn't change, by its position in the text of
+-- the page.
+CREATE TABLE highlight (
+    highlight_id UUID DEFAULT uuid_generate_v4(),
+    bookmark_user_id UUID NOT NULL,
+    exact TEXT NOT NULL,
+    prefix TEXT NOT NULL DEFAULT '',
+    suffix TEXT NOT NULL DEFAULT '',
+    -- In UTF-16 code units, as in the DOM.
+    start_offset INTEGER NOT NULL,
+    end_offset INTEGER NOT NULL,
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (highlight_id),
+    CONSTRAINT fk_bookmark_user FOREIGN KEY(bookmark_user_id) REFERENCES bookmark_user(bookmark_user_id) ON DELETE CASCADE
+);
+
+CREATE INDEX highlight_bookmark_user_index ON highlight (bookmark_user_id, start_offset);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('14', NOW());
@@ -0,0 +1,17 @@
+ALTER TABLE bookmark_user ADD COLUMN favorite BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE bookmark_user ADD COLUMN archived BOOLEAN NOT NULL DEFAULT false;
+-- NULL while unread.
+ALTER TABLE bookmark_user ADD COLUMN read_at TIMESTAMPTZ;
+-- The share of the page scrolled through, from 0 to 1.
+ALTER TABLE bookmark_user ADD COLUMN progress REAL NOT NULL DEFAULT 0
+    CONSTRAINT bookmark_user_progress_range CHECK (progress BETWEEN 0 AND 1);
+
+-- The progress is saved as the user scrolls, the search document is only
+-- computed again when one of its columns changes.
+DROP TRIGGER bookmark_user_search_tokens_trigger ON bookmark_user;
+CREATE TRIGGER bookmark_user_search_tokens_trigger
+BEFORE INSERT OR UPDATE OF tags, note, search_tokens ON bookmark_user
+FOR EACH ROW EXECUTE FUNCTION bookmark_user_search_tokens();
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('15', NOW());
@@ -0,0 +1,25 @@
+-- Computed from the extracted content when the page is processed.
+ALTER TABLE bookmark ADD COLUMN word_count INTEGER NOT NULL DEFAULT 0;
+-- In minutes, rounded up.
+ALTER TABLE bookmark ADD COLUMN reading_time INTEGER NOT NULL DEFAULT 0;
+-- Unknown for the pages processed before, their raw HTML isn't kept.
+ALTER TABLE bookmark ADD COLUMN image_count INTEGER;
+-- The share of the raw HTML kept as text, pages made of little besides
+-- markup and boilerplate score low.
+ALTER TABLE bookmark ADD COLUMN extraction_ratio REAL;
+
+UPDATE bookmark SET word_count = CASE
+    WHEN btrim(text_content) = '' THEN 0
+    ELSE array_length(regexp_split_to_array(btrim(text_content), '\s+'), 1)
+END;
+-- As `ContentStats::compute`, 200 words per minute and 12 seconds per
+-- image, the unknown image counts add nothing.
+UPDATE bookmark SET reading_time =
+    ceil((ceil(word_count * 60 / 200.0) + coalesce(image_count, 0) * 12) / 60.0);
+
+CREATE INDEX bookmark_reading_time_idx ON bookmark (reading_time);
+CREATE INDEX bookmark_word_count_idx ON bookmark (word_count);
+CREATE INDEX bookmark_extraction_ratio_idx ON bookmark (coalesce(extraction_ratio, 0));
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('16', NOW());
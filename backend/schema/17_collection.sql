@@ -0,0 +1,28 @@
+-- A curated list of a user's bookmarks, in the order the user chose.
+CREATE TABLE collection (
+    collection_id UUID DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (collection_id),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES "user"(user_id) ON DELETE CASCADE,
+    CONSTRAINT collection_name_unique UNIQUE (user_id, name)
+);
+
+CREATE TABLE collection_item (
+    collection_id UUID NOT NULL,
+    bookmark_user_id UUID NOT NULL,
+    -- From 0, without gaps.
+    position INTEGER NOT NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT collection_item_unique PRIMARY KEY (collection_id, bookmark_user_id),
+    CONSTRAINT fk_collection FOREIGN KEY(collection_id) REFERENCES collection(collection_id) ON DELETE CASCADE,
+    CONSTRAINT fk_bookmark_user FOREIGN KEY(bookmark_user_id) REFERENCES bookmark_user(bookmark_user_id) ON DELETE CASCADE
+);
+
+CREATE INDEX collection_item_bookmark_user_index ON collection_item (bookmark_user_id);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('17', NOW());
@@ -0,0 +1,72 @@
+-- The unstemmed words of the title, content and note, for query suggestions.
+ALTER TABLE bookmark_user ADD COLUMN words TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE FUNCTION bookmark_user_words() RETURNS TRIGGER AS $$
+BEGIN
+    SELECT tsvector_to_array(to_tsvector('simple', concat_ws(' ', b.title, b.text_content, NEW.note)))
+    INTO NEW.words
+    FROM bookmark b
+    WHERE b.bookmark_id = NEW.bookmark_id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- The search document is reset when the page content changes.
+CREATE TRIGGER bookmark_user_words_trigger
+BEFORE INSERT OR UPDATE OF note, search_tokens ON bookmark_user
+FOR EACH ROW EXECUTE FUNCTION bookmark_user_words();
+
+UPDATE bookmark_user bu
+SET words = tsvector_to_array(to_tsvector('simple', concat_ws(' ', b.title, b.text_content, bu.note)))
+FROM bookmark b
+WHERE b.bookmark_id = bu.bookmark_id;
+
+-- Every word of a user's bookmarks, so suggestions don't go through the
+-- content of all of them.
+CREATE TABLE search_word (
+    user_id UUID NOT NULL,
+    word TEXT NOT NULL,
+    -- The user's bookmarks containing the word, removed at 0.
+    bookmark_count INTEGER NOT NULL,
+    PRIMARY KEY (user_id, word),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES "user"(user_id) ON DELETE CASCADE
+);
+
+INSERT INTO search_word (user_id, word, bookmark_count)
+SELECT bu.user_id, w.word, count(1)
+FROM bookmark_user bu, unnest(bu.words) w(word)
+GROUP BY bu.user_id, w.word;
+
+CREATE INDEX search_word_trgm_index ON search_word USING GIN (word gin_trgm_ops);
+
+CREATE FUNCTION search_word_count() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.words = NEW.words THEN
+        RETURN NULL;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE search_word w SET bookmark_count = w.bookmark_count - 1
+        FROM unnest(OLD.words) o(word)
+        WHERE w.user_id = OLD.user_id AND w.word = o.word;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO search_word (user_id, word, bookmark_count)
+        SELECT NEW.user_id, n.word, 1 FROM unnest(NEW.words) n(word)
+        ON CONFLICT (user_id, word)
+        DO UPDATE SET bookmark_count = search_word.bookmark_count + 1;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        DELETE FROM search_word w
+        USING unnest(OLD.words) o(word)
+        WHERE w.user_id = OLD.user_id AND w.word = o.word AND w.bookmark_count <= 0;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER search_word_count_trigger
+AFTER INSERT OR UPDATE OR DELETE ON bookmark_user
+FOR EACH ROW EXECUTE FUNCTION search_word_count();
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('18', NOW());
@@ -0,0 +1,5 @@
+-- The title of an imported entry, so its failure can be recognised.
+ALTER TABLE bookmark_task ADD COLUMN title TEXT;
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('19', NOW());
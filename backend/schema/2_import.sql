@@ -0,0 +1,23 @@
+CREATE TYPE import_format AS ENUM ('netscape', 'pocket', 'pinboard', 'csv');
+
+CREATE TABLE import_job (
+    import_id UUID DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL,
+    format import_format NOT NULL,
+    total INTEGER NOT NULL,
+    parse_errors JSONB NOT NULL DEFAULT '[]'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (import_id),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES "user"(user_id) ON DELETE CASCADE
+);
+
+ALTER TABLE bookmark_task ADD COLUMN import_id UUID;
+ALTER TABLE bookmark_task ADD COLUMN user_created_at TIMESTAMPTZ;
+ALTER TABLE bookmark_task ADD CONSTRAINT fk_import_job
+    FOREIGN KEY(import_id) REFERENCES import_job(import_id) ON DELETE SET NULL;
+
+CREATE INDEX bookmark_task_import_index ON bookmark_task (import_id);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('2', NOW());
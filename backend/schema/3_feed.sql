@@ -0,0 +1,41 @@
+CREATE TABLE feed_subscription (
+    feed_id UUID DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    muted BOOLEAN NOT NULL DEFAULT false,
+    mute_keywords TEXT[] NOT NULL DEFAULT '{}',
+    etag TEXT,
+    last_modified TEXT,
+    last_polled_at TIMESTAMPTZ,
+    last_status SMALLINT,
+    last_error TEXT,
+    next_poll_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (feed_id),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES "user"(user_id) ON DELETE CASCADE,
+    CONSTRAINT feed_subscription_unique UNIQUE (user_id, url)
+);
+
+CREATE INDEX feed_subscription_next_poll_index ON feed_subscription (next_poll_at);
+
+CREATE TABLE feed_entry (
+    feed_id UUID NOT NULL,
+    entry_key TEXT NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT,
+    published_at TIMESTAMPTZ,
+    muted BOOLEAN NOT NULL DEFAULT false,
+    task_id UUID,
+    seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (feed_id, entry_key),
+    CONSTRAINT fk_feed FOREIGN KEY(feed_id) REFERENCES feed_subscription(feed_id) ON DELETE CASCADE,
+    CONSTRAINT fk_task FOREIGN KEY(task_id) REFERENCES bookmark_task(task_id) ON DELETE SET NULL
+);
+
+CREATE INDEX feed_entry_seen_index ON feed_entry (feed_id, seen_at DESC);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('3', NOW());
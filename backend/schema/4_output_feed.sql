@@ -0,0 +1,19 @@
+CREATE TYPE output_feed_kind AS ENUM ('all', 'tag', 'search');
+
+CREATE TABLE output_feed (
+    output_feed_id UUID DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL,
+    token TEXT NOT NULL,
+    title TEXT NOT NULL,
+    kind output_feed_kind NOT NULL,
+    tag TEXT,
+    search JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (output_feed_id),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES "user"(user_id) ON DELETE CASCADE,
+    CONSTRAINT output_feed_token_unique UNIQUE (token),
+    CONSTRAINT output_feed_tag_check CHECK ((kind = 'tag') = (tag IS NOT NULL))
+);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('4', NOW());
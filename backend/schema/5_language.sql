@@ -0,0 +1,21 @@
+-- Bookmarks saved before language detection keep the english configuration.
+ALTER TABLE bookmark ADD COLUMN language REGCONFIG NOT NULL DEFAULT 'english';
+
+ALTER TABLE bookmark DROP COLUMN search_tokens;
+ALTER TABLE bookmark ADD COLUMN search_tokens TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector(language, coalesce(title, '')), 'A') ||
+    setweight(to_tsvector(language, coalesce(text_content, '')), 'B')
+) STORED;
+
+CREATE INDEX bookmark_search_index ON bookmark USING GIN (search_tokens);
+
+CREATE TABLE user_settings (
+    user_id UUID NOT NULL,
+    query_language REGCONFIG NOT NULL DEFAULT 'english',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES "user"(user_id) ON DELETE CASCADE
+);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('5', NOW());
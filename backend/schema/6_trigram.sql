@@ -0,0 +1,8 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX bookmark_title_trgm_index ON bookmark USING GIN (title gin_trgm_ops);
+CREATE INDEX bookmark_domain_trgm_index ON bookmark USING GIN (domain gin_trgm_ops);
+CREATE INDEX bookmark_url_trgm_index ON bookmark USING GIN (url gin_trgm_ops);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('6', NOW());
@@ -0,0 +1,18 @@
+CREATE EXTENSION IF NOT EXISTS vector;
+
+-- Set once the chunks of a bookmark are embedded, even when it has none.
+ALTER TABLE bookmark ADD COLUMN embedded_at TIMESTAMPTZ;
+
+CREATE TABLE bookmark_chunk (
+    bookmark_id VARCHAR(512) NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    embedding vector(384) NOT NULL,
+    PRIMARY KEY (bookmark_id, chunk_index),
+    CONSTRAINT fk_bookmark FOREIGN KEY(bookmark_id) REFERENCES bookmark(bookmark_id) ON DELETE CASCADE
+);
+
+CREATE INDEX bookmark_chunk_embedding_index ON bookmark_chunk USING hnsw (embedding vector_cosine_ops);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('7', NOW());
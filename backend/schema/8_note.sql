@@ -0,0 +1,43 @@
+ALTER TABLE bookmark_user ADD COLUMN note TEXT;
+
+-- The user's own search document: the page content along with the user's
+-- tags, note and the url, each weighted separately.
+ALTER TABLE bookmark_user ADD COLUMN search_tokens TSVECTOR;
+
+CREATE FUNCTION bookmark_user_search_tokens() RETURNS TRIGGER AS $$
+BEGIN
+    SELECT
+        setweight(to_tsvector(b.language, coalesce(b.title, '')), 'A') ||
+        setweight(to_tsvector(b.language, array_to_string(coalesce(NEW.tags, '{}'), ' ')), 'A') ||
+        setweight(to_tsvector(b.language, coalesce(NEW.note, '')), 'B') ||
+        setweight(to_tsvector(b.language, coalesce(b.text_content, '')), 'C') ||
+        setweight(to_tsvector(b.language, regexp_replace(b.url, '[^[:alnum:]]+', ' ', 'g')), 'D')
+    INTO NEW.search_tokens
+    FROM bookmark b
+    WHERE b.bookmark_id = NEW.bookmark_id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bookmark_user_search_tokens_trigger
+BEFORE INSERT OR UPDATE ON bookmark_user
+FOR EACH ROW EXECUTE FUNCTION bookmark_user_search_tokens();
+
+-- Changes to the page content are carried over to every user's document.
+CREATE FUNCTION bookmark_content_changed() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE bookmark_user SET search_tokens = NULL WHERE bookmark_id = NEW.bookmark_id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bookmark_content_changed_trigger
+AFTER UPDATE OF title, text_content, url, language ON bookmark
+FOR EACH ROW EXECUTE FUNCTION bookmark_content_changed();
+
+UPDATE bookmark_user SET search_tokens = NULL;
+
+CREATE INDEX bookmark_user_search_index ON bookmark_user USING GIN (search_tokens);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('8', NOW());
@@ -0,0 +1,16 @@
+CREATE TABLE saved_search (
+    saved_search_id UUID DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    search JSONB NOT NULL,
+    -- Bookmarks saved after this are new matches.
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (saved_search_id),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES "user"(user_id) ON DELETE CASCADE,
+    CONSTRAINT saved_search_name_unique UNIQUE (user_id, name)
+);
+
+INSERT INTO schema_version (version, updated_at)
+VALUES ('9', NOW());
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use tracing::instrument;
+
+use crate::db::{self, bookmark::Bookmark, embedding::Chunk, PgPool};
+use crate::embedding::{self, Embedder};
+
+const EMBEDDING_BATCH: i64 = 16;
+
+/// Embeds the chunks of a batch of bookmarks not embedded yet, returns how
+/// many were processed. A bookmark failing to embed is stored without chunks,
+/// so it is not retried forever.
+#[instrument(skip_all)]
+pub async fn embed_pending(pool: &PgPool, embedder: &Arc<Embedder>) -> Result<usize> {
+    let bookmarks = db::embedding::get_pending(pool, EMBEDDING_BATCH).await?;
+    for bookmark in &bookmarks {
+        let chunks = match embed_bookmark(embedder.clone(), bookmark).await {
+            Ok(chunks) => chunks,
+            Err(error) => {
+                tracing::warn!(bookmark_id = %bookmark.bookmark_id, ?error, "Fail to embed bookmark");
+                Vec::new()
+            }
+        };
+        db::embedding::save_chunks(pool, &bookmark.bookmark_id, chunks).await?;
+    }
+    Ok(bookmarks.len())
+}
+
+async fn embed_bookmark(embedder: Arc<Embedder>, bookmark: &Bookmark) -> Result<Vec<Chunk>> {
+    let text = format!("{}\n{}", bookmark.title, bookmark.text_content);
+    tokio::task::spawn_blocking(move || {
+        let contents = embedding::chunks(&text);
+        let embeddings = embedder.embed(&contents)?;
+        Ok(contents
+            .into_iter()
+            .zip(embeddings)
+            .map(|(content, embedding)| Chunk { content, embedding })
+            .collect())
+    })
+    .await?
+}
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use feed_rs::model::{Entry, Feed};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, USER_AGENT};
+use reqwest::{Client as HttpClient, StatusCode};
+use tracing::instrument;
+use url::Url;
+
+use crate::db::{
+    self,
+    feed::{FeedSubscription, NewFeedEntry, PollOutcome},
+    PgPool,
+};
+
+const FEED_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const FEED_MAX_SIZE: usize = 10 * 1024 * 1024;
+const FEED_USER_AGENT: &str = concat!("bookmark-rs/", env!("CARGO_PKG_VERSION"));
+/// A new subscription only bookmarks this many of the most recent entries,
+/// the rest of the feed history is marked as seen.
+const FIRST_POLL_MAX_ENTRIES: usize = 10;
+
+/// Polls every feed due, returns the number of bookmark tasks created.
+pub async fn poll_due(pool: &PgPool, http: &HttpClient) -> Result<usize> {
+    let mut created = 0;
+    loop {
+        let feeds = db::feed::peek_due(pool, Utc::now()).await?;
+        if feeds.is_empty() {
+            break;
+        }
+        for feed in feeds {
+            created += poll(pool, http, &feed).await?;
+        }
+    }
+    Ok(created)
+}
+
+#[instrument(skip(pool, http, feed), fields(feed_id = %feed.feed_id, url = %feed.url))]
+async fn poll(pool: &PgPool, http: &HttpClient, feed: &FeedSubscription) -> Result<usize> {
+    let (outcome, parsed) = match fetch(http, feed).await {
+        Ok(FetchResult::NotModified) => {
+            tracing::info!("Feed not modified");
+            (PollOutcome::NotModified { status: 304 }, None)
+        }
+        Ok(FetchResult::Fetched {
+            feed: parsed,
+            etag,
+            last_modified,
+        }) => (
+            PollOutcome::Fetched {
+                status: 200,
+                title: parsed.title.as_ref().map(|t| t.content.clone()),
+                etag,
+                last_modified,
+            },
+            Some(parsed),
+        ),
+        Err(error) => {
+            tracing::warn!(?error, "Fail to poll feed");
+            let status = error
+                .downcast_ref::<reqwest::Error>()
+                .and_then(|e| e.status())
+                .map(|s| s.as_u16() as i16);
+            let outcome = PollOutcome::Failed {
+                status,
+                error: format!("{error:#}"),
+            };
+            (outcome, None)
+        }
+    };
+
+    let mut created = 0;
+    if let Some(parsed) = parsed {
+        let first_poll = !db::feed::has_entries(pool, feed.feed_id).await?;
+        let entries = new_entries(feed, parsed.entries, first_poll);
+        let tags = db::settings::tag_policy(pool, feed.user_id)
+            .await?
+            .normalize(&feed.tags);
+        created = db::feed::record_entries(pool, feed, &tags, &entries).await?;
+        tracing::info!(entries = entries.len(), created, "Feed polled");
+    }
+    db::feed::record_poll(pool, feed.feed_id, &outcome).await?;
+    Ok(created)
+}
+
+enum FetchResult {
+    NotModified,
+    Fetched {
+        feed: Box<Feed>,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+/// Conditional GET using the validators stored from the previous poll.
+async fn fetch(http: &HttpClient, feed: &FeedSubscription) -> Result<FetchResult> {
+    let mut request = http
+        .get(&feed.url)
+        .timeout(FEED_FETCH_TIMEOUT)
+        .header(USER_AGENT, FEED_USER_AGENT);
+    if let Some(etag) = &feed.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &feed.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    let response = request.send().await?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(FetchResult::NotModified);
+    }
+    let response = response.error_for_status()?;
+    if response.content_length().unwrap_or(0) as usize > FEED_MAX_SIZE {
+        bail!("feed larger than {FEED_MAX_SIZE} bytes");
+    }
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+    let bytes = response.bytes().await?;
+    if bytes.len() > FEED_MAX_SIZE {
+        bail!("feed larger than {FEED_MAX_SIZE} bytes");
+    }
+    let parsed = feed_rs::parser::Builder::new()
+        .base_uri(Some(&feed.url))
+        .build()
+        .parse(bytes.as_ref())?;
+    Ok(FetchResult::Fetched {
+        feed: Box::new(parsed),
+        etag,
+        last_modified,
+    })
+}
+
+fn new_entries(
+    feed: &FeedSubscription,
+    mut entries: Vec<Entry>,
+    first_poll: bool,
+) -> Vec<NewFeedEntry> {
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.published.or(entry.updated)));
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let url = entry_url(&entry)?;
+            let title = entry.title.map(|t| t.content);
+            Some(NewFeedEntry {
+                muted: feed.mutes(&url, title.as_deref()),
+                entry_key: entry.id,
+                url,
+                title,
+                published_at: entry.published.or(entry.updated),
+                backlog: false,
+            })
+        })
+        .enumerate()
+        .map(|(index, mut entry)| {
+            entry.backlog = first_poll && index >= FIRST_POLL_MAX_ENTRIES;
+            entry
+        })
+        .collect()
+}
+
+/// The entry's own page: the `alternate` link, or the first link given.
+fn entry_url(entry: &Entry) -> Option<String> {
+    let link = entry
+        .links
+        .iter()
+        .find(|link| matches!(link.rel.as_deref(), None | Some("alternate")))
+        .or_else(|| entry.links.first())?;
+    let url = Url::parse(link.href.trim()).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
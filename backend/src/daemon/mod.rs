@@ -3,8 +3,11 @@ use murmur3::murmur3_x64_128;
 use std::io::Cursor;
 use url::Url;
 
+mod embeddings;
+mod feeds;
 mod processor;
 mod runner;
+mod suggestions;
 
 pub use self::runner::run;
 
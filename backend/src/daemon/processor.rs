@@ -3,6 +3,7 @@ use chrono::Utc;
 use futures::future::join_all;
 use lol_html::{element, rewrite_str, RewriteStrSettings};
 use reqwest::Client;
+use scraper::{Html, Selector};
 use std::collections::HashMap;
 use tracing::instrument;
 use url::Url;
@@ -10,6 +11,12 @@ use url::Url;
 use crate::db::bookmark::Bookmark;
 use crate::readability;
 
+/// Pages stuffing their meta tags only get their first keywords kept.
+const MAX_META_KEYWORDS: usize = 20;
+const WORDS_PER_MINUTE: usize = 200;
+/// Time spent looking at each image of the article.
+const SECONDS_PER_IMAGE: usize = 12;
+
 #[derive(Debug)]
 #[allow(dead_code)] // FIXME
 pub struct Image {
@@ -36,7 +43,9 @@ pub async fn process_url(
     let original_url = Url::parse(original_url_str)?;
     let original_url = super::clean_url(original_url)?;
     let bookmark_id: String = super::make_bookmark_id(&original_url)?;
-    let raw_html = fetch_html_content(http, &original_url).await?;
+    let (raw_html, content_type) = fetch_html_content(http, &original_url).await?;
+    let keywords = meta_keywords(&raw_html);
+    let raw_length = raw_html.chars().count();
     let readability_response = readability::process(http, readability_url, raw_html).await?;
 
     let images_found = find_images(&original_url, &readability_response.content)?;
@@ -70,18 +79,56 @@ pub async fn process_url(
     let (new_content, images) =
         rewrite_images(&bookmark_id, &readability_response.content, images_index).await?;
 
+    let stats = ContentStats::compute(
+        &readability_response.text_content,
+        images_found.len(),
+        raw_length,
+    );
+
     let bookmark = Bookmark {
         bookmark_id,
         url: original_url.to_string(),
         domain: super::domain_from_url(&original_url)?,
         title: readability_response.title,
         text_content: readability_response.text_content,
+        keywords,
+        content_type,
+        word_count: stats.word_count,
+        reading_time: stats.reading_time,
+        image_count: Some(stats.image_count),
+        extraction_ratio: stats.extraction_ratio,
         created_at: Utc::now(),
     };
 
     Ok((bookmark, images, new_content))
 }
 
+#[derive(Debug)]
+struct ContentStats {
+    word_count: i32,
+    /// In minutes, rounded up.
+    reading_time: i32,
+    image_count: i32,
+    extraction_ratio: Option<f32>,
+}
+
+impl ContentStats {
+    /// `raw_length` is the length in characters of the page before extraction.
+    fn compute(text: &str, image_count: usize, raw_length: usize) -> Self {
+        let words = text.split_whitespace().count();
+        // Rounded up as the backfill of `16_content_stats.sql`.
+        let seconds = (words * 60).div_ceil(WORDS_PER_MINUTE) + image_count * SECONDS_PER_IMAGE;
+        let extraction_ratio =
+            (raw_length > 0).then(|| (text.chars().count() as f32 / raw_length as f32).min(1.0));
+        ContentStats {
+            word_count: i32::try_from(words).unwrap_or(i32::MAX),
+            reading_time: i32::try_from(seconds.div_ceil(60)).unwrap_or(i32::MAX),
+            image_count: i32::try_from(image_count).unwrap_or(i32::MAX),
+            extraction_ratio,
+        }
+    }
+}
+
 #[instrument(skip(content, images_found))]
 async fn rewrite_images(
     bookmark_id: &str,
@@ -189,7 +236,41 @@ fn find_images(base_url: &Url, content: &str) -> Result<Vec<ImageFound>> {
     Ok(images_found)
 }
 
+/// The keywords of the `keywords` and `news_keywords` meta tags, and of the
+/// `article:tag` properties.
+fn meta_keywords(raw_html: &str) -> Vec<String> {
+    let document = Html::parse_document(raw_html);
+    let selector = Selector::parse("meta[content]").expect("valid selector");
+    let mut keywords: Vec<String> = Vec::new();
+    for meta in document.select(&selector) {
+        let element = meta.value();
+        let content = element.attr("content").unwrap_or_default();
+        let name = element.attr("name").unwrap_or_default().to_lowercase();
+        let values: Vec<&str> = match (name.as_str(), element.attr("property")) {
+            ("keywords" | "news_keywords", _) => content.split(',').collect(),
+            (_, Some("article:tag")) => vec![content],
+            _ => continue,
+        };
+        for value in values.into_iter().map(str::trim).filter(|v| !v.is_empty()) {
+            let seen = keywords.iter().any(|k| k.eq_ignore_ascii_case(value));
+            if keywords.len() < MAX_META_KEYWORDS && !seen {
+                keywords.push(value.to_owned());
+            }
+        }
+    }
+    keywords
+}
+
+/// The content of the page, with its media type when the response has one.
 #[instrument(skip(client))]
-async fn fetch_html_content(client: &Client, url: &Url) -> Result<String> {
-    Ok(client.get(url.to_string()).send().await?.text().await?)
+async fn fetch_html_content(client: &Client, url: &Url) -> Result<(String, Option<String>)> {
+    let response = client.get(url.to_string()).send().await?;
+    let content_type = response
+        .headers()
+        .get("Content-Type")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| !v.is_empty());
+    Ok((response.text().await?, content_type))
 }
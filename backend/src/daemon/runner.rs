@@ -1,19 +1,22 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use chrono::Utc;
 use reqwest::Client as HttpClient;
 use tracing::instrument;
+use uuid::Uuid;
 
 use super::processor::Image;
-use crate::daemon::processor;
+use crate::daemon::{embeddings, feeds, processor, suggestions};
 use crate::db::{
     self,
     bookmark::Bookmark,
     task::{Task, TaskStatus},
     PgPool,
 };
-use crate::Config;
+use crate::embedding::Embedder;
+use crate::{language, Config};
 
 const DAEMON_IDLE_SLEEP: Duration = Duration::from_secs(300);
 
@@ -21,6 +24,7 @@ const DAEMON_IDLE_SLEEP: Duration = Duration::from_secs(300);
 pub async fn run(
     pool: &PgPool,
     config: &Config,
+    embedder: Option<Arc<Embedder>>,
     mut rx: tokio::sync::watch::Receiver<()>,
 ) -> Result<()> {
     let http: HttpClient = HttpClient::new();
@@ -29,13 +33,13 @@ pub async fn run(
         tokio::select! {
             _ = rx.changed() => {
                 tracing::info!("Notification receive, executing...");
-                if let Err(error) = execute_step(pool, &http, config).await {
+                if let Err(error) = execute_steps(pool, &http, config, embedder.as_ref()).await {
                     tracing::error!(?error, "Fail to process tasks");
                 }
             }
             _ = interval.tick() => {
                 tracing::info!("{DAEMON_IDLE_SLEEP:?} passed, executing...");
-                if let Err(error) = execute_step(pool, &http, config).await {
+                if let Err(error) = execute_steps(pool, &http, config, embedder.as_ref()).await {
                     tracing::error!(?error, "Fail to process tasks");
                 }
             }
@@ -43,13 +47,42 @@ pub async fn run(
     }
 }
 
-async fn execute_step(pool: &PgPool, http: &HttpClient, config: &Config) -> Result<()> {
+/// Imports can queue thousands of tasks at once, keep peeking until there is
+/// nothing ready instead of waiting for the next tick. Failed tasks are pushed
+/// to their next delivery window by `peek`, so this always terminates.
+///
+/// Feeds due for polling go first, so their new entries are processed in the
+/// same run. Embeddings come last, one batch at a time so new tasks don't wait
+/// for a whole backfill.
+async fn execute_steps(
+    pool: &PgPool,
+    http: &HttpClient,
+    config: &Config,
+    embedder: Option<&Arc<Embedder>>,
+) -> Result<()> {
+    match feeds::poll_due(pool, http).await {
+        Ok(created) => tracing::info!("Feeds polled, new tasks: {created}"),
+        Err(error) => tracing::error!(?error, "Fail to poll feeds"),
+    }
+    loop {
+        while execute_step(pool, http, config).await? > 0 {}
+        let Some(embedder) = embedder else {
+            return Ok(());
+        };
+        if embeddings::embed_pending(pool, embedder).await? == 0 {
+            return Ok(());
+        }
+    }
+}
+
+async fn execute_step(pool: &PgPool, http: &HttpClient, config: &Config) -> Result<usize> {
     let tasks: Vec<Task> = db::task::peek(pool, Utc::now()).await?;
     if tasks.is_empty() {
         tracing::info!("No new task");
-        return Ok(());
+        return Ok(0);
     }
-    tracing::info!("New tasks found: {}", tasks.len());
+    let tasks_count = tasks.len();
+    tracing::info!("New tasks found: {tasks_count}");
     for task in tasks {
         tracing::info!(?task, "Executing task");
         match handle_task(pool, http, config, &task).await {
@@ -83,24 +116,56 @@ async fn execute_step(pool: &PgPool, http: &HttpClient, config: &Config) -> Resu
             }
         }
     }
-    Ok(())
+    Ok(tasks_count)
 }
 
 #[instrument(skip(pool, http, config))]
 async fn handle_task(pool: &PgPool, http: &HttpClient, config: &Config, task: &Task) -> Result<()> {
     let bookmark = crease_or_retrieve_bookmark(pool, http, config, &task.url).await?;
-    let uuid =
-        db::bookmark::upsert_user_bookmark(pool, &bookmark.bookmark_id, task.user_id, &task.tags)
-            .await?;
+    let uuid = db::bookmark::upsert_user_bookmark(
+        pool,
+        &bookmark.bookmark_id,
+        task.user_id,
+        &task.tags,
+        task.user_created_at,
+    )
+    .await?;
     tracing::info!(
         user_id = format!("{}", task.user_id),
         bookmark_user_id = format!("{uuid}"),
         bookmark_id = &bookmark.bookmark_id,
         "Creating a new bookmark and bound with user",
     );
+    // Rules and suggestions are a bonus, they don't fail the task.
+    match apply_rules(pool, task.user_id, &bookmark.bookmark_id).await {
+        Ok(count) => tracing::info!(count, "Rules applied"),
+        Err(error) => tracing::warn!(?error, "Fail to apply rules"),
+    }
+    match suggestions::suggest(pool, task.user_id, uuid, &bookmark).await {
+        Ok(count) => tracing::info!(count, "Tags suggested"),
+        Err(error) => tracing::warn!(?error, "Fail to suggest tags"),
+    }
     Ok(())
 }
 
+/// Applies the user's enabled rules to the bookmark, in order. Returns the
+/// number of rules which changed it.
+#[instrument(skip(pool))]
+async fn apply_rules(pool: &PgPool, user_id: Uuid, bookmark_id: &str) -> Result<usize> {
+    let policy = db::settings::tag_policy(pool, user_id).await?;
+    let mut applied = 0;
+    for rule in db::rule::get_by_user(pool, user_id).await? {
+        if !rule.enabled {
+            continue;
+        }
+        let definition = rule.definition()?;
+        if db::rule::apply(pool, user_id, &definition, &policy, Some(bookmark_id)).await? > 0 {
+            applied += 1;
+        }
+    }
+    Ok(applied)
+}
+
 #[instrument(skip(pool, http, config))]
 async fn crease_or_retrieve_bookmark(
     pool: &PgPool,
@@ -121,12 +186,15 @@ async fn crease_or_retrieve_bookmark(
                 .with_context(|| {
                     format!("save_static_content: bookmark_id={}", &bookmark.bookmark_id)
                 })?;
-            db::bookmark::save(pool, &bookmark).await.with_context(|| {
-                format!(
-                    "save_bookmark_into_database: bookmark_id={}",
-                    &bookmark.bookmark_id
-                )
-            })?;
+            let language = language::detect(&bookmark.title, &bookmark.text_content);
+            db::bookmark::save(pool, &bookmark, language)
+                .await
+                .with_context(|| {
+                    format!(
+                        "save_bookmark_into_database: bookmark_id={}",
+                        &bookmark.bookmark_id
+                    )
+                })?;
             tracing::info!(
                 url = url,
                 bookmark_id = format!("{}", &bookmark.bookmark_id),
@@ -150,6 +218,10 @@ async fn save_static_content(
     }
     let index = bookmark_dir.join("index.html");
     tokio::fs::write(&index, content).await?;
+    match crate::export::html_to_markdown(content, &bookmark.bookmark_id, "") {
+        Ok(markdown) => tokio::fs::write(bookmark_dir.join("index.md"), markdown).await?,
+        Err(error) => tracing::warn!(?error, "Fail to convert content to markdown"),
+    }
     for image in images.iter() {
         let image_path = bookmark_dir.join(&image.id);
         if image_path.exists() {
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use strum_macros::IntoStaticStr;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::db::{
+    self,
+    bookmark::{Bookmark, TagOperation},
+    tag_suggestion::{NewTagSuggestion, SuggestionStatus},
+    PgPool,
+};
+
+const DOMAIN_TAGS: i64 = 10;
+const KEYWORDS: i64 = 5;
+const KEYWORD_MIN_OCCURRENCES: i64 = 3;
+const META_CONFIDENCE: f32 = 0.6;
+/// The confidence of the most frequent keyword, the others get a share of it.
+const KEYWORD_CONFIDENCE: f32 = 0.4;
+/// Added to a keyword the user already tags with.
+const VOCABULARY_BOOST: f32 = 0.3;
+const MAX_SUGGESTIONS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+enum Source {
+    /// Tagged on the user's other bookmarks of the same domain.
+    Domain,
+    /// Frequent in the text content.
+    Keyword,
+    /// Declared by the page.
+    Meta,
+}
+
+#[derive(Debug, Default)]
+struct Candidate {
+    confidence: f32,
+    sources: Vec<Source>,
+}
+
+impl Candidate {
+    /// Each source is independent evidence for the tag.
+    fn add(&mut self, source: Source, confidence: f32) {
+        self.confidence = 1.0 - (1.0 - self.confidence) * (1.0 - confidence.clamp(0.0, 1.0));
+        if !self.sources.contains(&source) {
+            self.sources.push(source);
+        }
+    }
+}
+
+/// Suggests tags for the user's new bookmark, besides the ones it has, the
+/// ones above the user's threshold are applied right away. Returns the number
+/// of suggestions.
+#[instrument(skip(pool, bookmark), fields(bookmark_id = %bookmark.bookmark_id))]
+pub async fn suggest(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_user_id: Uuid,
+    bookmark: &Bookmark,
+) -> Result<usize> {
+    let tags = db::bookmark::get_with_user_data(pool, user_id, &bookmark.bookmark_id)
+        .await?
+        .and_then(|b| b.tags)
+        .unwrap_or_default();
+    let settings = db::settings::get(pool, user_id).await?;
+    let policy = db::settings::tag_policy(pool, user_id).await?;
+    // Keywords are matched case insensitively with the user's own spelling.
+    let vocabulary: HashMap<String, String> = db::bookmark::get_tag_count_by_user(pool, user_id)
+        .await?
+        .into_iter()
+        .map(|(tag, _)| (tag.to_lowercase(), tag))
+        .collect();
+
+    let mut raw: Vec<(String, Source, f32)> = Vec::new();
+    for (tag, share) in
+        db::tag_suggestion::domain_tags(pool, user_id, &bookmark.bookmark_id, DOMAIN_TAGS).await?
+    {
+        raw.push((tag, Source::Domain, share));
+    }
+    for keyword in &bookmark.keywords {
+        raw.push((keyword.clone(), Source::Meta, META_CONFIDENCE));
+    }
+    let keywords = db::tag_suggestion::keywords(
+        pool,
+        &bookmark.bookmark_id,
+        KEYWORD_MIN_OCCURRENCES,
+        KEYWORDS,
+    )
+    .await?;
+    let most_frequent = keywords.first().map(|(_, n)| *n).unwrap_or(1) as f32;
+    for (keyword, occurrences) in keywords {
+        let confidence = KEYWORD_CONFIDENCE * occurrences as f32 / most_frequent;
+        raw.push((keyword, Source::Keyword, confidence));
+    }
+
+    // Keyed case insensitively, the first spelling seen is kept, the user's
+    // own one first.
+    let mut candidates: HashMap<String, (String, Candidate)> = HashMap::new();
+    for (tag, source, mut confidence) in raw {
+        let tag = match vocabulary.get(&tag.to_lowercase()) {
+            Some(known) => {
+                if source != Source::Domain {
+                    confidence += VOCABULARY_BOOST;
+                }
+                known.clone()
+            }
+            None => tag,
+        };
+        if let Some(tag) = policy.normalize_tag(&tag) {
+            candidates
+                .entry(tag.to_lowercase())
+                .or_insert_with(|| (tag, Candidate::default()))
+                .1
+                .add(source, confidence);
+        }
+    }
+    let suggested = db::tag_suggestion::get_suggested(pool, bookmark_user_id).await?;
+    let mut candidates: Vec<(String, Candidate)> = candidates
+        .into_values()
+        .filter(|(tag, _)| !tags.contains(tag) && !suggested.contains(tag))
+        .collect();
+    candidates.sort_by(|(a, x), (b, y)| y.confidence.total_cmp(&x.confidence).then(a.cmp(b)));
+    candidates.truncate(MAX_SUGGESTIONS);
+
+    let threshold = settings.tag_suggestion_threshold;
+    let suggestions: Vec<NewTagSuggestion> = candidates
+        .into_iter()
+        .map(|(tag, candidate)| NewTagSuggestion {
+            status: match threshold {
+                Some(threshold) if candidate.confidence >= threshold => SuggestionStatus::Accepted,
+                _ => SuggestionStatus::Pending,
+            },
+            tag,
+            confidence: candidate.confidence,
+            sources: candidate
+                .sources
+                .into_iter()
+                .map(|s| <&str>::from(s).to_owned())
+                .collect(),
+        })
+        .collect();
+    db::tag_suggestion::save(pool, bookmark_user_id, &suggestions).await?;
+    let applied: Vec<String> = suggestions
+        .iter()
+        .filter(|s| s.status == SuggestionStatus::Accepted)
+        .map(|s| s.tag.clone())
+        .collect();
+    if !applied.is_empty() {
+        db::bookmark::update_tags(
+            pool,
+            user_id,
+            &bookmark.bookmark_id,
+            &TagOperation::Append(applied),
+        )
+        .await?;
+    }
+    Ok(suggestions.len())
+}
@@ -6,8 +6,11 @@ use tracing::{info, instrument};
 use uuid::Uuid;
 
 use crate::error::{Error, Result};
+use crate::language::Language;
+use crate::tag::{TagPolicy, SEPARATOR};
 
-use super::PgPool;
+use super::pagination::{self, Cursor, Sort};
+use super::{PgPool, SqlParams};
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Bookmark {
@@ -16,6 +19,16 @@ pub struct Bookmark {
     pub domain: String,
     pub title: String,
     pub text_content: String,
+    /// Declared by the page in its meta tags.
+    pub keywords: Vec<String>,
+    /// The media type the page was served with.
+    pub content_type: Option<String>,
+    pub word_count: i32,
+    /// In minutes.
+    pub reading_time: i32,
+    pub image_count: Option<i32>,
+    /// The share of the raw HTML kept as text.
+    pub extraction_ratio: Option<f32>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -28,14 +41,87 @@ pub struct BookmarkWithUser {
     pub created_at: DateTime<Utc>,
     pub user_id: Option<Uuid>,
     pub tags: Option<Vec<String>>,
+    /// The user's private note.
+    pub note: Option<String>,
+    pub word_count: i32,
+    /// In minutes.
+    pub reading_time: i32,
+    pub image_count: Option<i32>,
+    pub extraction_ratio: Option<f32>,
+    pub favorite: bool,
+    pub archived: bool,
+    /// `None` while unread.
+    pub read_at: Option<DateTime<Utc>>,
+    /// The share of the page read, from 0 to 1.
+    pub progress: f32,
     pub user_created_at: Option<DateTime<Utc>>,
     pub user_updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone)]
+/// A bookmark similar to another one, `score` ranks it.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct RelatedBookmark {
+    #[from_row(flatten)]
+    #[serde(flatten)]
+    pub bookmark: BookmarkWithUser,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BookmarkPage {
+    pub bookmarks: Vec<BookmarkWithUser>,
+    pub total: u64,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub enum TagOperation {
     Set(Vec<String>),
     Append(Vec<String>),
+    Remove(Vec<String>),
+}
+
+impl TagOperation {
+    pub fn normalize(self, policy: &TagPolicy) -> Self {
+        match self {
+            TagOperation::Set(tags) => TagOperation::Set(policy.normalize(&tags)),
+            TagOperation::Append(tags) => TagOperation::Append(policy.normalize(&tags)),
+            // Removed tags are matched as stored, not as they would be saved now.
+            TagOperation::Remove(tags) => TagOperation::Remove(
+                tags.iter()
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn tags(&self) -> &[String] {
+        match self {
+            TagOperation::Set(tags) | TagOperation::Append(tags) | TagOperation::Remove(tags) => {
+                tags
+            }
+        }
+    }
+
+    /// The new value of the `tags` column, without duplicates, `tags` is the
+    /// placeholder of `self.tags()`.
+    pub(crate) fn expression(&self, tags: &str) -> String {
+        match self {
+            TagOperation::Set(_) => distinct_tags(&format!("{tags}::text[]")),
+            TagOperation::Append(_) => distinct_tags(&format!("array_cat(tags, {tags}::text[])")),
+            TagOperation::Remove(_) => distinct_tags(&format!(
+                "ARRAY(SELECT t FROM unnest(tags) AS t WHERE t <> ALL({tags}::text[]))"
+            )),
+        }
+    }
+}
+
+/// The tags of `array` without duplicates, in their original order.
+fn distinct_tags(array: &str) -> String {
+    format!(
+        "ARRAY(SELECT t FROM unnest({array}) WITH ORDINALITY AS u(t, i) GROUP BY t ORDER BY min(i))"
+    )
 }
 
 #[instrument(skip(pool))]
@@ -68,6 +154,11 @@ pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<BookmarkWit
         b.*,
         bu.user_id,
         bu.tags,
+        bu.note,
+        bu.favorite,
+        bu.archived,
+        bu.read_at,
+        bu.progress,
         bu.created_at as user_created_at,
         bu.updated_at as user_updated_at
     FROM bookmark_user bu
@@ -84,23 +175,218 @@ pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<BookmarkWit
     Ok(results)
 }
 
+/// One page of a listing, optionally only the bookmarks tagged `tag`.
+#[instrument(skip(pool))]
+pub async fn get_page(
+    pool: &PgPool,
+    user_id: Uuid,
+    tag: Option<&str>,
+    sort: Sort,
+    cursor: Option<&Cursor>,
+    limit: i64,
+) -> Result<BookmarkPage> {
+    let sort = sort.effective(false);
+    let mut params = SqlParams::default();
+    let user = params.push(user_id);
+    let mut filter = format!("bu.user_id = {user}");
+    if let Some(tag) = tag {
+        filter.push_str(&format!(
+            " AND bu.tags @> ARRAY[{}::text]",
+            params.push(tag.to_owned())
+        ));
+    }
+    let total_sql = format!("SELECT COUNT(1) FROM bookmark_user bu WHERE {filter}");
+    let inner = format!(
+        r#"
+    SELECT
+        b.*,
+        bu.user_id,
+        bu.tags,
+        bu.note,
+        bu.favorite,
+        bu.archived,
+        bu.read_at,
+        bu.progress,
+        bu.created_at as user_created_at,
+        bu.updated_at as user_updated_at,
+        {sort_key} AS sort_key
+    FROM bookmark_user bu
+    INNER JOIN bookmark b USING(bookmark_id)
+    WHERE {filter}"#,
+        sort_key = sort.key_expression(None),
+    );
+    let client = pool.get().await?;
+    let total: i64 = client
+        .query_one(&total_sql, &params.as_refs())
+        .await?
+        .try_get(0)?;
+    let sql = pagination::paginate(&inner, "r.*", sort, cursor, limit, &mut params)?;
+    let rows = client.query(&sql, &params.as_refs()).await?;
+    let (bookmarks, next_cursor) = pagination::split_page(rows, sort, limit, |row| {
+        BookmarkWithUser::try_from_row(row).map_err(Error::from)
+    })?;
+    Ok(BookmarkPage {
+        bookmarks,
+        total: total as u64,
+        next_cursor,
+    })
+}
+
+/// The user's other bookmarks most similar to `bookmark_id`, by the overlap
+/// with its most frequent terms (title terms count more), shared tags and
+/// domain. `None` when the user doesn't have `bookmark_id`.
+#[instrument(skip(pool))]
+pub async fn get_related(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+    limit: i64,
+) -> Result<Option<Vec<RelatedBookmark>>> {
+    const SOURCE: &str = "SELECT 1 FROM bookmark_user WHERE user_id = $1 AND bookmark_id = $2;";
+    const SQL: &str = r#"
+    WITH source AS (
+        SELECT b.bookmark_id, b.domain, bu.tags, b.search_tokens
+        FROM bookmark_user bu
+        INNER JOIN bookmark b USING(bookmark_id)
+        WHERE bu.user_id = $1 AND bu.bookmark_id = $2
+    ), terms AS (
+        -- Quoted for the tsquery syntax, `quote_literal` would give `E'...'`
+        -- for lexemes with a backslash.
+        SELECT string_agg(
+            '''' || replace(replace(t.lexeme, '\', '\\'), '''', '''''') || '''', ' | '
+        )::tsquery AS query
+        FROM (
+            SELECT t.lexeme
+            FROM source, unnest(source.search_tokens) t
+            ORDER BY
+                coalesce(array_length(t.positions, 1), 1)
+                    + CASE WHEN 'A' = ANY(t.weights) THEN 3 ELSE 0 END DESC,
+                t.lexeme
+            LIMIT $4
+        ) t
+    )
+    SELECT
+        b.*,
+        bu.user_id,
+        bu.tags,
+        bu.note,
+        bu.favorite,
+        bu.archived,
+        bu.read_at,
+        bu.progress,
+        bu.created_at as user_created_at,
+        bu.updated_at as user_updated_at,
+        (
+            coalesce(ts_rank(b.search_tokens, terms.query), 0)
+            + $5::real * cardinality(ARRAY(
+                SELECT unnest(bu.tags) INTERSECT SELECT unnest(source.tags)
+            ))
+            + CASE WHEN b.domain = source.domain THEN $6::real ELSE 0 END
+        )::real AS score
+    FROM source, terms, bookmark_user bu
+    INNER JOIN bookmark b USING(bookmark_id)
+    WHERE bu.user_id = $1
+    AND b.bookmark_id <> source.bookmark_id
+    AND (
+        b.search_tokens @@ terms.query
+        OR bu.tags && source.tags
+        OR b.domain = source.domain
+    )
+    ORDER BY score DESC, b.bookmark_id
+    LIMIT $3;"#;
+    const TERMS: i64 = 24;
+    const TAG_WEIGHT: f32 = 0.1;
+    const DOMAIN_WEIGHT: f32 = 0.05;
+    let client = pool.get().await?;
+    if client
+        .query_opt(SOURCE, &[&user_id, &bookmark_id])
+        .await?
+        .is_none()
+    {
+        return Ok(None);
+    }
+    let results = client
+        .query(
+            SQL,
+            &[
+                &user_id,
+                &bookmark_id,
+                &limit,
+                &TERMS,
+                &TAG_WEIGHT,
+                &DOMAIN_WEIGHT,
+            ],
+        )
+        .await?
+        .iter()
+        .map(|row| RelatedBookmark::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(results))
+}
+
+/// Most recently saved bookmarks first, optionally only those tagged `tag`.
+#[instrument(skip(pool))]
+pub async fn get_recent(
+    pool: &PgPool,
+    user_id: Uuid,
+    tag: Option<&str>,
+    limit: i64,
+) -> Result<Vec<BookmarkWithUser>> {
+    const SQL: &str = r#"
+    SELECT
+        b.*,
+        bu.user_id,
+        bu.tags,
+        bu.note,
+        bu.favorite,
+        bu.archived,
+        bu.read_at,
+        bu.progress,
+        bu.created_at as user_created_at,
+        bu.updated_at as user_updated_at
+    FROM bookmark_user bu
+    INNER JOIN bookmark b USING(bookmark_id)
+    WHERE bu.user_id = $1
+    AND ($2::text IS NULL OR bu.tags @> ARRAY[$2::text])
+    ORDER BY bu.created_at DESC
+    LIMIT $3;"#;
+    let client = pool.get().await?;
+    let results = client
+        .query(SQL, &[&user_id, &tag, &limit])
+        .await?
+        .iter()
+        .map(|row| BookmarkWithUser::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(results)
+}
+
+/// Fetches the caller's bookmarks for the given ids, in the same order.
 #[instrument(skip(pool))]
-pub async fn get_by_tag(pool: &PgPool, user_id: Uuid, tag: &str) -> Result<Vec<BookmarkWithUser>> {
+pub async fn get_by_ids(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_ids: &[String],
+) -> Result<Vec<BookmarkWithUser>> {
     const SQL: &str = r#"
     SELECT
         b.*,
         bu.user_id,
         bu.tags,
+        bu.note,
+        bu.favorite,
+        bu.archived,
+        bu.read_at,
+        bu.progress,
         bu.created_at as user_created_at,
         bu.updated_at as user_updated_at
     FROM bookmark_user bu
     INNER JOIN bookmark b USING(bookmark_id)
     WHERE bu.user_id = $1
-    AND bu.tags @> $2
-    ORDER BY bu.created_at ASC;"#;
+    AND bookmark_id = ANY($2::text[])
+    ORDER BY array_position($2::text[], bookmark_id::text);"#;
     let client = pool.get().await?;
     let results = client
-        .query(SQL, &[&user_id, &[&tag]])
+        .query(SQL, &[&user_id, &bookmark_ids])
         .await?
         .iter()
         .map(|row| BookmarkWithUser::try_from_row(row).map_err(Error::from))
@@ -120,6 +406,18 @@ pub async fn get_by_url(pool: &PgPool, url: &str) -> Result<Option<Bookmark>> {
     Ok(result)
 }
 
+#[instrument(skip(pool))]
+pub async fn get_by_id(pool: &PgPool, bookmark_id: &str) -> Result<Option<Bookmark>> {
+    const SQL: &str = "SELECT * FROM bookmark WHERE bookmark_id = $1;";
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(SQL, &[&bookmark_id])
+        .await?
+        .map(|row| Bookmark::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
 #[instrument(skip(pool))]
 pub async fn get_with_user_data(
     pool: &PgPool,
@@ -131,6 +429,11 @@ pub async fn get_with_user_data(
         b.*,
         bu.user_id,
         bu.tags,
+        bu.note,
+        bu.favorite,
+        bu.archived,
+        bu.read_at,
+        bu.progress,
         bu.created_at as user_created_at,
         bu.updated_at as user_updated_at
     FROM bookmark_user bu
@@ -153,15 +456,11 @@ pub async fn update_tags(
     bookmark_id: &str,
     operation: &TagOperation,
 ) -> Result<BookmarkWithUser> {
-    let (update_tag_sql, tags) = match operation.clone() {
-        TagOperation::Set(tags) => ("tags=$1", tags),
-        TagOperation::Append(tags) => ("tags=array_cat(tags, $1)", tags),
-    };
     let sql = format!(
         r#"
         WITH update_bookmark_user AS (
             UPDATE bookmark_user
-            SET {update_tag_sql}, updated_at=now()
+            SET tags={}, updated_at=now()
             WHERE bookmark_id=$2 AND user_id=$3
             RETURNING *
         )
@@ -169,37 +468,215 @@ pub async fn update_tags(
             b.*,
             bi.user_id,
             bi.tags,
+            bi.note,
+            bi.favorite,
+            bi.archived,
+            bi.read_at,
+            bi.progress,
             bi.created_at as user_created_at,
             bi.updated_at as user_updated_at
         FROM update_bookmark_user bi
-        INNER JOIN bookmark b using(bookmark_id);"#
+        INNER JOIN bookmark b using(bookmark_id);"#,
+        operation.expression("$1")
     );
     let client = pool.get().await?;
     let row = client
-        .query_one(&sql, &[&tags, &bookmark_id, &user_id])
+        .query_one(&sql, &[&operation.tags(), &bookmark_id, &user_id])
         .await?;
     let result = BookmarkWithUser::try_from_row(&row)?;
     info!(?operation, %bookmark_id, "Updated tags for bookmark");
     Ok(result)
 }
 
+/// Applies `operation` to the bookmarks `bookmark_ids` of the user, returns
+/// the number of bookmarks updated.
+#[instrument(skip(pool))]
+pub async fn update_tags_by_ids(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_ids: &[String],
+    operation: &TagOperation,
+) -> Result<u64> {
+    let sql = format!(
+        r#"
+        UPDATE bookmark_user
+        SET tags={expression}, updated_at=now()
+        WHERE user_id=$2 AND bookmark_id = ANY($3) AND tags IS DISTINCT FROM {expression};"#,
+        expression = operation.expression("$1")
+    );
+    let client = pool.get().await?;
+    let updated = client
+        .execute(&sql, &[&operation.tags(), &user_id, &bookmark_ids])
+        .await?;
+    info!(?operation, updated, "Updated tags for bookmarks");
+    Ok(updated)
+}
+
+/// Replaces `tags` with `into` on every bookmark of the user, their
+/// descendants move under `into`. Returns the number of bookmarks updated.
+#[instrument(skip(pool))]
+pub async fn merge_tags(pool: &PgPool, user_id: Uuid, tags: &[String], into: &str) -> Result<u64> {
+    // The closest ancestor among `tags` is the one replaced.
+    let renamed = format!(
+        "coalesce((SELECT $3 || substr(t, length(f) + 1) FROM unnest($2::text[]) f
+        WHERE t = f OR starts_with(t, f || '{SEPARATOR}') ORDER BY length(f) DESC LIMIT 1), t)"
+    );
+    let sql = format!(
+        r#"
+        UPDATE bookmark_user
+        SET tags={}, updated_at=now()
+        WHERE user_id=$1 AND EXISTS (
+            SELECT 1 FROM unnest(tags) t, unnest($2::text[]) f
+            WHERE t = f OR starts_with(t, f || '{SEPARATOR}')
+        );"#,
+        distinct_tags(&format!("ARRAY(SELECT {renamed} FROM unnest(tags) AS t)"))
+    );
+    let client = pool.get().await?;
+    let updated = client.execute(&sql, &[&user_id, &tags, &into]).await?;
+    info!(updated, "Merged tags");
+    Ok(updated)
+}
+
+/// Removes `tag` from every bookmark of the user, returns the number of
+/// bookmarks updated.
+#[instrument(skip(pool))]
+pub async fn delete_tag(pool: &PgPool, user_id: Uuid, tag: &str) -> Result<u64> {
+    const SQL: &str = r#"
+    UPDATE bookmark_user
+    SET tags=array_remove(tags, $2), updated_at=now()
+    WHERE user_id=$1 AND $2 = ANY(tags);"#;
+    let client = pool.get().await?;
+    let updated = client.execute(SQL, &[&user_id, &tag]).await?;
+    info!(updated, "Deleted tag");
+    Ok(updated)
+}
+
+/// Sets the user's note, `None` removes it. `None` when the user doesn't have
+/// `bookmark_id`.
+#[instrument(skip(pool))]
+pub async fn update_note(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+    note: Option<&str>,
+) -> Result<Option<BookmarkWithUser>> {
+    const SQL: &str = r#"
+    WITH update_bookmark_user AS (
+        UPDATE bookmark_user
+        SET note=$1, updated_at=now()
+        WHERE bookmark_id=$2 AND user_id=$3
+        RETURNING *
+    )
+    SELECT
+        b.*,
+        bi.user_id,
+        bi.tags,
+        bi.note,
+        bi.favorite,
+        bi.archived,
+        bi.read_at,
+        bi.progress,
+        bi.created_at as user_created_at,
+        bi.updated_at as user_updated_at
+    FROM update_bookmark_user bi
+    INNER JOIN bookmark b using(bookmark_id);"#;
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(SQL, &[&note, &bookmark_id, &user_id])
+        .await?
+        .map(|row| BookmarkWithUser::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    info!(%bookmark_id, "Updated note for bookmark");
+    Ok(result)
+}
+
+/// Changes to the reading state of a bookmark, `None` keeps the current value.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct ReadingState {
+    pub read: Option<bool>,
+    pub favorite: Option<bool>,
+    pub archived: Option<bool>,
+    pub progress: Option<f32>,
+}
+
+/// Marking a bookmark read keeps when it was first read. The progress alone
+/// doesn't count as an update of the bookmark.
+#[instrument(skip(pool))]
+pub async fn update_reading_state(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+    state: &ReadingState,
+) -> Result<Option<BookmarkWithUser>> {
+    const SQL: &str = r#"
+    WITH update_bookmark_user AS (
+        UPDATE bookmark_user SET
+            read_at = CASE WHEN $3::boolean THEN coalesce(read_at, now())
+                WHEN NOT $3::boolean THEN NULL ELSE read_at END,
+            favorite = coalesce($4, favorite),
+            archived = coalesce($5, archived),
+            progress = coalesce($6, progress),
+            updated_at = CASE WHEN coalesce($3, $4, $5) IS NULL THEN updated_at ELSE now() END
+        WHERE bookmark_id=$1 AND user_id=$2
+        RETURNING *
+    )
+    SELECT
+        b.*,
+        bi.user_id,
+        bi.tags,
+        bi.note,
+        bi.favorite,
+        bi.archived,
+        bi.read_at,
+        bi.progress,
+        bi.created_at as user_created_at,
+        bi.updated_at as user_updated_at
+    FROM update_bookmark_user bi
+    INNER JOIN bookmark b using(bookmark_id);"#;
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(
+            SQL,
+            &[
+                &bookmark_id,
+                &user_id,
+                &state.read,
+                &state.favorite,
+                &state.archived,
+                &state.progress,
+            ],
+        )
+        .await?
+        .map(|row| BookmarkWithUser::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    info!(%bookmark_id, "Updated reading state for bookmark");
+    Ok(result)
+}
+
 #[instrument(skip(pool))]
 pub async fn upsert_user_bookmark(
     pool: &PgPool,
     bookmark_id: &str,
     user_id: Uuid,
     tags: &[String],
+    created_at: Option<DateTime<Utc>>,
 ) -> Result<Uuid> {
-    const SQL: &str = r#"
-    INSERT INTO bookmark_user
-    (bookmark_user_id, bookmark_id, user_id, tags, created_at, updated_at)
-    VALUES (uuid_generate_v4(), $1, $2, $3, now(), now())
-    ON CONFLICT ON CONSTRAINT bookmark_user_unique
-    DO UPDATE SET tags = $3, updated_at = now()
-    RETURNING bookmark_user_id;"#;
+    let sql = format!(
+        r#"
+        INSERT INTO bookmark_user
+        (bookmark_user_id, bookmark_id, user_id, tags, created_at, updated_at)
+        VALUES (uuid_generate_v4(), $1, $2, {}, coalesce($4, now()), now())
+        ON CONFLICT ON CONSTRAINT bookmark_user_unique
+        DO UPDATE SET
+            tags = EXCLUDED.tags,
+            created_at = least(bookmark_user.created_at, coalesce($4, bookmark_user.created_at)),
+            updated_at = now()
+        RETURNING bookmark_user_id;"#,
+        distinct_tags("$3::text[]")
+    );
     let client = pool.get().await?;
     let row = client
-        .query_one(SQL, &[&bookmark_id, &user_id, &tags])
+        .query_one(&sql, &[&bookmark_id, &user_id, &tags, &created_at])
         .await?;
     let uuid: Uuid = row.try_get(0)?;
     info!(?uuid, %bookmark_id, %user_id, ?tags, "Bookmark upsert");
@@ -207,11 +684,12 @@ pub async fn upsert_user_bookmark(
 }
 
 #[instrument(skip(pool))]
-pub async fn save(pool: &PgPool, bookmark: &Bookmark) -> Result<()> {
+pub async fn save(pool: &PgPool, bookmark: &Bookmark, language: Language) -> Result<()> {
     const SQL: &str = r#"
     INSERT INTO bookmark
-    (bookmark_id, url, domain, title, text_content, created_at)
-    VALUES ($1, $2, $3, $4, $5, now());"#;
+    (bookmark_id, url, domain, title, text_content, keywords, content_type, word_count,
+    reading_time, image_count, extraction_ratio, language, created_at)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12::text::regconfig, now());"#;
     let client = pool.get().await?;
     let rows_affected = client
         .execute(
@@ -222,9 +700,16 @@ pub async fn save(pool: &PgPool, bookmark: &Bookmark) -> Result<()> {
                 &bookmark.domain,
                 &bookmark.title,
                 &bookmark.text_content,
+                &bookmark.keywords,
+                &bookmark.content_type,
+                &bookmark.word_count,
+                &bookmark.reading_time,
+                &bookmark.image_count,
+                &bookmark.extraction_ratio,
+                &language.config(),
             ],
         )
         .await?;
-    info!(%rows_affected, ?bookmark, "Bookmark safe");
+    info!(%rows_affected, ?bookmark, ?language, "Bookmark safe");
     Ok(())
 }
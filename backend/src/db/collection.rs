@@ -0,0 +1,299 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use postgres_from_row::FromRow;
+use serde::Serialize;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+use super::bookmark::BookmarkWithUser;
+use super::{PgPool, ResultExt};
+
+const COLUMNS: &str = "c.collection_id, c.user_id, c.name, c.description, c.created_at, \
+    c.updated_at, (SELECT count(1) FROM collection_item ci \
+    WHERE ci.collection_id = c.collection_id) AS item_count";
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Collection {
+    pub collection_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub item_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn name_taken(_: tokio_postgres::error::DbError) -> Error {
+    Error::constraint_violation("collection_name_unique", "name already used")
+}
+
+fn already_added(_: tokio_postgres::error::DbError) -> Error {
+    Error::constraint_violation(
+        "collection_item_unique",
+        "bookmark already in the collection",
+    )
+}
+
+#[instrument(skip(pool))]
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: &str,
+    description: Option<&str>,
+) -> Result<Collection> {
+    let sql = format!(
+        "WITH c AS (
+            INSERT INTO collection (user_id, name, description)
+            VALUES ($1, $2, $3) RETURNING *
+        )
+        SELECT {COLUMNS} FROM c;"
+    );
+    let client = pool.get().await?;
+    let row = client
+        .query_one(&sql, &[&user_id, &name, &description])
+        .await
+        .on_constraint("collection_name_unique", name_taken)?;
+    let collection = Collection::try_from_row(&row)?;
+    info!(collection_id = %collection.collection_id, "Collection created");
+    Ok(collection)
+}
+
+#[instrument(skip(pool))]
+pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<Collection>> {
+    let sql = format!("SELECT {COLUMNS} FROM collection c WHERE c.user_id = $1 ORDER BY c.name;");
+    let client = pool.get().await?;
+    let result = client
+        .query(&sql, &[&user_id])
+        .await?
+        .iter()
+        .map(|row| Collection::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn get(pool: &PgPool, user_id: Uuid, collection_id: Uuid) -> Result<Option<Collection>> {
+    let sql = format!(
+        "SELECT {COLUMNS} FROM collection c WHERE c.user_id = $1 AND c.collection_id = $2;"
+    );
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(&sql, &[&user_id, &collection_id])
+        .await?
+        .map(|row| Collection::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+/// An empty `description` removes it, `None` keeps it.
+#[instrument(skip(pool))]
+pub async fn update(
+    pool: &PgPool,
+    user_id: Uuid,
+    collection_id: Uuid,
+    name: Option<&str>,
+    description: Option<&str>,
+) -> Result<Option<Collection>> {
+    let sql = format!(
+        "WITH c AS (
+            UPDATE collection SET
+                name = coalesce($3, name),
+                description = CASE WHEN $4::text IS NULL THEN description
+                    ELSE nullif($4, '') END,
+                updated_at = now()
+            WHERE user_id = $1 AND collection_id = $2
+            RETURNING *
+        )
+        SELECT {COLUMNS} FROM c;"
+    );
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(&sql, &[&user_id, &collection_id, &name, &description])
+        .await
+        .on_constraint("collection_name_unique", name_taken)?
+        .map(|row| Collection::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn delete(pool: &PgPool, user_id: Uuid, collection_id: Uuid) -> Result<bool> {
+    const SQL: &str = "DELETE FROM collection WHERE user_id = $1 AND collection_id = $2;";
+    let client = pool.get().await?;
+    let deleted = client.execute(SQL, &[&user_id, &collection_id]).await?;
+    Ok(deleted > 0)
+}
+
+/// The bookmarks of the collection, in its order.
+#[instrument(skip(pool))]
+pub async fn get_items(
+    pool: &PgPool,
+    user_id: Uuid,
+    collection_id: Uuid,
+) -> Result<Vec<BookmarkWithUser>> {
+    const SQL: &str = r#"
+    SELECT
+        b.*,
+        bu.user_id,
+        bu.tags,
+        bu.note,
+        bu.favorite,
+        bu.archived,
+        bu.read_at,
+        bu.progress,
+        bu.created_at as user_created_at,
+        bu.updated_at as user_updated_at
+    FROM collection_item ci
+    INNER JOIN collection c USING(collection_id)
+    INNER JOIN bookmark_user bu USING(bookmark_user_id)
+    INNER JOIN bookmark b USING(bookmark_id)
+    WHERE c.user_id = $1 AND c.collection_id = $2
+    ORDER BY ci.position, ci.added_at;"#;
+    let client = pool.get().await?;
+    let results = client
+        .query(SQL, &[&user_id, &collection_id])
+        .await?
+        .iter()
+        .map(|row| BookmarkWithUser::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(results)
+}
+
+/// Inserts the bookmark at `position`, or after the last item. `false` when
+/// the user has no such collection or bookmark.
+#[instrument(skip(pool))]
+pub async fn add_item(
+    pool: &PgPool,
+    user_id: Uuid,
+    collection_id: Uuid,
+    bookmark_id: &str,
+    position: Option<i32>,
+) -> Result<bool> {
+    const LOCK: &str = r#"
+    SELECT c.collection_id FROM collection c
+    WHERE c.user_id = $1 AND c.collection_id = $2 FOR UPDATE;"#;
+    const SHIFT: &str = r#"
+    UPDATE collection_item SET position = position + 1
+    WHERE collection_id = $1 AND position >= $2;"#;
+    const INSERT: &str = r#"
+    INSERT INTO collection_item (collection_id, bookmark_user_id, position)
+    SELECT $1, bu.bookmark_user_id, least(
+        coalesce($4, 2147483647),
+        (SELECT count(1) FROM collection_item WHERE collection_id = $1)
+    )
+    FROM bookmark_user bu WHERE bu.user_id = $2 AND bu.bookmark_id = $3;"#;
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    if tx
+        .query_opt(LOCK, &[&user_id, &collection_id])
+        .await?
+        .is_none()
+    {
+        return Ok(false);
+    }
+    if let Some(position) = position {
+        tx.execute(SHIFT, &[&collection_id, &position]).await?;
+    }
+    let inserted = tx
+        .execute(INSERT, &[&collection_id, &user_id, &bookmark_id, &position])
+        .await
+        .on_constraint("collection_item_unique", already_added)?;
+    if inserted == 0 {
+        return Ok(false);
+    }
+    touch(&tx, collection_id).await?;
+    tx.commit().await?;
+    info!(%collection_id, %bookmark_id, "Bookmark added to collection");
+    Ok(true)
+}
+
+/// `false` when the bookmark isn't in the user's collection.
+#[instrument(skip(pool))]
+pub async fn remove_item(
+    pool: &PgPool,
+    user_id: Uuid,
+    collection_id: Uuid,
+    bookmark_id: &str,
+) -> Result<bool> {
+    const DELETE: &str = r#"
+    DELETE FROM collection_item ci
+    USING collection c, bookmark_user bu
+    WHERE ci.collection_id = c.collection_id AND ci.bookmark_user_id = bu.bookmark_user_id
+    AND c.user_id = $1 AND c.collection_id = $2 AND bu.bookmark_id = $3
+    RETURNING ci.position;"#;
+    const SHIFT: &str = r#"
+    UPDATE collection_item SET position = position - 1
+    WHERE collection_id = $1 AND position > $2;"#;
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    let Some(row) = tx
+        .query_opt(DELETE, &[&user_id, &collection_id, &bookmark_id])
+        .await?
+    else {
+        return Ok(false);
+    };
+    let position: i32 = row.try_get(0)?;
+    tx.execute(SHIFT, &[&collection_id, &position]).await?;
+    touch(&tx, collection_id).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Orders the items as `bookmark_ids`, which must list each of them once.
+/// `false` when the user has no such collection.
+#[instrument(skip(pool))]
+pub async fn reorder(
+    pool: &PgPool,
+    user_id: Uuid,
+    collection_id: Uuid,
+    bookmark_ids: &[String],
+) -> Result<bool> {
+    const LOCK: &str = r#"
+    SELECT c.collection_id FROM collection c
+    WHERE c.user_id = $1 AND c.collection_id = $2 FOR UPDATE;"#;
+    const CURRENT: &str = r#"
+    SELECT bu.bookmark_id FROM collection_item ci
+    INNER JOIN bookmark_user bu USING(bookmark_user_id)
+    WHERE ci.collection_id = $1;"#;
+    const UPDATE: &str = r#"
+    UPDATE collection_item ci SET position = o.position - 1
+    FROM bookmark_user bu, unnest($2::text[]) WITH ORDINALITY AS o(bookmark_id, position)
+    WHERE ci.bookmark_user_id = bu.bookmark_user_id AND bu.bookmark_id = o.bookmark_id
+    AND ci.collection_id = $1;"#;
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    if tx
+        .query_opt(LOCK, &[&user_id, &collection_id])
+        .await?
+        .is_none()
+    {
+        return Ok(false);
+    }
+    let mut current = tx
+        .query(CURRENT, &[&collection_id])
+        .await?
+        .iter()
+        .map(|row| row.try_get::<_, String>(0))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut wanted = bookmark_ids.to_vec();
+    current.sort();
+    wanted.sort();
+    if current != wanted {
+        return Err(Error::bad_request([(
+            "bookmark_ids",
+            "must list every item of the collection once",
+        )]));
+    }
+    tx.execute(UPDATE, &[&collection_id, &bookmark_ids]).await?;
+    touch(&tx, collection_id).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+async fn touch(client: &impl GenericClient, collection_id: Uuid) -> Result<()> {
+    const SQL: &str = "UPDATE collection SET updated_at = now() WHERE collection_id = $1;";
+    client.execute(SQL, &[&collection_id]).await?;
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use deadpool_postgres::GenericClient;
+use pgvector::Vector;
+use postgres_from_row::FromRow;
+use tracing::{info, instrument};
+
+use crate::error::{Error, Result};
+
+use super::bookmark::Bookmark;
+use super::PgPool;
+
+/// A piece of a bookmark's text with its embedding.
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub content: String,
+    pub embedding: Vec<f32>,
+}
+
+/// Bookmarks whose chunks were not embedded yet, oldest first.
+#[instrument(skip(pool))]
+pub async fn get_pending(pool: &PgPool, limit: i64) -> Result<Vec<Bookmark>> {
+    const SQL: &str = r#"
+    SELECT * FROM bookmark WHERE embedded_at IS NULL
+    ORDER BY created_at ASC LIMIT $1;"#;
+    let client = pool.get().await?;
+    let result = client
+        .query(SQL, &[&limit])
+        .await?
+        .iter()
+        .map(|row| Bookmark::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+/// Replaces the chunks of a bookmark and marks it as embedded.
+#[instrument(skip(pool, chunks), fields(chunks = chunks.len()))]
+pub async fn save_chunks(pool: &PgPool, bookmark_id: &str, chunks: Vec<Chunk>) -> Result<()> {
+    const DELETE: &str = "DELETE FROM bookmark_chunk WHERE bookmark_id = $1;";
+    const INSERT: &str = r#"
+    INSERT INTO bookmark_chunk (bookmark_id, chunk_index, content, embedding)
+    VALUES ($1, $2, $3, $4);"#;
+    const MARK: &str = "UPDATE bookmark SET embedded_at = now() WHERE bookmark_id = $1;";
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    tx.execute(DELETE, &[&bookmark_id]).await?;
+    let insert = tx.prepare(INSERT).await?;
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let embedding = Vector::from(chunk.embedding);
+        tx.execute(
+            &insert,
+            &[&bookmark_id, &(index as i32), &chunk.content, &embedding],
+        )
+        .await?;
+    }
+    tx.execute(MARK, &[&bookmark_id]).await?;
+    tx.commit().await?;
+    info!(%bookmark_id, "Bookmark chunks embedded");
+    Ok(())
+}
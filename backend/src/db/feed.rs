@@ -0,0 +1,389 @@
+use chrono::{DateTime, Duration, Utc};
+use deadpool_postgres::GenericClient;
+use postgres_from_row::FromRow;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+use super::task::TaskStatus;
+use super::{PgPool, ResultExt};
+
+const FEED_POLL_INTERVAL: Duration = Duration::minutes(30);
+const FEED_ERROR_BACKOFF: Duration = Duration::hours(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FeedSubscription {
+    pub feed_id: Uuid,
+    pub user_id: Uuid,
+    pub url: String,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub muted: bool,
+    pub mute_keywords: Vec<String>,
+    #[serde(skip)]
+    pub etag: Option<String>,
+    #[serde(skip)]
+    pub last_modified: Option<String>,
+    pub last_polled_at: Option<DateTime<Utc>>,
+    pub last_status: Option<i16>,
+    pub last_error: Option<String>,
+    pub next_poll_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FeedSubscription {
+    /// An entry is muted when the whole feed is, or when its title or url
+    /// contains one of the feed's mute keywords.
+    pub fn mutes(&self, url: &str, title: Option<&str>) -> bool {
+        if self.muted {
+            return true;
+        }
+        let url = url.to_lowercase();
+        let title = title.unwrap_or_default().to_lowercase();
+        self.mute_keywords
+            .iter()
+            .map(|keyword| keyword.to_lowercase())
+            .any(|keyword| url.contains(&keyword) || title.contains(&keyword))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FeedEntry {
+    pub feed_id: Uuid,
+    pub entry_key: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub muted: bool,
+    pub task_id: Option<Uuid>,
+    pub seen_at: DateTime<Utc>,
+    pub task_status: Option<TaskStatus>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewFeedEntry {
+    pub entry_key: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub muted: bool,
+    /// Older entries found on the first poll, recorded as seen only.
+    pub backlog: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FeedChanges {
+    pub title: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub muted: Option<bool>,
+    pub mute_keywords: Option<Vec<String>>,
+}
+
+/// Result of a poll, as reported by the daemon.
+#[derive(Debug, Clone)]
+pub enum PollOutcome {
+    NotModified {
+        status: i16,
+    },
+    Fetched {
+        status: i16,
+        title: Option<String>,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+    Failed {
+        status: Option<i16>,
+        error: String,
+    },
+}
+
+#[instrument(skip(pool))]
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    url: &str,
+    tags: &[String],
+    mute_keywords: &[String],
+) -> Result<FeedSubscription> {
+    const SQL: &str = r#"
+    INSERT INTO feed_subscription (user_id, url, tags, mute_keywords)
+    VALUES ($1, $2, $3, $4) RETURNING feed_subscription.*;"#;
+    let client = pool.get().await?;
+    let row = client
+        .query_one(SQL, &[&user_id, &url, &tags, &mute_keywords])
+        .await
+        .on_constraint("feed_subscription_unique", |_| {
+            Error::constraint_violation("feed_subscription_unique", "feed already subscribed")
+        })?;
+    let feed = FeedSubscription::try_from_row(&row)?;
+    info!(feed_id = %feed.feed_id, "Feed subscription created");
+    Ok(feed)
+}
+
+#[instrument(skip(pool))]
+pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<FeedSubscription>> {
+    const SQL: &str =
+        "SELECT * FROM feed_subscription WHERE user_id = $1 ORDER BY created_at DESC;";
+    let client = pool.get().await?;
+    let result = client
+        .query(SQL, &[&user_id])
+        .await?
+        .iter()
+        .map(|row| FeedSubscription::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn get(pool: &PgPool, user_id: Uuid, feed_id: Uuid) -> Result<Option<FeedSubscription>> {
+    const SQL: &str = "SELECT * FROM feed_subscription WHERE user_id = $1 AND feed_id = $2;";
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(SQL, &[&user_id, &feed_id])
+        .await?
+        .map(|row| FeedSubscription::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn update(
+    pool: &PgPool,
+    user_id: Uuid,
+    feed_id: Uuid,
+    changes: &FeedChanges,
+) -> Result<Option<FeedSubscription>> {
+    const SQL: &str = r#"
+    UPDATE feed_subscription SET
+        title = coalesce($3, title),
+        tags = coalesce($4, tags),
+        muted = coalesce($5, muted),
+        mute_keywords = coalesce($6, mute_keywords),
+        updated_at = now()
+    WHERE user_id = $1 AND feed_id = $2
+    RETURNING feed_subscription.*;"#;
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(
+            SQL,
+            &[
+                &user_id,
+                &feed_id,
+                &changes.title,
+                &changes.tags,
+                &changes.muted,
+                &changes.mute_keywords,
+            ],
+        )
+        .await?
+        .map(|row| FeedSubscription::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn delete(pool: &PgPool, user_id: Uuid, feed_id: Uuid) -> Result<bool> {
+    const SQL: &str = "DELETE FROM feed_subscription WHERE user_id = $1 AND feed_id = $2;";
+    let client = pool.get().await?;
+    let deleted = client.execute(SQL, &[&user_id, &feed_id]).await?;
+    Ok(deleted > 0)
+}
+
+/// Makes the feed due right away, the daemon picks it up on its next run.
+#[instrument(skip(pool))]
+pub async fn schedule_now(pool: &PgPool, user_id: Uuid, feed_id: Uuid) -> Result<bool> {
+    const SQL: &str =
+        "UPDATE feed_subscription SET next_poll_at = now() WHERE user_id = $1 AND feed_id = $2;";
+    let client = pool.get().await?;
+    let updated = client.execute(SQL, &[&user_id, &feed_id]).await?;
+    Ok(updated > 0)
+}
+
+#[instrument(skip(pool))]
+pub async fn get_entries(
+    pool: &PgPool,
+    user_id: Uuid,
+    feed_id: Uuid,
+    limit: i64,
+) -> Result<Vec<FeedEntry>> {
+    const SQL: &str = r#"
+    SELECT fe.*, t.status AS task_status
+    FROM feed_entry fe
+    INNER JOIN feed_subscription fs USING(feed_id)
+    LEFT JOIN bookmark_task t USING(task_id)
+    WHERE fs.user_id = $1 AND fe.feed_id = $2
+    ORDER BY fe.seen_at DESC, fe.published_at DESC NULLS LAST
+    LIMIT $3;"#;
+    let client = pool.get().await?;
+    let result = client
+        .query(SQL, &[&user_id, &feed_id, &limit])
+        .await?
+        .iter()
+        .map(|row| FeedEntry::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+/// Mutes a single entry. Its bookmark task is dropped if it was not processed
+/// yet, bookmarks already saved are kept.
+#[instrument(skip(pool))]
+pub async fn mute_entry(
+    pool: &PgPool,
+    user_id: Uuid,
+    feed_id: Uuid,
+    entry_key: &str,
+) -> Result<bool> {
+    const MUTE: &str = r#"
+    UPDATE feed_entry fe SET muted = true
+    FROM feed_subscription fs
+    WHERE fs.feed_id = fe.feed_id AND fs.user_id = $1
+    AND fe.feed_id = $2 AND fe.entry_key = $3
+    RETURNING fe.task_id;"#;
+    const DROP_TASK: &str = "DELETE FROM bookmark_task WHERE task_id = $1 AND status = 'pending';";
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    let Some(row) = tx
+        .query_opt(MUTE, &[&user_id, &feed_id, &entry_key])
+        .await?
+    else {
+        return Ok(false);
+    };
+    let task_id: Option<Uuid> = row.get(0);
+    if let Some(task_id) = task_id {
+        tx.execute(DROP_TASK, &[&task_id]).await?;
+    }
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Takes the feeds due for polling and pushes their next poll forward, so
+/// concurrent runs don't poll the same feed twice.
+#[instrument(skip(pool))]
+pub async fn peek_due(pool: &PgPool, now: DateTime<Utc>) -> Result<Vec<FeedSubscription>> {
+    const QUERY: &str = r#"SELECT * FROM feed_subscription WHERE next_poll_at <= $1
+    ORDER BY next_poll_at FOR UPDATE SKIP LOCKED LIMIT 10;"#;
+    const UPDATE: &str = "UPDATE feed_subscription SET next_poll_at = $1 WHERE feed_id = ANY ($2);";
+
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    let feeds = tx
+        .query(QUERY, &[&now])
+        .await?
+        .iter()
+        .map(|row| FeedSubscription::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    let ids: Vec<Uuid> = feeds.iter().map(|f| f.feed_id).collect();
+    tx.execute(UPDATE, &[&(now + FEED_POLL_INTERVAL), &ids])
+        .await?;
+    tx.commit().await?;
+    Ok(feeds)
+}
+
+#[instrument(skip(pool))]
+pub async fn record_poll(pool: &PgPool, feed_id: Uuid, outcome: &PollOutcome) -> Result<()> {
+    let client = pool.get().await?;
+    match outcome {
+        PollOutcome::NotModified { status } => {
+            const SQL: &str = r#"
+            UPDATE feed_subscription
+            SET last_polled_at = now(), last_status = $2, last_error = NULL
+            WHERE feed_id = $1;"#;
+            client.execute(SQL, &[&feed_id, status]).await?;
+        }
+        PollOutcome::Fetched {
+            status,
+            title,
+            etag,
+            last_modified,
+        } => {
+            const SQL: &str = r#"
+            UPDATE feed_subscription
+            SET last_polled_at = now(), last_status = $2, last_error = NULL,
+                title = coalesce(title, $3), etag = $4, last_modified = $5
+            WHERE feed_id = $1;"#;
+            client
+                .execute(SQL, &[&feed_id, status, title, etag, last_modified])
+                .await?;
+        }
+        PollOutcome::Failed { status, error } => {
+            const SQL: &str = r#"
+            UPDATE feed_subscription
+            SET last_polled_at = now(), last_status = $2, last_error = $3, next_poll_at = $4
+            WHERE feed_id = $1;"#;
+            let next_poll_at = Utc::now() + FEED_ERROR_BACKOFF;
+            client
+                .execute(SQL, &[&feed_id, status, error, &next_poll_at])
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Records the entries of a poll and queues a bookmark task, tagged with
+/// `tags`, for each one that was not seen before and is not muted. Returns the
+/// number of tasks created.
+#[instrument(skip(pool, feed, entries), fields(feed_id = %feed.feed_id))]
+pub async fn record_entries(
+    pool: &PgPool,
+    feed: &FeedSubscription,
+    tags: &[String],
+    entries: &[NewFeedEntry],
+) -> Result<usize> {
+    const INSERT_ENTRY: &str = r#"
+    INSERT INTO feed_entry (feed_id, entry_key, url, title, published_at, muted)
+    VALUES ($1, $2, $3, $4, $5, $6)
+    ON CONFLICT (feed_id, entry_key) DO NOTHING;"#;
+    const INSERT_TASK: &str = r#"
+    INSERT INTO bookmark_task (user_id, url, status, tags)
+    VALUES ($1, $2, $3, $4) RETURNING task_id;"#;
+    const LINK_TASK: &str =
+        "UPDATE feed_entry SET task_id = $3 WHERE feed_id = $1 AND entry_key = $2;";
+
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    let insert_entry = tx.prepare(INSERT_ENTRY).await?;
+    let mut tasks = 0;
+    for entry in entries {
+        let inserted = tx
+            .execute(
+                &insert_entry,
+                &[
+                    &feed.feed_id,
+                    &entry.entry_key,
+                    &entry.url,
+                    &entry.title,
+                    &entry.published_at,
+                    &entry.muted,
+                ],
+            )
+            .await?;
+        if inserted == 0 || entry.muted || entry.backlog {
+            continue;
+        }
+        let row = tx
+            .query_one(
+                INSERT_TASK,
+                &[&feed.user_id, &entry.url, &TaskStatus::Pending, &tags],
+            )
+            .await?;
+        let task_id: Uuid = row.get(0);
+        tx.execute(LINK_TASK, &[&feed.feed_id, &entry.entry_key, &task_id])
+            .await?;
+        tasks += 1;
+    }
+    tx.commit().await?;
+    Ok(tasks)
+}
+
+/// Whether the feed was polled before, the first poll only bookmarks the most
+/// recent entries.
+#[instrument(skip(pool))]
+pub async fn has_entries(pool: &PgPool, feed_id: Uuid) -> Result<bool> {
+    const SQL: &str = "SELECT EXISTS (SELECT 1 FROM feed_entry WHERE feed_id = $1);";
+    let client = pool.get().await?;
+    let row = client.query_one(SQL, &[&feed_id]).await?;
+    Ok(row.get(0))
+}
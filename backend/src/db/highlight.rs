@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use postgres_from_row::FromRow;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+use super::PgPool;
+
+const COLUMNS: &str = "h.highlight_id, h.exact, h.prefix, h.suffix, h.start_offset, \
+    h.end_offset, h.note, h.created_at, h.updated_at";
+
+/// A passage of the archived page, anchored by a text quote and a position
+/// selector. Offsets are in UTF-16 code units of the text of the page.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Highlight {
+    pub highlight_id: Uuid,
+    pub exact: String,
+    /// The text right before `exact`, to tell its occurrences apart.
+    pub prefix: String,
+    /// The text right after `exact`.
+    pub suffix: String,
+    pub start_offset: i32,
+    pub end_offset: i32,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewHighlight {
+    pub exact: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub suffix: String,
+    pub start_offset: i32,
+    pub end_offset: i32,
+    pub note: Option<String>,
+}
+
+/// The user's highlights of the bookmark, in the order of the page.
+#[instrument(skip(pool))]
+pub async fn get_by_bookmark(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+) -> Result<Vec<Highlight>> {
+    let sql = format!(
+        "SELECT {COLUMNS} FROM highlight h
+        INNER JOIN bookmark_user bu USING(bookmark_user_id)
+        WHERE bu.user_id = $1 AND bu.bookmark_id = $2
+        ORDER BY h.start_offset, h.created_at;"
+    );
+    let client = pool.get().await?;
+    let result = client
+        .query(&sql, &[&user_id, &bookmark_id])
+        .await?
+        .iter()
+        .map(|row| Highlight::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+/// `None` when the user has no such bookmark.
+#[instrument(skip(pool, highlight))]
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+    highlight: &NewHighlight,
+) -> Result<Option<Highlight>> {
+    let sql = format!(
+        "WITH h AS (
+            INSERT INTO highlight
+            (bookmark_user_id, exact, prefix, suffix, start_offset, end_offset, note)
+            SELECT bookmark_user_id, $3, $4, $5, $6, $7, $8 FROM bookmark_user
+            WHERE user_id = $1 AND bookmark_id = $2
+            RETURNING *
+        )
+        SELECT {COLUMNS} FROM h;"
+    );
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(
+            &sql,
+            &[
+                &user_id,
+                &bookmark_id,
+                &highlight.exact,
+                &highlight.prefix,
+                &highlight.suffix,
+                &highlight.start_offset,
+                &highlight.end_offset,
+                &highlight.note,
+            ],
+        )
+        .await?
+        .map(|row| Highlight::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    if let Some(highlight) = &result {
+        info!(highlight_id = %highlight.highlight_id, "Highlight created");
+    }
+    Ok(result)
+}
+
+/// `None` removes the note.
+#[instrument(skip(pool))]
+pub async fn update_note(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+    highlight_id: Uuid,
+    note: Option<&str>,
+) -> Result<Option<Highlight>> {
+    let sql = format!(
+        "UPDATE highlight h SET note = $4, updated_at = now()
+        FROM bookmark_user bu
+        WHERE h.bookmark_user_id = bu.bookmark_user_id
+        AND bu.user_id = $1 AND bu.bookmark_id = $2 AND h.highlight_id = $3
+        RETURNING {COLUMNS};"
+    );
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(&sql, &[&user_id, &bookmark_id, &highlight_id, &note])
+        .await?
+        .map(|row| Highlight::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn delete(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+    highlight_id: Uuid,
+) -> Result<bool> {
+    const SQL: &str = r#"
+    DELETE FROM highlight h USING bookmark_user bu
+    WHERE h.bookmark_user_id = bu.bookmark_user_id
+    AND bu.user_id = $1 AND bu.bookmark_id = $2 AND h.highlight_id = $3;"#;
+    let client = pool.get().await?;
+    let deleted = client
+        .execute(SQL, &[&user_id, &bookmark_id, &highlight_id])
+        .await?;
+    Ok(deleted > 0)
+}
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumString;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::import::{ImportEntry, ImportError};
+
+use super::task::TaskStatus;
+use super::PgPool;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, EnumString, FromSql, ToSql)]
+#[postgres(name = "import_format", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(ascii_case_insensitive)]
+pub enum ImportFormat {
+    Netscape,
+    Pocket,
+    Pinboard,
+    Csv,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ImportJob {
+    pub import_id: Uuid,
+    pub user_id: Uuid,
+    pub format: ImportFormat,
+    pub total: i32,
+    pub parse_errors: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ImportProgress {
+    #[from_row(flatten)]
+    #[serde(flatten)]
+    pub job: ImportJob,
+    pub pending: i64,
+    pub done: i64,
+    pub failed: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ImportFailure {
+    pub task_id: Uuid,
+    pub url: String,
+    /// The title in the imported file, if any.
+    pub title: Option<String>,
+    pub fail_reason: Option<String>,
+}
+
+const PROGRESS_SQL: &str = r#"
+    SELECT
+        ij.*,
+        count(t.task_id) FILTER (WHERE t.status = 'pending') AS pending,
+        count(t.task_id) FILTER (WHERE t.status = 'done') AS done,
+        count(t.task_id) FILTER (WHERE t.status = 'fail') AS failed
+    FROM import_job ij
+    LEFT JOIN bookmark_task t USING(import_id)"#;
+
+#[instrument(skip(pool, entries, errors))]
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    format: ImportFormat,
+    entries: &[ImportEntry],
+    errors: &[ImportError],
+) -> Result<ImportJob> {
+    const INSERT_JOB: &str = r#"
+    INSERT INTO import_job (user_id, format, total, parse_errors)
+    VALUES ($1, $2, $3, $4) RETURNING import_job.*;"#;
+    const INSERT_TASK: &str = r#"
+    INSERT INTO bookmark_task (user_id, url, status, tags, import_id, user_created_at, title)
+    VALUES ($1, $2, $3, $4, $5, $6, $7);"#;
+
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    let total = entries.len() as i32;
+    let parse_errors = serde_json::to_value(errors).map_err(anyhow::Error::from)?;
+    let row = tx
+        .query_one(INSERT_JOB, &[&user_id, &format, &total, &parse_errors])
+        .await?;
+    let job = ImportJob::try_from_row(&row)?;
+    let statement = tx.prepare(INSERT_TASK).await?;
+    for entry in entries {
+        tx.execute(
+            &statement,
+            &[
+                &user_id,
+                &entry.url.to_string(),
+                &TaskStatus::Pending,
+                &entry.tags,
+                &job.import_id,
+                &entry.created_at,
+                &entry.title,
+            ],
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    info!(import_id = %job.import_id, %total, errors = errors.len(), "Import job created");
+    Ok(job)
+}
+
+#[instrument(skip(pool))]
+pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<ImportProgress>> {
+    let sql = format!(
+        "{PROGRESS_SQL} WHERE ij.user_id = $1 GROUP BY ij.import_id ORDER BY ij.created_at DESC;"
+    );
+    let client = pool.get().await?;
+    let result = client
+        .query(&sql, &[&user_id])
+        .await?
+        .iter()
+        .map(|row| ImportProgress::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn get_progress(
+    pool: &PgPool,
+    user_id: Uuid,
+    import_id: Uuid,
+) -> Result<Option<ImportProgress>> {
+    let sql = format!(
+        "{PROGRESS_SQL} WHERE ij.user_id = $1 AND ij.import_id = $2 GROUP BY ij.import_id;"
+    );
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(&sql, &[&user_id, &import_id])
+        .await?
+        .map(|row| ImportProgress::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn get_failures(
+    pool: &PgPool,
+    user_id: Uuid,
+    import_id: Uuid,
+) -> Result<Vec<ImportFailure>> {
+    const SQL: &str = r#"
+    SELECT task_id, url, title, fail_reason FROM bookmark_task
+    WHERE user_id = $1 AND import_id = $2 AND status = 'fail'
+    ORDER BY created_at ASC;"#;
+    let client = pool.get().await?;
+    let result = client
+        .query(SQL, &[&user_id, &import_id])
+        .await?
+        .iter()
+        .map(|row| ImportFailure::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
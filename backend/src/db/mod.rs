@@ -2,6 +2,7 @@ use anyhow::Context;
 use deadpool_postgres::{
     Config, GenericClient, ManagerConfig, PoolConfig, RecyclingMethod, Runtime,
 };
+use postgres_types::ToSql;
 use secrecy::ExposeSecret;
 use tracing::{debug, info};
 
@@ -9,13 +10,47 @@ use crate::error::{Error, Result};
 use crate::PgParams;
 
 pub mod bookmark;
+pub mod collection;
+pub mod embedding;
+pub mod feed;
+pub mod highlight;
+pub mod import;
+pub mod output_feed;
+pub mod pagination;
+pub mod rule;
+pub mod saved_search;
 pub mod search;
+pub mod settings;
+pub mod tag_alias;
+pub mod tag_suggestion;
 pub mod task;
 pub mod user;
 
 pub type PgPool = deadpool_postgres::Pool;
 pub type PgConnection = deadpool_postgres::Object;
 
+/// Positional parameters of a statement assembled at runtime, placeholders
+/// are numbered in the order values are pushed.
+#[derive(Debug, Default)]
+pub struct SqlParams {
+    values: Vec<Box<dyn ToSql + Sync + Send>>,
+}
+
+impl SqlParams {
+    /// Binds `value` and returns its placeholder, e.g. `$3`.
+    pub fn push<T: ToSql + Sync + Send + 'static>(&mut self, value: T) -> String {
+        self.values.push(Box::new(value));
+        format!("${}", self.values.len())
+    }
+
+    pub fn as_refs(&self) -> Vec<&(dyn ToSql + Sync)> {
+        self.values
+            .iter()
+            .map(|value| value.as_ref() as &(dyn ToSql + Sync))
+            .collect()
+    }
+}
+
 const CREATE_GET_SCHEMA_FUNCTION: &str = "
 CREATE OR REPLACE FUNCTION get_schema_version() RETURNS INTEGER AS $$
 DECLARE
@@ -36,10 +71,123 @@ BEGIN
 END;
 $$ LANGUAGE plpgsql;";
 
-const SCHEMAS: [(i32, &str); 1] = [(
-    1,
-    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/schema/1_init.sql")),
-)];
+const SCHEMAS: [(i32, &str); 19] = [
+    (
+        1,
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/schema/1_init.sql")),
+    ),
+    (
+        2,
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/schema/2_import.sql")),
+    ),
+    (
+        3,
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/schema/3_feed.sql")),
+    ),
+    (
+        4,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/4_output_feed.sql"
+        )),
+    ),
+    (
+        5,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/5_language.sql"
+        )),
+    ),
+    (
+        6,
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/schema/6_trigram.sql")),
+    ),
+    (
+        7,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/7_embedding.sql"
+        )),
+    ),
+    (
+        8,
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/schema/8_note.sql")),
+    ),
+    (
+        9,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/9_saved_search.sql"
+        )),
+    ),
+    (
+        10,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/10_tag_policy.sql"
+        )),
+    ),
+    (
+        11,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/11_tag_alias.sql"
+        )),
+    ),
+    (
+        12,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/12_tag_suggestion.sql"
+        )),
+    ),
+    (
+        13,
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/schema/13_rule.sql")),
+    ),
+    (
+        14,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/14_highlight.sql"
+        )),
+    ),
+    (
+        15,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/15_reading_state.sql"
+        )),
+    ),
+    (
+        16,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/16_content_stats.sql"
+        )),
+    ),
+    (
+        17,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/17_collection.sql"
+        )),
+    ),
+    (
+        18,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/18_search_word.sql"
+        )),
+    ),
+    (
+        19,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/19_import_title.sql"
+        )),
+    ),
+];
 
 pub async fn get_pool(pg: PgParams) -> anyhow::Result<PgPool> {
     info!("Creating postgres pool with {pg:?}");
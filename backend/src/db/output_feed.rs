@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+use super::PgPool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, FromSql, ToSql)]
+#[postgres(name = "output_feed_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFeedKind {
+    All,
+    Tag,
+    Search,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OutputFeed {
+    pub output_feed_id: Uuid,
+    pub user_id: Uuid,
+    pub token: String,
+    pub title: String,
+    pub kind: OutputFeedKind,
+    pub tag: Option<String>,
+    pub search: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[instrument(skip(pool, token))]
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    token: &str,
+    title: &str,
+    kind: OutputFeedKind,
+    tag: Option<&str>,
+    search: Option<&serde_json::Value>,
+) -> Result<OutputFeed> {
+    const SQL: &str = r#"
+    INSERT INTO output_feed (user_id, token, title, kind, tag, search)
+    VALUES ($1, $2, $3, $4, $5, $6) RETURNING output_feed.*;"#;
+    let client = pool.get().await?;
+    let row = client
+        .query_one(SQL, &[&user_id, &token, &title, &kind, &tag, &search])
+        .await?;
+    let feed = OutputFeed::try_from_row(&row)?;
+    info!(output_feed_id = %feed.output_feed_id, "Output feed created");
+    Ok(feed)
+}
+
+#[instrument(skip(pool))]
+pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<OutputFeed>> {
+    const SQL: &str = "SELECT * FROM output_feed WHERE user_id = $1 ORDER BY created_at DESC;";
+    let client = pool.get().await?;
+    let result = client
+        .query(SQL, &[&user_id])
+        .await?
+        .iter()
+        .map(|row| OutputFeed::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool, token))]
+pub async fn get_by_token(pool: &PgPool, token: &str) -> Result<Option<OutputFeed>> {
+    const SQL: &str = "SELECT * FROM output_feed WHERE token = $1;";
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(SQL, &[&token])
+        .await?
+        .map(|row| OutputFeed::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn delete(pool: &PgPool, user_id: Uuid, output_feed_id: Uuid) -> Result<bool> {
+    const SQL: &str = "DELETE FROM output_feed WHERE user_id = $1 AND output_feed_id = $2;";
+    let client = pool.get().await?;
+    let deleted = client.execute(SQL, &[&user_id, &output_feed_id]).await?;
+    Ok(deleted > 0)
+}
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+
+use crate::error::{Error, Result};
+
+use super::SqlParams;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sort {
+    #[default]
+    Relevance,
+    Saved,
+    Updated,
+    Title,
+    Domain,
+    /// The quickest reads first.
+    ReadingTime,
+    /// The shortest first.
+    WordCount,
+    /// The pages kept the most as text first, the unknown ones last.
+    ExtractionRatio,
+}
+
+impl Sort {
+    /// Relevance needs a text query to rank by, without one the most recently
+    /// saved bookmarks come first.
+    pub fn effective(self, has_query: bool) -> Sort {
+        match self {
+            Sort::Relevance if !has_query => Sort::Saved,
+            sort => sort,
+        }
+    }
+
+    /// The `sort_key` column for this sort, `rank` is the relevance expression.
+    pub fn key_expression(self, rank: Option<&str>) -> String {
+        match (self, rank) {
+            (Sort::Relevance, Some(rank)) => rank.to_owned(),
+            (Sort::Relevance | Sort::Saved, _) => "bu.created_at".to_owned(),
+            (Sort::Updated, _) => "bu.updated_at".to_owned(),
+            (Sort::Title, _) => "lower(b.title)".to_owned(),
+            (Sort::Domain, _) => "b.domain".to_owned(),
+            (Sort::ReadingTime, _) => "b.reading_time".to_owned(),
+            (Sort::WordCount, _) => "b.word_count".to_owned(),
+            (Sort::ExtractionRatio, _) => "coalesce(b.extraction_ratio, 0)".to_owned(),
+        }
+    }
+
+    fn descending(self) -> bool {
+        matches!(
+            self,
+            Sort::Relevance | Sort::Saved | Sort::Updated | Sort::ExtractionRatio
+        )
+    }
+}
+
+/// Position after the last row of a page, handed to clients as an opaque
+/// base64url string. It carries its sort so it can't be replayed on another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "sort", rename_all = "snake_case")]
+pub enum Cursor {
+    Relevance { rank: f32, id: String },
+    Saved { at: DateTime<Utc>, id: String },
+    Updated { at: DateTime<Utc>, id: String },
+    Title { title: String, id: String },
+    Domain { domain: String, id: String },
+    ReadingTime { minutes: i32, id: String },
+    WordCount { words: i32, id: String },
+    ExtractionRatio { ratio: f32, id: String },
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("Serialize should not fail");
+        base64_url::encode(&json)
+    }
+
+    pub fn decode(raw: &str) -> Result<Self> {
+        base64_url::decode(raw)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| Error::bad_request([("cursor", "invalid cursor")]))
+    }
+
+    fn sort(&self) -> Sort {
+        match self {
+            Cursor::Relevance { .. } => Sort::Relevance,
+            Cursor::Saved { .. } => Sort::Saved,
+            Cursor::Updated { .. } => Sort::Updated,
+            Cursor::Title { .. } => Sort::Title,
+            Cursor::Domain { .. } => Sort::Domain,
+            Cursor::ReadingTime { .. } => Sort::ReadingTime,
+            Cursor::WordCount { .. } => Sort::WordCount,
+            Cursor::ExtractionRatio { .. } => Sort::ExtractionRatio,
+        }
+    }
+
+    fn from_row(sort: Sort, row: &Row) -> Result<Self> {
+        let id: String = row.try_get("bookmark_id")?;
+        let cursor = match sort {
+            Sort::Relevance => Cursor::Relevance {
+                rank: row.try_get("sort_key")?,
+                id,
+            },
+            Sort::Saved => Cursor::Saved {
+                at: row.try_get("sort_key")?,
+                id,
+            },
+            Sort::Updated => Cursor::Updated {
+                at: row.try_get("sort_key")?,
+                id,
+            },
+            Sort::Title => Cursor::Title {
+                title: row.try_get("sort_key")?,
+                id,
+            },
+            Sort::Domain => Cursor::Domain {
+                domain: row.try_get("sort_key")?,
+                id,
+            },
+            Sort::ReadingTime => Cursor::ReadingTime {
+                minutes: row.try_get("sort_key")?,
+                id,
+            },
+            Sort::WordCount => Cursor::WordCount {
+                words: row.try_get("sort_key")?,
+                id,
+            },
+            Sort::ExtractionRatio => Cursor::ExtractionRatio {
+                ratio: row.try_get("sort_key")?,
+                id,
+            },
+        };
+        Ok(cursor)
+    }
+
+    fn bind(&self, params: &mut SqlParams) -> (String, String) {
+        match self.clone() {
+            Cursor::Relevance { rank, id } => (params.push(rank), params.push(id)),
+            Cursor::Saved { at, id } | Cursor::Updated { at, id } => {
+                (params.push(at), params.push(id))
+            }
+            Cursor::Title { title: key, id } | Cursor::Domain { domain: key, id } => {
+                (params.push(key), params.push(id))
+            }
+            Cursor::ReadingTime { minutes: key, id } | Cursor::WordCount { words: key, id } => {
+                (params.push(key), params.push(id))
+            }
+            Cursor::ExtractionRatio { ratio, id } => (params.push(ratio), params.push(id)),
+        }
+    }
+}
+
+pub fn page_size(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Wraps `inner`, which must select `bookmark_id` and a `sort_key` column, into
+/// a keyset paginated query. One row more than `limit` is fetched to know if
+/// there is a next page, see [`split_page`].
+pub fn paginate(
+    inner: &str,
+    select: &str,
+    sort: Sort,
+    cursor: Option<&Cursor>,
+    limit: i64,
+    params: &mut SqlParams,
+) -> Result<String> {
+    let (comparison, direction) = if sort.descending() {
+        ("<", "DESC")
+    } else {
+        (">", "ASC")
+    };
+    let mut sql = format!("SELECT {select} FROM ({inner}) r");
+    if let Some(cursor) = cursor {
+        if cursor.sort() != sort {
+            return Err(Error::bad_request([(
+                "cursor",
+                "cursor belongs to another sort",
+            )]));
+        }
+        let (key, id) = cursor.bind(params);
+        sql.push_str(&format!(
+            " WHERE (r.sort_key, r.bookmark_id) {comparison} ({key}, {id})"
+        ));
+    }
+    sql.push_str(&format!(
+        " ORDER BY r.sort_key {direction}, r.bookmark_id {direction} LIMIT {}",
+        limit + 1
+    ));
+    Ok(sql)
+}
+
+/// Maps the rows of a [`paginate`] query, returning the cursor of the next
+/// page when there is one.
+pub fn split_page<T>(
+    mut rows: Vec<Row>,
+    sort: Sort,
+    limit: i64,
+    map: impl Fn(&Row) -> Result<T>,
+) -> Result<(Vec<T>, Option<String>)> {
+    let limit = limit as usize;
+    let next_cursor = if rows.len() > limit {
+        rows.truncate(limit);
+        rows.last()
+            .map(|row| Cursor::from_row(sort, row))
+            .transpose()?
+            .map(|cursor| cursor.encode())
+    } else {
+        None
+    };
+    let items = rows.iter().map(map).collect::<Result<Vec<_>>>()?;
+    Ok((items, next_cursor))
+}
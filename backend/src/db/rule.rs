@@ -0,0 +1,378 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use postgres_from_row::FromRow;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::tag::TagPolicy;
+
+use super::bookmark::{BookmarkWithUser, TagOperation};
+use super::{PgPool, ResultExt, SqlParams};
+
+/// The bookmarks listed by a dry run.
+const DRY_RUN_LIMIT: i64 = 50;
+
+/// Matched against a bookmark, patterns are matched against the whole value
+/// and `*` matches anything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// The domain or one of its subdomains.
+    Domain(String),
+    /// A pattern of the url.
+    Url(String),
+    /// Words of the title, stemmed as in searches.
+    Title(String),
+    /// Words of the title or the text content.
+    Content(String),
+    /// A pattern of the media type the page was served with, as in
+    /// `application/*`.
+    ContentType(String),
+}
+
+impl Condition {
+    fn value(&self) -> &str {
+        match self {
+            Condition::Domain(value)
+            | Condition::Url(value)
+            | Condition::Title(value)
+            | Condition::Content(value)
+            | Condition::ContentType(value) => value,
+        }
+    }
+
+    /// The filter on `b`, the bookmark.
+    fn sql(&self, params: &mut SqlParams) -> String {
+        match self {
+            Condition::Domain(domain) => {
+                let domain = domain.to_lowercase();
+                let subdomain = params.push(format!(".{domain}"));
+                format!(
+                    "(b.domain = {} OR right(b.domain, length({subdomain})) = {subdomain})",
+                    params.push(domain)
+                )
+            }
+            Condition::Url(pattern) => format!("b.url LIKE {}", params.push(like(pattern))),
+            Condition::Title(words) => format!(
+                "to_tsvector(b.language, b.title) @@ plainto_tsquery(b.language, {})",
+                params.push(words.clone())
+            ),
+            Condition::Content(words) => format!(
+                "b.search_tokens @@ plainto_tsquery(b.language, {})",
+                params.push(words.clone())
+            ),
+            Condition::ContentType(pattern) => format!(
+                "lower(b.content_type) LIKE {}",
+                params.push(like(&pattern.to_lowercase()))
+            ),
+        }
+    }
+}
+
+/// The `LIKE` pattern of a pattern where `*` matches anything.
+fn like(pattern: &str) -> String {
+    pattern
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+        .replace('*', "%")
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Actions {
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub archive: bool,
+}
+
+impl Actions {
+    pub fn is_empty(&self) -> bool {
+        self.add_tags.is_empty() && !self.favorite && !self.archive
+    }
+}
+
+/// The conditions and actions of a rule, as written by the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDefinition {
+    pub conditions: Vec<Condition>,
+    pub actions: Actions,
+}
+
+impl RuleDefinition {
+    /// The definition without blank conditions, an error when it would match
+    /// every bookmark or do nothing.
+    pub fn validate(self) -> Result<Self> {
+        let conditions: Vec<Condition> = self
+            .conditions
+            .into_iter()
+            .filter(|c| !c.value().trim().is_empty())
+            .collect();
+        if conditions.is_empty() {
+            return Err(Error::bad_request([("conditions", "can't be empty")]));
+        }
+        if self.actions.is_empty() {
+            return Err(Error::bad_request([("actions", "can't be empty")]));
+        }
+        Ok(Self {
+            conditions,
+            actions: self.actions,
+        })
+    }
+
+    /// The filter on `bu` and `b` of the user's bookmarks the rule matches and
+    /// would change.
+    fn filter(&self, user_id: Uuid, tags: &[String], params: &mut SqlParams) -> String {
+        let mut sql = format!("bu.user_id = {}", params.push(user_id));
+        for condition in &self.conditions {
+            sql.push_str(" AND ");
+            sql.push_str(&condition.sql(params));
+        }
+        let mut changes = vec![format!(
+            "NOT coalesce(bu.tags, '{{}}') @> {}::text[]",
+            params.push(tags.to_vec())
+        )];
+        if self.actions.favorite {
+            changes.push("NOT bu.favorite".to_owned());
+        }
+        if self.actions.archive {
+            changes.push("NOT bu.archived".to_owned());
+        }
+        sql.push_str(&format!(" AND ({})", changes.join(" OR ")));
+        sql
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Rule {
+    pub rule_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    /// The `Condition`s.
+    pub conditions: serde_json::Value,
+    /// The `Actions`.
+    pub actions: serde_json::Value,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Rule {
+    pub fn definition(&self) -> Result<RuleDefinition> {
+        let parse = || -> serde_json::Result<RuleDefinition> {
+            Ok(RuleDefinition {
+                conditions: serde_json::from_value(self.conditions.clone())?,
+                actions: serde_json::from_value(self.actions.clone())?,
+            })
+        };
+        parse().map_err(|e| Error::from(anyhow::Error::from(e)))
+    }
+}
+
+/// The bookmarks a rule would change, the most recent ones first.
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRun {
+    pub total: i64,
+    pub bookmarks: Vec<BookmarkWithUser>,
+}
+
+fn name_taken(_: tokio_postgres::error::DbError) -> Error {
+    Error::constraint_violation("rule_name_unique", "name already used")
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<serde_json::Value> {
+    Ok(serde_json::to_value(value).map_err(anyhow::Error::from)?)
+}
+
+#[instrument(skip(pool))]
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: &str,
+    definition: &RuleDefinition,
+    enabled: bool,
+) -> Result<Rule> {
+    const SQL: &str = r#"
+    INSERT INTO rule (user_id, name, conditions, actions, enabled)
+    VALUES ($1, $2, $3, $4, $5) RETURNING rule.*;"#;
+    let client = pool.get().await?;
+    let row = client
+        .query_one(
+            SQL,
+            &[
+                &user_id,
+                &name,
+                &to_json(&definition.conditions)?,
+                &to_json(&definition.actions)?,
+                &enabled,
+            ],
+        )
+        .await
+        .on_constraint("rule_name_unique", name_taken)?;
+    let rule = Rule::try_from_row(&row)?;
+    info!(rule_id = %rule.rule_id, "Rule created");
+    Ok(rule)
+}
+
+/// The user's rules, in the order they are applied.
+#[instrument(skip(pool))]
+pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<Rule>> {
+    const SQL: &str = "SELECT * FROM rule WHERE user_id = $1 ORDER BY created_at, name;";
+    let client = pool.get().await?;
+    let result = client
+        .query(SQL, &[&user_id])
+        .await?
+        .iter()
+        .map(|row| Rule::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn get(pool: &PgPool, user_id: Uuid, rule_id: Uuid) -> Result<Option<Rule>> {
+    const SQL: &str = "SELECT * FROM rule WHERE user_id = $1 AND rule_id = $2;";
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(SQL, &[&user_id, &rule_id])
+        .await?
+        .map(|row| Rule::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn update(
+    pool: &PgPool,
+    user_id: Uuid,
+    rule_id: Uuid,
+    name: Option<&str>,
+    definition: Option<&RuleDefinition>,
+    enabled: Option<bool>,
+) -> Result<Option<Rule>> {
+    const SQL: &str = r#"
+    UPDATE rule SET
+        name = coalesce($3, name),
+        conditions = coalesce($4, conditions),
+        actions = coalesce($5, actions),
+        enabled = coalesce($6, enabled),
+        updated_at = now()
+    WHERE user_id = $1 AND rule_id = $2
+    RETURNING rule.*;"#;
+    let conditions = definition.map(|d| to_json(&d.conditions)).transpose()?;
+    let actions = definition.map(|d| to_json(&d.actions)).transpose()?;
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(
+            SQL,
+            &[&user_id, &rule_id, &name, &conditions, &actions, &enabled],
+        )
+        .await
+        .on_constraint("rule_name_unique", name_taken)?
+        .map(|row| Rule::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn delete(pool: &PgPool, user_id: Uuid, rule_id: Uuid) -> Result<bool> {
+    const SQL: &str = "DELETE FROM rule WHERE user_id = $1 AND rule_id = $2;";
+    let client = pool.get().await?;
+    let deleted = client.execute(SQL, &[&user_id, &rule_id]).await?;
+    Ok(deleted > 0)
+}
+
+/// The user's bookmarks the rule would change, without changing them.
+#[instrument(skip(pool, policy))]
+pub async fn dry_run(
+    pool: &PgPool,
+    user_id: Uuid,
+    definition: &RuleDefinition,
+    policy: &TagPolicy,
+) -> Result<DryRun> {
+    let mut params = SqlParams::default();
+    let filter = definition.filter(
+        user_id,
+        &policy.normalize(&definition.actions.add_tags),
+        &mut params,
+    );
+    let count_sql = format!(
+        "SELECT count(1) FROM bookmark_user bu
+        INNER JOIN bookmark b USING(bookmark_id) WHERE {filter};"
+    );
+    let sql = format!(
+        r#"
+        SELECT
+            b.*,
+            bu.user_id,
+            bu.tags,
+            bu.note,
+            bu.favorite,
+            bu.archived,
+            bu.read_at,
+            bu.progress,
+            bu.created_at as user_created_at,
+            bu.updated_at as user_updated_at
+        FROM bookmark_user bu
+        INNER JOIN bookmark b USING(bookmark_id)
+        WHERE {filter}
+        ORDER BY bu.created_at DESC LIMIT {DRY_RUN_LIMIT};"#
+    );
+    let client = pool.get().await?;
+    let total: i64 = client
+        .query_one(&count_sql, &params.as_refs())
+        .await?
+        .try_get(0)?;
+    let bookmarks = client
+        .query(&sql, &params.as_refs())
+        .await?
+        .iter()
+        .map(|row| BookmarkWithUser::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(DryRun { total, bookmarks })
+}
+
+/// Applies the rule to the user's matching bookmarks, or only to
+/// `bookmark_id`. Returns the number of bookmarks changed.
+#[instrument(skip(pool, policy))]
+pub async fn apply(
+    pool: &PgPool,
+    user_id: Uuid,
+    definition: &RuleDefinition,
+    policy: &TagPolicy,
+    bookmark_id: Option<&str>,
+) -> Result<u64> {
+    let operation = TagOperation::Append(definition.actions.add_tags.clone()).normalize(policy);
+    let mut params = SqlParams::default();
+    let tags = params.push(operation.tags().to_vec());
+    let mut filter = definition.filter(user_id, operation.tags(), &mut params);
+    if let Some(bookmark_id) = bookmark_id {
+        filter.push_str(&format!(
+            " AND bu.bookmark_id = {}",
+            params.push(bookmark_id.to_owned())
+        ));
+    }
+    let sql = format!(
+        r#"
+        UPDATE bookmark_user SET
+            tags = {},
+            favorite = favorite OR {},
+            archived = archived OR {},
+            updated_at = now()
+        WHERE bookmark_user_id IN (
+            SELECT bu.bookmark_user_id FROM bookmark_user bu
+            INNER JOIN bookmark b USING(bookmark_id)
+            WHERE {filter}
+        );"#,
+        operation.expression(&tags),
+        params.push(definition.actions.favorite),
+        params.push(definition.actions.archive),
+    );
+    let client = pool.get().await?;
+    let updated = client.execute(&sql, &params.as_refs()).await?;
+    info!(updated, "Rule applied");
+    Ok(updated)
+}
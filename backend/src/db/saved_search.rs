@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use postgres_from_row::FromRow;
+use serde::Serialize;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+use super::search::SearchRequest;
+use super::{PgPool, ResultExt};
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SavedSearch {
+    pub saved_search_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    /// A `SearchRequest`, without cursor.
+    pub search: serde_json::Value,
+    pub last_seen_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SavedSearch {
+    pub fn request(&self) -> Result<SearchRequest> {
+        serde_json::from_value(self.search.clone()).map_err(|e| Error::from(anyhow::Error::from(e)))
+    }
+}
+
+fn name_taken(_: tokio_postgres::error::DbError) -> Error {
+    Error::constraint_violation("saved_search_name_unique", "name already used")
+}
+
+#[instrument(skip(pool))]
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: &str,
+    search: &serde_json::Value,
+) -> Result<SavedSearch> {
+    const SQL: &str = r#"
+    INSERT INTO saved_search (user_id, name, search)
+    VALUES ($1, $2, $3) RETURNING saved_search.*;"#;
+    let client = pool.get().await?;
+    let row = client
+        .query_one(SQL, &[&user_id, &name, &search])
+        .await
+        .on_constraint("saved_search_name_unique", name_taken)?;
+    let saved_search = SavedSearch::try_from_row(&row)?;
+    info!(saved_search_id = %saved_search.saved_search_id, "Saved search created");
+    Ok(saved_search)
+}
+
+#[instrument(skip(pool))]
+pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<SavedSearch>> {
+    const SQL: &str = "SELECT * FROM saved_search WHERE user_id = $1 ORDER BY name;";
+    let client = pool.get().await?;
+    let result = client
+        .query(SQL, &[&user_id])
+        .await?
+        .iter()
+        .map(|row| SavedSearch::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn get(
+    pool: &PgPool,
+    user_id: Uuid,
+    saved_search_id: Uuid,
+) -> Result<Option<SavedSearch>> {
+    const SQL: &str = "SELECT * FROM saved_search WHERE user_id = $1 AND saved_search_id = $2;";
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(SQL, &[&user_id, &saved_search_id])
+        .await?
+        .map(|row| SavedSearch::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn update(
+    pool: &PgPool,
+    user_id: Uuid,
+    saved_search_id: Uuid,
+    name: Option<&str>,
+    search: Option<&serde_json::Value>,
+) -> Result<Option<SavedSearch>> {
+    const SQL: &str = r#"
+    UPDATE saved_search SET
+        name = coalesce($3, name),
+        search = coalesce($4, search),
+        updated_at = now()
+    WHERE user_id = $1 AND saved_search_id = $2
+    RETURNING saved_search.*;"#;
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(SQL, &[&user_id, &saved_search_id, &name, &search])
+        .await
+        .on_constraint("saved_search_name_unique", name_taken)?
+        .map(|row| SavedSearch::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
+
+/// The matches saved from now on are the new ones.
+#[instrument(skip(pool))]
+pub async fn mark_seen(pool: &PgPool, user_id: Uuid, saved_search_id: Uuid) -> Result<()> {
+    const SQL: &str = r#"
+    UPDATE saved_search SET last_seen_at = now()
+    WHERE user_id = $1 AND saved_search_id = $2;"#;
+    let client = pool.get().await?;
+    client.execute(SQL, &[&user_id, &saved_search_id]).await?;
+    Ok(())
+}
+
+#[instrument(skip(pool))]
+pub async fn delete(pool: &PgPool, user_id: Uuid, saved_search_id: Uuid) -> Result<bool> {
+    const SQL: &str = "DELETE FROM saved_search WHERE user_id = $1 AND saved_search_id = $2;";
+    let client = pool.get().await?;
+    let deleted = client.execute(SQL, &[&user_id, &saved_search_id]).await?;
+    Ok(deleted > 0)
+}
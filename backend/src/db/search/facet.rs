@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use postgres_from_row::FromRow;
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumString, IntoStaticStr};
+
+use crate::db::SqlParams;
+
+/// The dimensions search results are counted along, besides tags.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    EnumString,
+    IntoStaticStr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Facet {
+    Domain,
+    /// The month the user saved the bookmark, e.g. `2024-05`.
+    Month,
+    /// The url scheme, e.g. `https`.
+    Scheme,
+    /// The last label of the domain, e.g. `rs`.
+    Tld,
+    /// `tagged` or `untagged`.
+    State,
+}
+
+impl Facet {
+    pub const ALL: [Facet; 5] = [
+        Facet::Domain,
+        Facet::Month,
+        Facet::Scheme,
+        Facet::Tld,
+        Facet::State,
+    ];
+
+    /// The value of the facet for a `bookmark_user bu` joined with its
+    /// `bookmark b`.
+    pub fn expression(self) -> &'static str {
+        match self {
+            Facet::Domain => "b.domain",
+            Facet::Month => "to_char(bu.created_at AT TIME ZONE 'UTC', 'YYYY-MM')",
+            Facet::Scheme => "split_part(b.url, ':', 1)",
+            Facet::Tld => "substring(b.domain FROM '[^.]+$')",
+            Facet::State => "CASE WHEN cardinality(bu.tags) > 0 THEN 'tagged' ELSE 'untagged' END",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Values to keep per facet, a bookmark matches when it has one of the values
+/// of every facet.
+pub type FacetFilters = BTreeMap<Facet, Vec<String>>;
+
+pub type Facets = BTreeMap<Facet, Vec<FacetCount>>;
+
+/// The `WHERE` conditions of `filters`.
+pub fn compile(filters: &FacetFilters, params: &mut SqlParams) -> String {
+    filters
+        .iter()
+        .filter(|(_, values)| !values.is_empty())
+        .map(|(facet, values)| {
+            format!(
+                " AND {} = ANY({}::text[]) ",
+                facet.expression(),
+                params.push(values.clone())
+            )
+        })
+        .collect()
+}
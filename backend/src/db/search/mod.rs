@@ -0,0 +1,728 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use deadpool_postgres::GenericClient;
+use futures::TryFutureExt;
+use pgvector::Vector;
+use postgres_from_row::FromRow;
+use serde::{Deserialize, Serialize};
+use tokio::try_join;
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+use crate::embedding::Embedder;
+use crate::error::{Error, Result};
+use crate::language::Language;
+use crate::tag::{TagNode, SEPARATOR};
+
+use self::facet::{Facet, FacetCount, FacetFilters, Facets};
+use self::parser::{Bound, Query, State, Term};
+use self::snippet::Snippet;
+use super::bookmark::TagOperation;
+use super::pagination::{self, Cursor, Sort};
+use super::{saved_search, settings, PgConnection, PgPool, SqlParams};
+
+mod facet;
+mod parser;
+mod snippet;
+mod suggestion;
+
+/// Minimum `word_similarity` of a fuzzy match.
+const FUZZY_THRESHOLD: f32 = 0.4;
+/// Fragments of the text content per hit.
+const SNIPPET_FRAGMENTS: usize = 3;
+/// Minimum cosine similarity of a semantic match.
+const SEMANTIC_THRESHOLD: f32 = 0.3;
+/// Smoothing constant of the reciprocal rank fusion.
+const RRF_K: i32 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResultItem {
+    pub bookmark_id: String,
+    pub url: String,
+    pub domain: String,
+    pub title: String,
+    /// Where the free text matches the text content.
+    pub snippets: Vec<Snippet>,
+    pub created_at: DateTime<Utc>,
+    pub user_id: Option<Uuid>,
+    pub tags: Option<Vec<String>>,
+    /// In minutes.
+    pub reading_time: i32,
+    pub word_count: i32,
+    pub image_count: Option<i32>,
+    pub extraction_ratio: Option<f32>,
+    pub favorite: bool,
+    pub archived: bool,
+    pub read_at: Option<DateTime<Utc>>,
+    pub progress: f32,
+    pub user_created_at: Option<DateTime<Utc>>,
+    pub user_updated_at: Option<DateTime<Utc>>,
+}
+
+/// A hit as selected, `search_match` is a headline or the closest chunk.
+#[derive(Debug, FromRow)]
+struct SearchRow {
+    bookmark_id: String,
+    url: String,
+    domain: String,
+    title: String,
+    text_content: String,
+    search_match: Option<String>,
+    created_at: DateTime<Utc>,
+    user_id: Option<Uuid>,
+    tags: Option<Vec<String>>,
+    reading_time: i32,
+    word_count: i32,
+    image_count: Option<i32>,
+    extraction_ratio: Option<f32>,
+    favorite: bool,
+    archived: bool,
+    read_at: Option<DateTime<Utc>>,
+    progress: f32,
+    user_created_at: Option<DateTime<Utc>>,
+    user_updated_at: Option<DateTime<Utc>>,
+}
+
+impl SearchRow {
+    fn into_item(self, semantic: bool) -> SearchResultItem {
+        let snippets = match &self.search_match {
+            Some(chunk) if semantic => vec![snippet::plain(chunk, &self.text_content)],
+            Some(headline) => snippet::parse(headline, &self.text_content),
+            None => Vec::new(),
+        };
+        SearchResultItem {
+            bookmark_id: self.bookmark_id,
+            url: self.url,
+            domain: self.domain,
+            title: self.title,
+            snippets,
+            created_at: self.created_at,
+            user_id: self.user_id,
+            tags: self.tags,
+            reading_time: self.reading_time,
+            word_count: self.word_count,
+            image_count: self.image_count,
+            extraction_ratio: self.extraction_ratio,
+            favorite: self.favorite,
+            archived: self.archived,
+            read_at: self.read_at,
+            progress: self.progress,
+            user_created_at: self.user_created_at,
+            user_updated_at: self.user_updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub bookmarks: Vec<SearchResultItem>,
+    /// The matches per tag, as a tree of the tag hierarchy.
+    tags: Vec<TagNode>,
+    facets: Facets,
+    total: u64,
+    next_cursor: Option<String>,
+    /// The query with its words replaced by the closest ones of the user's
+    /// bookmarks, when it has no hit.
+    did_you_mean: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TagFilter {
+    And(Vec<String>),
+    Or(Vec<String>),
+    Any,
+    Untagged,
+    /// The matches of one of the user's saved searches.
+    SavedSearch(Uuid),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    #[default]
+    FullText,
+    /// Full text, plus trigram matches of the free text on the title, domain
+    /// and url, which tolerate typos and partial words.
+    Fuzzy,
+    /// Ranks by the similarity of the free text with the closest chunk of
+    /// each bookmark.
+    Semantic,
+    /// Reciprocal rank fusion of the full text and semantic rankings.
+    Hybrid,
+}
+
+impl SearchMode {
+    fn needs_embedding(self) -> bool {
+        matches!(self, SearchMode::Semantic | SearchMode::Hybrid)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchRequest {
+    query: Option<String>,
+    tags_filter: Option<TagFilter>,
+    facet_filters: Option<FacetFilters>,
+    limit: Option<i32>,
+    sort: Option<Sort>,
+    cursor: Option<String>,
+    mode: Option<SearchMode>,
+    /// Only the read, or unread, bookmarks.
+    read: Option<bool>,
+    favorite: Option<bool>,
+    archived: Option<bool>,
+    /// Bounds of the reading time, in minutes, both included.
+    min_reading_time: Option<i32>,
+    max_reading_time: Option<i32>,
+    min_word_count: Option<i32>,
+    max_word_count: Option<i32>,
+    /// Bounds of the extraction ratio, from 0 to 1, the pages without one
+    /// are left out.
+    min_extraction_ratio: Option<f32>,
+    max_extraction_ratio: Option<f32>,
+}
+
+impl SearchRequest {
+    /// The request to save, rejects what would fail every time it is run.
+    pub fn validate(mut self) -> Result<Self> {
+        parser::parse(self.query.as_deref().unwrap_or_default())?;
+        if matches!(self.tags_filter, Some(TagFilter::SavedSearch(_))) {
+            return Err(Error::bad_request([(
+                "tags_filter",
+                "a saved search can't refer to another saved search",
+            )]));
+        }
+        self.cursor = None;
+        Ok(self)
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<String>) -> Self {
+        self.cursor = cursor;
+        self
+    }
+}
+
+/// What a request is compiled with, resolved once per search.
+#[derive(Debug)]
+struct SearchContext {
+    user_id: Uuid,
+    query: Query,
+    language: Language,
+    /// The embedding of the free text, in the semantic modes.
+    embedding: Option<Vector>,
+    /// Only the bookmarks saved after this.
+    since: Option<DateTime<Utc>>,
+    /// The saved search of a `TagFilter::SavedSearch`.
+    saved: Option<Box<(SearchRequest, SearchContext)>>,
+}
+
+impl SearchContext {
+    async fn resolve(
+        pool: &PgPool,
+        embedder: Option<&Arc<Embedder>>,
+        user_id: Uuid,
+        request: &SearchRequest,
+    ) -> Result<Self> {
+        let mut context = Self::resolve_request(pool, embedder, user_id, request).await?;
+        if let Some(TagFilter::SavedSearch(saved_search_id)) = request.tags_filter {
+            let saved = saved_search::get(pool, user_id, saved_search_id)
+                .await?
+                .ok_or_else(|| Error::bad_request([("tags_filter", "unknown saved search")]))?
+                .request()?
+                .validate()?;
+            let saved_context = Self::resolve_request(pool, embedder, user_id, &saved).await?;
+            context.saved = Some(Box::new((saved, saved_context)));
+        }
+        Ok(context)
+    }
+
+    /// The context of `request` alone, without the saved search it refers to.
+    async fn resolve_request(
+        pool: &PgPool,
+        embedder: Option<&Arc<Embedder>>,
+        user_id: Uuid,
+        request: &SearchRequest,
+    ) -> Result<Self> {
+        let query = parser::parse(request.query.as_deref().unwrap_or_default())?;
+        let language = settings::get(pool, user_id).await?.query_language;
+        let embedding = match (&query.text, request.mode.unwrap_or_default()) {
+            (Some(text), mode) if mode.needs_embedding() => {
+                let embedder = embedder.cloned().ok_or_else(|| {
+                    Error::bad_request([("mode", "semantic search is not enabled")])
+                })?;
+                let texts = vec![text.clone()];
+                let embedding = tokio::task::spawn_blocking(move || embedder.embed(&texts))
+                    .await
+                    .map_err(anyhow::Error::from)??
+                    .pop();
+                embedding.map(Vector::from)
+            }
+            _ => None,
+        };
+        Ok(Self {
+            user_id,
+            query,
+            language,
+            embedding,
+            since: None,
+            saved: None,
+        })
+    }
+}
+
+/// The joins and `WHERE` conditions of a request, shared by the search,
+/// aggregation and total queries.
+#[derive(Debug)]
+struct Filters {
+    joins: String,
+    sql: String,
+    /// The free text, unless the search is semantic only.
+    tsquery: Option<TsQuery>,
+    /// The relevance of a bookmark, when there is free text.
+    rank: Option<String>,
+    /// Placeholder of the free text embedding, in semantic mode.
+    embedding: Option<String>,
+}
+
+/// Placeholders of the free text and of the user's query language.
+#[derive(Debug)]
+struct TsQuery {
+    text: String,
+    language: String,
+}
+
+impl TsQuery {
+    /// The free text parsed with the user's query language and with the
+    /// language of the bookmark in `table`, so every bookmark is matched with
+    /// its own stemming.
+    fn on(&self, table: &str) -> String {
+        let TsQuery { text, language } = self;
+        format!(
+            "(websearch_to_tsquery({language}::text::regconfig, {text}) || websearch_to_tsquery({table}.language, {text}))"
+        )
+    }
+}
+
+/// Whether the bookmark has `tag` or one of its descendants.
+fn has_tag(tag: &str) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM unnest(bu.tags) t WHERE t = {tag} OR starts_with(t, {tag} || '{SEPARATOR}'))"
+    )
+}
+
+fn compile_filters(
+    request: &SearchRequest,
+    context: &SearchContext,
+    params: &mut SqlParams,
+) -> Filters {
+    let SearchContext {
+        query, language, ..
+    } = context;
+    let mut joins = String::new();
+    let mut sql = String::new();
+    let mut rank = None;
+    let mut embedding = None;
+    let tsquery = query.text.as_ref().and_then(|text| {
+        let mode = request.mode.unwrap_or_default();
+        if let (SearchMode::Semantic, Some(vector)) = (mode, &context.embedding) {
+            let vector = params.push(vector.clone());
+            joins.push_str(&similarity_join(&vector));
+            sql.push_str(&format!(" AND s.similarity >= {SEMANTIC_THRESHOLD} "));
+            rank = Some("s.similarity::real".to_owned());
+            embedding = Some(vector);
+            return None;
+        }
+        let tsquery = TsQuery {
+            text: params.push(text.clone()),
+            language: params.push(language.config()),
+        };
+        let matches = format!("bu.search_tokens @@ {}", tsquery.on("b"));
+        let ts_rank = format!("ts_rank(bu.search_tokens, {})", tsquery.on("b"));
+        match (mode, &context.embedding) {
+            (SearchMode::Hybrid, Some(vector)) => {
+                joins.push_str(&similarity_join(&params.push(vector.clone())));
+                let similar = format!("s.similarity >= {SEMANTIC_THRESHOLD}");
+                sql.push_str(&format!(" AND ({matches} OR {similar}) "));
+                let full_text_rank = reciprocal_rank(&matches, &ts_rank);
+                let semantic_rank = reciprocal_rank(&similar, "s.similarity");
+                rank = Some(format!("({full_text_rank} + {semantic_rank})::real"));
+            }
+            (SearchMode::Fuzzy, _) => {
+                let text = &tsquery.text;
+                let similarity = format!(
+                    "greatest(word_similarity({text}, b.title), word_similarity({text}, b.domain), word_similarity({text}, b.url))"
+                );
+                sql.push_str(&format!(
+                    " AND ({matches} OR {similarity} >= {FUZZY_THRESHOLD}) "
+                ));
+                rank = Some(format!("({ts_rank} + {similarity})"));
+            }
+            _ => {
+                sql.push_str(&format!(" AND {matches} "));
+                rank = Some(ts_rank);
+            }
+        }
+        Some(tsquery)
+    });
+    for filter in &query.filters {
+        let condition = match &filter.term {
+            Term::Tag(tag) => has_tag(&format!("{}::text", params.push(tag.clone()))),
+            Term::Domain(domain) => {
+                // Compared as is, `_` or `%` in the value aren't wildcards.
+                let domain = params.push(domain.clone());
+                format!(
+                    "(b.domain = {domain} OR right(b.domain, length({domain}) + 1) = '.' || {domain})"
+                )
+            }
+            Term::Title(title) => {
+                format!(
+                    "strpos(lower(b.title), lower({})) > 0",
+                    params.push(title.clone())
+                )
+            }
+            Term::After(day) => format!("bu.created_at >= {}", params.push(start_of(*day))),
+            Term::Before(day) => format!("bu.created_at < {}", params.push(start_of(*day))),
+            Term::Is(State::Tagged) => "cardinality(bu.tags) > 0".to_owned(),
+            Term::Is(State::Untagged) => "cardinality(bu.tags) = 0".to_owned(),
+            Term::Is(State::Read) => "bu.read_at IS NOT NULL".to_owned(),
+            Term::Is(State::Unread) => "bu.read_at IS NULL".to_owned(),
+            Term::Is(State::Favorite) => "bu.favorite".to_owned(),
+            Term::Is(State::Archived) => "bu.archived".to_owned(),
+            Term::ReadingTime(Bound::Under(minutes)) => {
+                format!("b.reading_time < {}", params.push(*minutes))
+            }
+            Term::ReadingTime(Bound::Over(minutes)) => {
+                format!("b.reading_time > {}", params.push(*minutes))
+            }
+            Term::WordCount(Bound::Under(words)) => {
+                format!("b.word_count < {}", params.push(*words))
+            }
+            Term::WordCount(Bound::Over(words)) => {
+                format!("b.word_count > {}", params.push(*words))
+            }
+        };
+        if filter.negated {
+            sql.push_str(&format!(" AND NOT {condition} "));
+        } else {
+            sql.push_str(&format!(" AND {condition} "));
+        }
+    }
+    match request.tags_filter.clone().unwrap_or(TagFilter::Any) {
+        TagFilter::And(tags) => sql.push_str(&format!(
+            " AND NOT EXISTS (SELECT 1 FROM unnest({}::text[]) f WHERE NOT {}) ",
+            params.push(tags),
+            has_tag("f")
+        )),
+        TagFilter::Or(tags) => sql.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM unnest({}::text[]) f WHERE {}) ",
+            params.push(tags),
+            has_tag("f")
+        )),
+        TagFilter::Untagged => sql.push_str(" AND cardinality(bu.tags) = 0 "),
+        TagFilter::SavedSearch(_) => {
+            if let Some((saved, saved_context)) = context.saved.as_deref() {
+                let saved = compile_filters(saved, saved_context, params);
+                sql.push_str(&format!(
+                    " AND bu.bookmark_user_id IN (
+                    SELECT bu.bookmark_user_id FROM bookmark_user bu
+                    INNER JOIN bookmark b USING(bookmark_id) {}
+                    WHERE bu.user_id = {} {}) ",
+                    saved.joins,
+                    params.push(context.user_id),
+                    saved.sql
+                ));
+            }
+        }
+        TagFilter::Any => {}
+    }
+    if let Some(read) = request.read {
+        sql.push_str(&format!(
+            " AND (bu.read_at IS NOT NULL) = {} ",
+            params.push(read)
+        ));
+    }
+    if let Some(favorite) = request.favorite {
+        sql.push_str(&format!(" AND bu.favorite = {} ", params.push(favorite)));
+    }
+    if let Some(archived) = request.archived {
+        sql.push_str(&format!(" AND bu.archived = {} ", params.push(archived)));
+    }
+    if let Some(minutes) = request.min_reading_time {
+        sql.push_str(&format!(" AND b.reading_time >= {} ", params.push(minutes)));
+    }
+    if let Some(minutes) = request.max_reading_time {
+        sql.push_str(&format!(" AND b.reading_time <= {} ", params.push(minutes)));
+    }
+    if let Some(words) = request.min_word_count {
+        sql.push_str(&format!(" AND b.word_count >= {} ", params.push(words)));
+    }
+    if let Some(words) = request.max_word_count {
+        sql.push_str(&format!(" AND b.word_count <= {} ", params.push(words)));
+    }
+    if let Some(ratio) = request.min_extraction_ratio {
+        sql.push_str(&format!(
+            " AND b.extraction_ratio >= {} ",
+            params.push(ratio)
+        ));
+    }
+    if let Some(ratio) = request.max_extraction_ratio {
+        sql.push_str(&format!(
+            " AND b.extraction_ratio <= {} ",
+            params.push(ratio)
+        ));
+    }
+    if let Some(since) = context.since {
+        sql.push_str(&format!(" AND bu.created_at > {} ", params.push(since)));
+    }
+    if let Some(facet_filters) = &request.facet_filters {
+        sql.push_str(&facet::compile(facet_filters, params));
+    }
+    Filters {
+        joins,
+        sql,
+        tsquery,
+        rank,
+        embedding,
+    }
+}
+
+fn start_of(day: NaiveDate) -> DateTime<Utc> {
+    day.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// Joins `s.similarity`, the cosine similarity of the closest chunk of the
+/// bookmark to the `vector` placeholder, null when it has no chunk.
+fn similarity_join(vector: &str) -> String {
+    format!(
+        " LEFT JOIN LATERAL (
+        SELECT 1 - min(c.embedding <=> {vector}) AS similarity
+        FROM bookmark_chunk c WHERE c.bookmark_id = b.bookmark_id
+        ) s ON true "
+    )
+}
+
+/// The reciprocal rank fusion term of the bookmarks matching `condition`,
+/// ranked by `score`.
+fn reciprocal_rank(condition: &str, score: &str) -> String {
+    format!(
+        "coalesce(1.0 / ({RRF_K} + CASE WHEN {condition} THEN rank() OVER (PARTITION BY {condition} ORDER BY {score} DESC) END), 0)"
+    )
+}
+
+#[instrument(skip(pool, embedder))]
+pub async fn search(
+    pool: &PgPool,
+    embedder: Option<&Arc<Embedder>>,
+    user_id: Uuid,
+    request: &SearchRequest,
+) -> Result<SearchResponse> {
+    let cursor = request.cursor.as_deref().map(Cursor::decode).transpose()?;
+    let context = SearchContext::resolve(pool, embedder, user_id, request).await?;
+    let client = pool.get().await?;
+    let f_search = run_search(&client, user_id, request, &context, cursor.as_ref()).map_err(|e| {
+        warn!("Search query fail");
+        e
+    });
+    let f_aggregation = run_aggregation(&client, user_id, request, &context).map_err(|e| {
+        warn!("Aggregation query fail");
+        e
+    });
+    let f_total = run_total(&client, user_id, request, &context).map_err(|e| {
+        warn!("Total query fail");
+        e
+    });
+    let ((bookmarks, next_cursor), (tags, facets), total) =
+        try_join!(f_search, f_aggregation, f_total)?;
+    let did_you_mean = match (&context.query.text, &request.query) {
+        (Some(text), Some(raw)) if total == 0 => {
+            suggestion::did_you_mean(&client, user_id, raw, text).await?
+        }
+        _ => None,
+    };
+    Ok(SearchResponse {
+        bookmarks,
+        tags,
+        facets,
+        total,
+        next_cursor,
+        did_you_mean,
+    })
+}
+
+/// How many bookmarks saved after `since` match the request. The free text
+/// isn't embedded, semantic and hybrid requests count their full text matches.
+#[instrument(skip(pool))]
+pub async fn count_since(
+    pool: &PgPool,
+    user_id: Uuid,
+    request: &SearchRequest,
+    since: DateTime<Utc>,
+) -> Result<u64> {
+    let request = &SearchRequest {
+        mode: None,
+        ..request.clone()
+    };
+    let mut context = SearchContext::resolve(pool, None, user_id, request).await?;
+    context.since = Some(since);
+    let client = pool.get().await?;
+    run_total(&client, user_id, request, &context).await
+}
+
+/// Applies `operation` to every match of the request, regardless of its limit
+/// and cursor, in a single statement. Returns the number of bookmarks updated.
+#[instrument(skip(pool, embedder))]
+pub async fn update_tags(
+    pool: &PgPool,
+    embedder: Option<&Arc<Embedder>>,
+    user_id: Uuid,
+    request: &SearchRequest,
+    operation: &TagOperation,
+) -> Result<u64> {
+    let context = SearchContext::resolve(pool, embedder, user_id, request).await?;
+    let mut params = SqlParams::default();
+    let user = params.push(user_id);
+    let tags = params.push(operation.tags().to_vec());
+    let filters = compile_filters(request, &context, &mut params);
+    let sql = format!(
+        "UPDATE bookmark_user SET tags = {expression}, updated_at = now()
+        WHERE tags IS DISTINCT FROM {expression} AND bookmark_user_id IN (
+            SELECT bu.bookmark_user_id FROM bookmark_user bu
+            INNER JOIN bookmark b USING (bookmark_id) {} WHERE bu.user_id = {user} {}
+        )",
+        filters.joins,
+        filters.sql,
+        expression = operation.expression(&tags),
+    );
+    debug!(?sql, ?params, "Update tags query");
+    let client = pool.get().await?;
+    let updated = client.execute(&sql, &params.as_refs()).await?;
+    Ok(updated)
+}
+
+#[instrument(skip(client))]
+async fn run_total(
+    client: &PgConnection,
+    user_id: Uuid,
+    request: &SearchRequest,
+    context: &SearchContext,
+) -> Result<u64> {
+    let mut params = SqlParams::default();
+    let user = params.push(user_id);
+    let filters = compile_filters(request, context, &mut params);
+    let sql = format!(
+        "SELECT COUNT(1) FROM bookmark_user bu
+        INNER JOIN bookmark b USING (bookmark_id) {} WHERE bu.user_id = {user} {}",
+        filters.joins, filters.sql
+    );
+    debug!(?sql, ?params, "Total query");
+    let row = client.query_one(&sql, &params.as_refs()).await?;
+    let total: i64 = row.try_get(0).map_err(Error::from)?;
+    Ok(total as u64)
+}
+
+/// Counts the matches per tag, and ancestor, and per value of every facet.
+#[instrument(skip(client))]
+async fn run_aggregation(
+    client: &PgConnection,
+    user_id: Uuid,
+    request: &SearchRequest,
+    context: &SearchContext,
+) -> Result<(Vec<TagNode>, Facets)> {
+    let mut params = SqlParams::default();
+    let user = params.push(user_id);
+    let filters = compile_filters(request, context, &mut params);
+    let columns: String = Facet::ALL
+        .iter()
+        .map(|facet| format!(", {} AS {}", facet.expression(), <&str>::from(facet)))
+        .collect();
+    let counts: String = Facet::ALL
+        .iter()
+        .map(|facet| {
+            let name = <&str>::from(facet);
+            format!(
+                " UNION ALL SELECT '{name}', {name}, count(1) FROM matches
+                WHERE {name} IS NOT NULL GROUP BY {name}"
+            )
+        })
+        .collect();
+    let sql = format!(
+        "WITH matches AS (
+        SELECT bu.tags {columns} FROM bookmark_user bu
+        INNER JOIN bookmark b USING(bookmark_id) {joins}
+        WHERE bu.user_id = {user} {filters}
+        )
+        SELECT 'tag' AS facet, tag AS value, count(1) AS count FROM matches,
+        LATERAL (
+            SELECT DISTINCT array_to_string((string_to_array(t, '{SEPARATOR}'))[1:n], '{SEPARATOR}') AS tag
+            FROM unnest(tags) t, generate_series(1, cardinality(string_to_array(t, '{SEPARATOR}'))) n
+        ) ancestors
+        GROUP BY tag {counts}
+        ORDER BY facet, count DESC, value",
+        joins = filters.joins,
+        filters = filters.sql,
+    );
+    debug!(%sql, ?params, "Aggregation query");
+    let mut tags = Vec::new();
+    let mut facets: Facets = Facet::ALL.into_iter().map(|f| (f, Vec::new())).collect();
+    for row in client.query(&sql, &params.as_refs()).await? {
+        let facet: String = row.try_get("facet")?;
+        let count = FacetCount::try_from_row(&row)?;
+        match facet.as_str() {
+            "tag" => tags.push((count.value, count.count)),
+            facet => facets
+                .entry(Facet::from_str(facet).map_err(anyhow::Error::from)?)
+                .or_default()
+                .push(count),
+        }
+    }
+    Ok((TagNode::tree(&tags), facets))
+}
+
+#[instrument(skip(client))]
+async fn run_search(
+    client: &PgConnection,
+    user_id: Uuid,
+    request: &SearchRequest,
+    context: &SearchContext,
+    cursor: Option<&Cursor>,
+) -> Result<(Vec<SearchResultItem>, Option<String>)> {
+    let mut params = SqlParams::default();
+    let user = params.push(user_id);
+    let filters = compile_filters(request, context, &mut params);
+    let sort = request
+        .sort
+        .unwrap_or_default()
+        .effective(filters.rank.is_some());
+    let inner = format!(
+        "SELECT b.*, bu.user_id, bu.tags, bu.favorite, bu.archived, bu.read_at, bu.progress,
+        bu.created_at AS user_created_at, bu.updated_at AS user_updated_at, {sort_key} AS sort_key
+        FROM bookmark_user bu INNER JOIN bookmark b USING(bookmark_id) {joins}
+        WHERE bu.user_id = {user} {filters}",
+        sort_key = sort.key_expression(filters.rank.as_deref()),
+        joins = filters.joins,
+        filters = filters.sql,
+    );
+    let select = match (&filters.embedding, &filters.tsquery) {
+        (Some(vector), _) => format!(
+            "r.*, (SELECT c.content FROM bookmark_chunk c WHERE c.bookmark_id = r.bookmark_id
+            ORDER BY c.embedding <=> {vector} LIMIT 1) AS search_match"
+        ),
+        (None, Some(tsquery)) => format!(
+            "r.*, ts_headline(r.language, r.text_content, {}, {}) AS search_match",
+            tsquery.on("r"),
+            params.push(snippet::headline_options(SNIPPET_FRAGMENTS)),
+        ),
+        (None, None) => "r.*, NULL::text AS search_match".to_owned(),
+    };
+    let limit = pagination::page_size(request.limit.map(i64::from));
+    let sql = pagination::paginate(&inner, &select, sort, cursor, limit, &mut params)?;
+
+    debug!(?sql, ?params, %user_id, "Search query");
+
+    let rows = client.query(&sql, &params.as_refs()).await?;
+    let semantic = filters.embedding.is_some();
+    pagination::split_page(rows, sort, limit, |row| {
+        Ok(SearchRow::try_from_row(row)?.into_item(semantic))
+    })
+}
@@ -0,0 +1,253 @@
+use chrono::NaiveDate;
+use std::fmt;
+
+use crate::error::Error;
+
+/// A search query split into free text and field filters, e.g.
+/// `tokio runtime tag:rust -tag:draft domain:tokio.rs title:"async" after:2024-01-01 time:<10 words:>500`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Query {
+    /// Free text, in `websearch_to_tsquery` syntax.
+    pub text: Option<String>,
+    pub filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub term: Term,
+    pub negated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Tag(String),
+    /// The domain or one of its subdomains.
+    Domain(String),
+    /// Case insensitive substring of the title.
+    Title(String),
+    /// Saved on or after the day.
+    After(NaiveDate),
+    /// Saved before the day.
+    Before(NaiveDate),
+    Is(State),
+    /// The reading time in minutes, e.g. `time:<10`.
+    ReadingTime(Bound),
+    /// The number of words, e.g. `words:>500`.
+    WordCount(Bound),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Under(i32),
+    Over(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    Tagged,
+    Untagged,
+    Read,
+    Unread,
+    Favorite,
+    Archived,
+}
+
+/// A syntax error, `position` is the offset in characters where it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::bad_request([("query", error.to_string())])
+    }
+}
+
+const FIELDS: [&str; 8] = [
+    "tag", "domain", "title", "after", "before", "is", "time", "words",
+];
+
+pub fn parse(input: &str) -> Result<Query, ParseError> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        position: 0,
+    };
+    let mut text = Vec::new();
+    let mut filters = Vec::new();
+    while parser.skip_whitespace() {
+        let start = parser.position;
+        let negated = parser.eat('-');
+        match parser.field() {
+            Some(field) => {
+                let value_start = parser.position;
+                let value = parser.value()?;
+                if value.is_empty() {
+                    return Err(parser.error(value_start, format!("missing value for {field}:")));
+                }
+                let term = term(field, value, value_start, &parser)?;
+                filters.push(Filter { term, negated });
+            }
+            None => {
+                parser.position = start;
+                text.push(parser.text()?);
+            }
+        }
+    }
+    let text = Some(text.join(" ")).filter(|text| !text.is_empty());
+    Ok(Query { text, filters })
+}
+
+fn term(field: &str, value: String, position: usize, parser: &Parser) -> Result<Term, ParseError> {
+    let date = |value: &str| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            parser.error(
+                position,
+                format!("invalid date {value:?}, expected YYYY-MM-DD"),
+            )
+        })
+    };
+    let term = match field {
+        "tag" => Term::Tag(value),
+        "domain" => Term::Domain(value.to_lowercase()),
+        "title" => Term::Title(value),
+        "after" => Term::After(date(&value)?),
+        "before" => Term::Before(date(&value)?),
+        "is" => match value.as_str() {
+            "tagged" => Term::Is(State::Tagged),
+            "untagged" => Term::Is(State::Untagged),
+            "read" => Term::Is(State::Read),
+            "unread" => Term::Is(State::Unread),
+            "favorite" => Term::Is(State::Favorite),
+            "archived" => Term::Is(State::Archived),
+            _ => return Err(parser.error(position, format!("unknown state {value:?}"))),
+        },
+        "time" => Term::ReadingTime(bound(&value).ok_or_else(|| {
+            parser.error(
+                position,
+                format!("invalid time {value:?}, expected <minutes or >minutes"),
+            )
+        })?),
+        "words" => Term::WordCount(bound(&value).ok_or_else(|| {
+            parser.error(
+                position,
+                format!("invalid words {value:?}, expected <count or >count"),
+            )
+        })?),
+        _ => unreachable!("unknown field {field}"),
+    };
+    Ok(term)
+}
+
+fn bound(value: &str) -> Option<Bound> {
+    let number = |n: &str| n.parse::<i32>().ok().filter(|n| *n >= 0);
+    if let Some(n) = value.strip_prefix('<') {
+        number(n).map(Bound::Under)
+    } else if let Some(n) = value.strip_prefix('>') {
+        number(n).map(Bound::Over)
+    } else {
+        None
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn error(&self, position: usize, message: String) -> ParseError {
+        ParseError { position, message }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `false` at the end of the input.
+    fn skip_whitespace(&mut self) -> bool {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+        self.peek().is_some()
+    }
+
+    /// Consumes a known `field:` prefix.
+    fn field(&mut self) -> Option<&'static str> {
+        let rest = &self.chars[self.position..];
+        let field = FIELDS.into_iter().find(|field| {
+            rest.len() > field.len()
+                && rest[field.len()] == ':'
+                && rest
+                    .iter()
+                    .zip(field.chars())
+                    .all(|(a, b)| a.eq_ignore_ascii_case(&b))
+        })?;
+        self.position += field.len() + 1;
+        Some(field)
+    }
+
+    /// A quoted string or everything up to the next whitespace.
+    fn value(&mut self) -> Result<String, ParseError> {
+        if self.peek() == Some('"') {
+            return self.quoted();
+        }
+        let start = self.position;
+        while self.peek().is_some_and(|c| !c.is_whitespace()) {
+            self.position += 1;
+        }
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+
+    fn quoted(&mut self) -> Result<String, ParseError> {
+        let start = self.position;
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.position += 1;
+                    return Ok(value);
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.position += 1;
+                }
+                None => return Err(self.error(start, "unterminated quote".to_owned())),
+            }
+        }
+    }
+
+    /// A free text word or quoted phrase, kept verbatim for `websearch_to_tsquery`.
+    fn text(&mut self) -> Result<String, ParseError> {
+        let start = self.position;
+        self.eat('-');
+        if self.peek() == Some('"') {
+            self.quoted()?;
+        } else {
+            while self.peek().is_some_and(|c| !c.is_whitespace() && c != '"') {
+                self.position += 1;
+            }
+            if self.peek() == Some('"') {
+                return Err(self.error(self.position, "unexpected quote".to_owned()));
+            }
+        }
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+/// Private use characters `ts_headline` marks the matches and separates the
+/// fragments with, they can't be confused with the content.
+const START: char = '\u{E000}';
+const STOP: char = '\u{E001}';
+const DELIMITER: char = '\u{E002}';
+
+/// A fragment of the text content of a hit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub text: String,
+    /// The matches in `text`.
+    pub highlights: Vec<Highlight>,
+    /// Offset of `text` in the text content, in characters.
+    pub position: Option<usize>,
+    /// Where the first match is in the archived page.
+    pub anchor: Option<Anchor>,
+}
+
+/// A range of characters, `end` excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The `occurrence`-th (from 0) case insensitive occurrence of `term` in the
+/// text content.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub term: String,
+    pub occurrence: usize,
+}
+
+/// The `ts_headline` options producing what `parse` expects.
+pub fn headline_options(max_fragments: usize) -> String {
+    format!(
+        "StartSel={START}, StopSel={STOP}, FragmentDelimiter={DELIMITER}, MaxFragments={max_fragments}, MinWords=8, MaxWords=24"
+    )
+}
+
+/// Splits a headline into its fragments, located in `text_content`.
+pub fn parse(headline: &str, text_content: &str) -> Vec<Snippet> {
+    headline
+        .split(DELIMITER)
+        .filter_map(|fragment| parse_fragment(fragment, text_content))
+        .collect()
+}
+
+/// A fragment without highlight, such as a chunk matched semantically.
+pub fn plain(text: &str, text_content: &str) -> Snippet {
+    let text = text.trim().to_owned();
+    Snippet {
+        position: char_position(text_content, &text),
+        text,
+        highlights: Vec::new(),
+        anchor: None,
+    }
+}
+
+fn parse_fragment(fragment: &str, text_content: &str) -> Option<Snippet> {
+    let leading = fragment
+        .chars()
+        .take_while(|c| c.is_whitespace() || *c == START || *c == STOP)
+        .filter(|c| c.is_whitespace())
+        .count();
+    let mut text = String::with_capacity(fragment.len());
+    let mut highlights = Vec::new();
+    let mut length: usize = 0;
+    let mut start = None;
+    for c in fragment.chars() {
+        match c {
+            START => start = Some(length),
+            STOP => {
+                if let Some(start) = start.take() {
+                    highlights.push(Highlight {
+                        start: start.saturating_sub(leading),
+                        end: length - leading,
+                    });
+                }
+            }
+            c => {
+                text.push(c);
+                length += 1;
+            }
+        }
+    }
+    let text = text.trim().to_owned();
+    if text.is_empty() {
+        return None;
+    }
+    let position = char_position(text_content, &text);
+    let anchor = match (position, highlights.first()) {
+        (Some(position), Some(highlight)) => {
+            let term: String = text
+                .chars()
+                .skip(highlight.start)
+                .take(highlight.end - highlight.start)
+                .collect();
+            let before: String = text_content
+                .chars()
+                .take(position + highlight.start)
+                .collect();
+            let occurrence = before.to_lowercase().matches(&term.to_lowercase()).count();
+            Some(Anchor { term, occurrence })
+        }
+        _ => None,
+    };
+    Some(Snippet {
+        text,
+        highlights,
+        position,
+        anchor,
+    })
+}
+
+fn char_position(text_content: &str, text: &str) -> Option<usize> {
+    text_content
+        .find(text)
+        .map(|index| text_content[..index].chars().count())
+}
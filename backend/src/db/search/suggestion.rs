@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use deadpool_postgres::GenericClient;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+use crate::db::PgConnection;
+use crate::error::Result;
+
+/// Minimum trigram `similarity` between a query word and its replacement. The
+/// `%` operator, which uses the index, filters with pg_trgm's default of 0.3
+/// first.
+const SUGGESTION_THRESHOLD: f32 = 0.3;
+/// Shorter words have too few trigrams to be compared.
+const MIN_WORD_CHARS: usize = 3;
+
+/// Replaces the words of the free `text` in the `raw` query by the most similar
+/// words of the user's bookmarks, kept in `search_word`. `None` when no word
+/// has a replacement.
+#[instrument(skip(client))]
+pub async fn did_you_mean(
+    client: &PgConnection,
+    user_id: Uuid,
+    raw: &str,
+    text: &str,
+) -> Result<Option<String>> {
+    const SQL: &str = r#"
+    SELECT DISTINCT ON (q.word) q.word, w.word AS suggestion
+    FROM unnest($2::text[]) q(word)
+    INNER JOIN search_word w
+        ON w.user_id = $1 AND w.word % q.word AND w.word <> q.word
+        AND similarity(q.word, w.word) >= $3
+    WHERE NOT EXISTS (
+        SELECT 1 FROM search_word known WHERE known.user_id = $1 AND known.word = q.word
+    )
+    ORDER BY q.word, similarity(q.word, w.word) DESC, w.word;"#;
+    let words = words(text);
+    if words.is_empty() {
+        return Ok(None);
+    }
+    let replacements = client
+        .query(SQL, &[&user_id, &words, &SUGGESTION_THRESHOLD])
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+        .collect::<Result<HashMap<String, String>>>()?;
+    debug!(?replacements, "Query suggestions");
+    if replacements.is_empty() {
+        return Ok(None);
+    }
+    let suggestion = raw
+        .split_whitespace()
+        .map(|token| {
+            let word = token.trim_start_matches(['-', '"']).trim_end_matches('"');
+            match replacements.get(&word.to_lowercase()) {
+                Some(replacement) => token.replacen(word, replacement, 1),
+                None => token.to_owned(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(Some(suggestion))
+}
+
+/// The lowercased words of the free text, without `websearch_to_tsquery`
+/// operators.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_WORD_CHARS && !word.eq_ignore_ascii_case("or"))
+        .map(str::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use serde::Serialize;
+use tokio_postgres::Row;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::language::Language;
+use crate::tag::TagPolicy;
+
+use super::{tag_alias, PgPool};
+
+const COLUMNS: &str = "query_language::text AS query_language, tag_lowercase, tag_allowed_chars,
+    tag_suggestion_threshold, updated_at";
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UserSettings {
+    /// The text search configuration queries are parsed with.
+    pub query_language: Language,
+    pub tag_policy: TagPolicy,
+    /// Tag suggestions at least this confident are applied right away, `None`
+    /// leaves them all pending.
+    pub tag_suggestion_threshold: Option<f32>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<&Row> for UserSettings {
+    type Error = Error;
+
+    fn try_from(row: &Row) -> Result<Self> {
+        let query_language: String = row.try_get("query_language")?;
+        let query_language = query_language
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Unknown text search configuration: {query_language}"))?;
+        Ok(Self {
+            query_language,
+            tag_policy: TagPolicy {
+                lowercase: row.try_get("tag_lowercase")?,
+                allowed_chars: row.try_get("tag_allowed_chars")?,
+                ..Default::default()
+            },
+            tag_suggestion_threshold: row.try_get("tag_suggestion_threshold")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// The user's settings, the defaults when none were saved yet.
+#[instrument(skip(pool))]
+pub async fn get(pool: &PgPool, user_id: Uuid) -> Result<UserSettings> {
+    let sql = format!("SELECT {COLUMNS} FROM user_settings WHERE user_id = $1;");
+    let client = pool.get().await?;
+    client
+        .query_opt(&sql, &[&user_id])
+        .await?
+        .map(|row| UserSettings::try_from(&row))
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+#[instrument(skip(pool))]
+pub async fn update(
+    pool: &PgPool,
+    user_id: Uuid,
+    query_language: Option<Language>,
+    tag_policy: Option<&TagPolicy>,
+    tag_suggestion_threshold: Option<Option<f32>>,
+) -> Result<UserSettings> {
+    // The tag policy is replaced as a whole, `$3` is only null without one.
+    // `$5` tells a threshold set to null from no threshold given.
+    let sql = format!(
+        r#"
+        INSERT INTO user_settings AS s
+        (user_id, query_language, tag_lowercase, tag_allowed_chars, tag_suggestion_threshold)
+        VALUES ($1, coalesce($2::text, 'english')::regconfig, coalesce($3, false), $4, $6)
+        ON CONFLICT (user_id) DO UPDATE SET
+            query_language = coalesce($2::text::regconfig, s.query_language),
+            tag_lowercase = coalesce($3, s.tag_lowercase),
+            tag_allowed_chars = CASE WHEN $3 IS NULL THEN s.tag_allowed_chars ELSE $4 END,
+            tag_suggestion_threshold =
+                CASE WHEN $5 THEN $6 ELSE s.tag_suggestion_threshold END,
+            updated_at = now()
+        RETURNING {COLUMNS};"#
+    );
+    let client = pool.get().await?;
+    let row = client
+        .query_one(
+            &sql,
+            &[
+                &user_id,
+                &query_language.map(Language::config),
+                &tag_policy.map(|p| p.lowercase),
+                &tag_policy.and_then(|p| p.allowed_chars.as_deref()),
+                &tag_suggestion_threshold.is_some(),
+                &tag_suggestion_threshold.flatten(),
+            ],
+        )
+        .await?;
+    let settings = UserSettings::try_from(&row)?;
+    info!(?settings, "User settings updated");
+    Ok(settings)
+}
+
+/// The user's tag policy along with their aliases.
+#[instrument(skip(pool))]
+pub async fn tag_policy(pool: &PgPool, user_id: Uuid) -> Result<TagPolicy> {
+    let mut policy = get(pool, user_id).await?.tag_policy;
+    policy.aliases = tag_alias::get_by_user(pool, user_id)
+        .await?
+        .into_iter()
+        .map(|a| (a.alias, a.tag))
+        .collect();
+    Ok(policy)
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use postgres_from_row::FromRow;
+use serde::Serialize;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+use super::PgPool;
+
+/// `alias` is written as `tag`.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TagAlias {
+    pub alias: String,
+    pub tag: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[instrument(skip(pool))]
+pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<TagAlias>> {
+    const SQL: &str = "SELECT * FROM tag_alias WHERE user_id = $1 ORDER BY alias;";
+    let client = pool.get().await?;
+    let result = client
+        .query(SQL, &[&user_id])
+        .await?
+        .iter()
+        .map(|row| TagAlias::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+/// Creates the alias or points it to another tag.
+#[instrument(skip(pool))]
+pub async fn upsert(pool: &PgPool, user_id: Uuid, alias: &str, tag: &str) -> Result<TagAlias> {
+    const SQL: &str = r#"
+    INSERT INTO tag_alias (user_id, alias, tag) VALUES ($1, $2, $3)
+    ON CONFLICT (user_id, alias) DO UPDATE SET tag = EXCLUDED.tag
+    RETURNING tag_alias.*;"#;
+    let client = pool.get().await?;
+    let row = client.query_one(SQL, &[&user_id, &alias, &tag]).await?;
+    let result = TagAlias::try_from_row(&row)?;
+    info!(%alias, %tag, "Tag alias saved");
+    Ok(result)
+}
+
+#[instrument(skip(pool))]
+pub async fn delete(pool: &PgPool, user_id: Uuid, alias: &str) -> Result<bool> {
+    const SQL: &str = "DELETE FROM tag_alias WHERE user_id = $1 AND alias = $2;";
+    let client = pool.get().await?;
+    let deleted = client.execute(SQL, &[&user_id, &alias]).await?;
+    Ok(deleted > 0)
+}
@@ -0,0 +1,185 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+use super::PgPool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, FromSql, ToSql)]
+#[postgres(name = "tag_suggestion_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionStatus {
+    Pending,
+    Accepted,
+    Dismissed,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TagSuggestion {
+    pub tag: String,
+    /// From 0 to 1.
+    pub confidence: f32,
+    /// What the tag was suggested from.
+    pub sources: Vec<String>,
+    pub status: SuggestionStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewTagSuggestion {
+    pub tag: String,
+    pub confidence: f32,
+    pub sources: Vec<String>,
+    pub status: SuggestionStatus,
+}
+
+/// The tags of the user's other bookmarks of the same domain, with the share
+/// of those bookmarks tagged with each.
+#[instrument(skip(pool))]
+pub async fn domain_tags(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+    limit: i64,
+) -> Result<Vec<(String, f32)>> {
+    const SQL: &str = r#"
+    WITH siblings AS (
+        SELECT bu.tags FROM bookmark_user bu
+        INNER JOIN bookmark b USING(bookmark_id)
+        WHERE bu.user_id = $1 AND bu.bookmark_id <> $2
+        AND b.domain = (SELECT domain FROM bookmark WHERE bookmark_id = $2)
+    )
+    SELECT tag, (count(1)::real / (SELECT count(1) FROM siblings))::real AS share
+    FROM siblings, unnest(tags) tag
+    GROUP BY tag ORDER BY share DESC, tag LIMIT $3;"#;
+    let client = pool.get().await?;
+    let rows = client.query(SQL, &[&user_id, &bookmark_id, &limit]).await?;
+    let result = rows
+        .iter()
+        .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+/// The most frequent words of the title and text content occurring at least
+/// `min_occurrences` times, without the stop words of the bookmark's language.
+#[instrument(skip(pool))]
+pub async fn keywords(
+    pool: &PgPool,
+    bookmark_id: &str,
+    min_occurrences: i64,
+    limit: i64,
+) -> Result<Vec<(String, i64)>> {
+    const SQL: &str = r#"
+    SELECT w, count(1) AS occurrences
+    FROM bookmark b, regexp_split_to_table(lower(b.title || ' ' || b.text_content), '[^[:alnum:]]+') w
+    WHERE b.bookmark_id = $1 AND length(w) >= 4 AND w !~ '^[0-9]+$'
+    AND to_tsvector(b.language, w) <> ''::tsvector
+    GROUP BY w HAVING count(1) >= $2
+    ORDER BY occurrences DESC, w LIMIT $3;"#;
+    let client = pool.get().await?;
+    let rows = client
+        .query(SQL, &[&bookmark_id, &min_occurrences, &limit])
+        .await?;
+    let result = rows
+        .iter()
+        .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+/// Every tag suggested for the user's bookmark, whatever its status.
+#[instrument(skip(pool))]
+pub async fn get_suggested(pool: &PgPool, bookmark_user_id: Uuid) -> Result<Vec<String>> {
+    const SQL: &str = "SELECT tag FROM tag_suggestion WHERE bookmark_user_id = $1;";
+    let client = pool.get().await?;
+    let rows = client.query(SQL, &[&bookmark_user_id]).await?;
+    let result = rows
+        .iter()
+        .map(|row| row.try_get(0).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+#[instrument(skip(pool, suggestions))]
+pub async fn save(
+    pool: &PgPool,
+    bookmark_user_id: Uuid,
+    suggestions: &[NewTagSuggestion],
+) -> Result<()> {
+    const SQL: &str = r#"
+    INSERT INTO tag_suggestion (bookmark_user_id, tag, confidence, sources, status)
+    VALUES ($1, $2, $3, $4, $5)
+    ON CONFLICT (bookmark_user_id, tag) DO NOTHING;"#;
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    let statement = tx.prepare(SQL).await?;
+    for suggestion in suggestions {
+        tx.execute(
+            &statement,
+            &[
+                &bookmark_user_id,
+                &suggestion.tag,
+                &suggestion.confidence,
+                &suggestion.sources,
+                &suggestion.status,
+            ],
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    info!(%bookmark_user_id, suggestions = suggestions.len(), "Tag suggestions saved");
+    Ok(())
+}
+
+/// The pending suggestions of the user's bookmark, most confident first.
+#[instrument(skip(pool))]
+pub async fn get_pending(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+) -> Result<Vec<TagSuggestion>> {
+    const SQL: &str = r#"
+    SELECT ts.* FROM tag_suggestion ts
+    INNER JOIN bookmark_user bu USING(bookmark_user_id)
+    WHERE bu.user_id = $1 AND bu.bookmark_id = $2 AND ts.status = 'pending'
+    ORDER BY ts.confidence DESC, ts.tag;"#;
+    let client = pool.get().await?;
+    let result = client
+        .query(SQL, &[&user_id, &bookmark_id])
+        .await?
+        .iter()
+        .map(|row| TagSuggestion::try_from_row(row).map_err(Error::from))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(result)
+}
+
+/// Accepts or dismisses a pending suggestion, `None` when there is no such
+/// pending suggestion.
+#[instrument(skip(pool))]
+pub async fn resolve(
+    pool: &PgPool,
+    user_id: Uuid,
+    bookmark_id: &str,
+    tag: &str,
+    status: SuggestionStatus,
+) -> Result<Option<TagSuggestion>> {
+    const SQL: &str = r#"
+    UPDATE tag_suggestion ts SET status = $4, updated_at = now()
+    FROM bookmark_user bu
+    WHERE ts.bookmark_user_id = bu.bookmark_user_id
+    AND bu.user_id = $1 AND bu.bookmark_id = $2 AND ts.tag = $3 AND ts.status = 'pending'
+    RETURNING ts.*;"#;
+    let client = pool.get().await?;
+    let result = client
+        .query_opt(SQL, &[&user_id, &bookmark_id, &tag, &status])
+        .await?
+        .map(|row| TagSuggestion::try_from_row(&row).map_err(Error::from))
+        .transpose()?;
+    Ok(result)
+}
@@ -34,6 +34,8 @@ pub struct Task {
     pub next_delivery: DateTime<Utc>,
     pub retries: Option<i16>,
     pub fail_reason: Option<String>,
+    pub import_id: Option<Uuid>,
+    pub user_created_at: Option<DateTime<Utc>>,
 }
 
 impl Task {
@@ -0,0 +1,114 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use candle_core::{Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+
+/// Size of the vectors stored in `bookmark_chunk.embedding`.
+pub const EMBEDDING_DIMENSIONS: usize = 384;
+/// Longer chunks are truncated by the tokenizer.
+const MAX_TOKENS: usize = 256;
+const CHUNK_WORDS: usize = 160;
+const CHUNK_OVERLAP_WORDS: usize = 32;
+/// Caps the work per bookmark, the beginning of an article is what matters.
+const MAX_CHUNKS: usize = 64;
+const BATCH_SIZE: usize = 8;
+
+/// A sentence-transformers BERT model running on the CPU, such as
+/// `all-MiniLM-L6-v2`.
+pub struct Embedder {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    device: Device,
+}
+
+impl Embedder {
+    /// Loads `config.json`, `tokenizer.json` and `model.safetensors` from `dir`.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let device = Device::Cpu;
+        let config = std::fs::read_to_string(dir.join("config.json"))
+            .with_context(|| format!("Reading model config from {dir:?}"))?;
+        let config: Config = serde_json::from_str(&config)?;
+        if config.hidden_size != EMBEDDING_DIMENSIONS {
+            bail!(
+                "Embedding model has {} dimensions, expected {EMBEDDING_DIMENSIONS}",
+                config.hidden_size
+            );
+        }
+        let mut tokenizer =
+            Tokenizer::from_file(dir.join("tokenizer.json")).map_err(anyhow::Error::msg)?;
+        tokenizer.with_padding(Some(PaddingParams {
+            strategy: PaddingStrategy::BatchLongest,
+            ..Default::default()
+        }));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS,
+                ..Default::default()
+            }))
+            .map_err(anyhow::Error::msg)?;
+        // SAFETY: the model file is not expected to change while mapped.
+        let vb = unsafe {
+            VarBuilder::from_mmaped_safetensors(&[dir.join("model.safetensors")], DTYPE, &device)?
+        };
+        let model = BertModel::load(vb, &config)?;
+        tracing::info!(?dir, "Embedding model loaded");
+        Ok(Self {
+            model,
+            tokenizer,
+            device,
+        })
+    }
+
+    /// Mean pooled and L2 normalized embeddings, one per text. This is CPU
+    /// bound, run it from a blocking task.
+    pub fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(BATCH_SIZE) {
+            embeddings.extend(self.embed_batch(batch)?);
+        }
+        Ok(embeddings)
+    }
+
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(anyhow::Error::msg)?;
+        let ids = encodings
+            .iter()
+            .map(|encoding| Tensor::new(encoding.get_ids(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+        let masks = encodings
+            .iter()
+            .map(|encoding| Tensor::new(encoding.get_attention_mask(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+        let ids = Tensor::stack(&ids, 0)?;
+        let mask = Tensor::stack(&masks, 0)?;
+        let output = self.model.forward(&ids, &ids.zeros_like()?, Some(&mask))?;
+        // Padding tokens are left out of the mean.
+        let mask = mask.to_dtype(DTYPE)?.unsqueeze(2)?;
+        let mean = output
+            .broadcast_mul(&mask)?
+            .sum(1)?
+            .broadcast_div(&mask.sum(1)?)?;
+        let norm = mean.sqr()?.sum_keepdim(1)?.sqrt()?;
+        Ok(mean.broadcast_div(&norm)?.to_vec2()?)
+    }
+}
+
+/// Splits a text in overlapping windows of words, small enough to fit in the
+/// model's context.
+pub fn chunks(text: &str) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    (0..words.len())
+        .step_by(CHUNK_WORDS)
+        .map(|start| {
+            let end = (start + CHUNK_WORDS).min(words.len());
+            words[start.saturating_sub(CHUNK_OVERLAP_WORDS)..end].join(" ")
+        })
+        .take(MAX_CHUNKS)
+        .collect()
+}
@@ -1,5 +1,7 @@
-use axum::extract::Path;
+use axum::extract::{Path, Query};
+use axum::http::header::CONTENT_TYPE;
 use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use axum::{routing::get, routing::post, Extension, Router};
 use axum_macros::debug_handler;
@@ -7,21 +9,52 @@ use serde::{Deserialize, Serialize};
 use tracing::error;
 use url::Url;
 
-use crate::db::bookmark::{self, BookmarkWithUser, TagOperation};
+use crate::db::bookmark::{
+    self, BookmarkPage, BookmarkWithUser, ReadingState, RelatedBookmark, TagOperation,
+};
+use crate::db::highlight::{self, Highlight};
+use crate::db::pagination::{self, Cursor, Sort};
+use crate::db::search::{self, SearchRequest};
+use crate::db::settings;
+use crate::db::tag_suggestion::{self, SuggestionStatus, TagSuggestion};
 use crate::db::task::{self, Task};
 use crate::endpoints::Error;
 use crate::error::Result;
-use crate::AppContext;
+use crate::{export, AppContext};
 
 use super::Claim;
 
+const RELATED_LIMIT: i64 = 10;
+const MAX_RELATED_LIMIT: i64 = 50;
+
 pub fn routes() -> Router {
     Router::new()
         .route("/tags", get(get_all_tags))
-        .route("/tags/:tag", get(get_bookmarks_by_tag))
+        .route("/tags/:tag", get(get_bookmarks_by_tag).delete(delete_tag))
+        .route("/tags/:tag/rename", post(rename_tag))
+        .route("/tag-merges", post(merge_tags))
         .route("/bookmarks", get(get_bookmarks).post(new_bookmark))
-        .route("/bookmarks/:id", get(get_bookmark))
-        .route("/bookmarks/:id/tags", post(set_tags).patch(append_tags))
+        .route(
+            "/bookmarks/:id",
+            get(get_bookmark).patch(update_reading_state),
+        )
+        .route("/bookmarks/:id/content", get(get_bookmark_content))
+        .route("/bookmarks/:id/related", get(get_related_bookmarks))
+        .route("/bookmarks/tags", post(bulk_update_tags))
+        .route(
+            "/bookmarks/:id/tags",
+            post(set_tags).patch(append_tags).delete(remove_tags),
+        )
+        .route("/bookmarks/:id/note", post(set_note))
+        .route("/bookmarks/:id/suggestions", get(get_suggestions))
+        .route(
+            "/bookmarks/:id/suggestions/:tag/accept",
+            post(accept_suggestion),
+        )
+        .route(
+            "/bookmarks/:id/suggestions/:tag/dismiss",
+            post(dismiss_suggestion),
+        )
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -40,9 +73,76 @@ struct Tags {
     tags: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct TagRename {
+    to: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagMerge {
+    tags: Vec<String>,
+    into: String,
+}
+
+/// A tag operation on the bookmarks `bookmark_ids`, or on every match of
+/// `search`.
+#[derive(Debug, Deserialize)]
+struct BulkTags {
+    operation: TagOperation,
+    bookmark_ids: Option<Vec<String>>,
+    search: Option<SearchRequest>,
+}
+
+/// The number of bookmarks a tag change was applied to.
+#[derive(Debug, Serialize)]
+struct TagsUpdated {
+    updated: u64,
+}
+
 #[derive(Debug, Serialize)]
-struct Bookmarks {
-    bookmarks: Vec<BookmarkWithUser>,
+struct TagSuggestions {
+    suggestions: Vec<TagSuggestion>,
+}
+
+/// A bookmark with the user's highlights of its content.
+#[derive(Debug, Serialize)]
+struct BookmarkWithHighlights {
+    #[serde(flatten)]
+    bookmark: BookmarkWithUser,
+    highlights: Vec<Highlight>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Note {
+    note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageParams {
+    #[serde(default)]
+    sort: Sort,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+impl PageParams {
+    async fn fetch(
+        &self,
+        app_context: &AppContext,
+        claims: &Claim,
+        tag: Option<&str>,
+    ) -> Result<BookmarkPage> {
+        let cursor = self.cursor.as_deref().map(Cursor::decode).transpose()?;
+        bookmark::get_page(
+            &app_context.pool,
+            claims.user_id,
+            tag,
+            self.sort,
+            cursor.as_ref(),
+            pagination::page_size(self.limit),
+        )
+        .await
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,13 +151,39 @@ struct NewBookmark {
     tags: Option<Vec<String>>,
 }
 
+#[derive(Debug, Serialize)]
+struct RelatedBookmarks {
+    bookmarks: Vec<RelatedBookmark>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelatedParams {
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ContentFormat {
+    #[default]
+    Html,
+    Markdown,
+    Text,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentParams {
+    #[serde(default)]
+    format: ContentFormat,
+}
+
 #[debug_handler]
 async fn get_bookmarks(
     claims: Claim,
     Extension(app_context): Extension<AppContext>,
-) -> Result<Json<Bookmarks>> {
-    let bookmarks = bookmark::get_by_user(&app_context.pool, claims.user_id).await?;
-    Ok(Json(Bookmarks { bookmarks }))
+    Query(params): Query<PageParams>,
+) -> Result<Json<BookmarkPage>> {
+    let page = params.fetch(&app_context, &claims, None).await?;
+    Ok(Json(page))
 }
 
 #[debug_handler]
@@ -78,9 +204,61 @@ async fn get_bookmarks_by_tag(
     claims: Claim,
     Extension(app_context): Extension<AppContext>,
     Path(tag): Path<String>,
-) -> Result<Json<Bookmarks>> {
-    let bookmarks = bookmark::get_by_tag(&app_context.pool, claims.user_id, &tag).await?;
-    Ok(Json(Bookmarks { bookmarks }))
+    Query(params): Query<PageParams>,
+) -> Result<Json<BookmarkPage>> {
+    let page = params.fetch(&app_context, &claims, Some(&tag)).await?;
+    Ok(Json(page))
+}
+
+/// The tag normalized with the user's policy.
+async fn normalize_tag(app_context: &AppContext, claims: &Claim, tag: &str) -> Result<String> {
+    settings::tag_policy(&app_context.pool, claims.user_id)
+        .await?
+        .normalize_tag(tag)
+        .ok_or_else(|| Error::bad_request([("tag", "can't be empty")]))
+}
+
+#[debug_handler]
+async fn rename_tag(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(tag): Path<String>,
+    Json(input): Json<TagRename>,
+) -> Result<Json<TagsUpdated>> {
+    let to = normalize_tag(&app_context, &claims, &input.to).await?;
+    let updated = bookmark::merge_tags(&app_context.pool, claims.user_id, &[tag], &to).await?;
+    if updated == 0 {
+        return Err(Error::NotFound);
+    }
+    Ok(Json(TagsUpdated { updated }))
+}
+
+#[debug_handler]
+async fn merge_tags(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<TagMerge>,
+) -> Result<Json<TagsUpdated>> {
+    if input.tags.is_empty() {
+        return Err(Error::bad_request([("tags", "can't be empty")]));
+    }
+    let into = normalize_tag(&app_context, &claims, &input.into).await?;
+    let updated =
+        bookmark::merge_tags(&app_context.pool, claims.user_id, &input.tags, &into).await?;
+    Ok(Json(TagsUpdated { updated }))
+}
+
+#[debug_handler]
+async fn delete_tag(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(tag): Path<String>,
+) -> Result<Json<TagsUpdated>> {
+    let updated = bookmark::delete_tag(&app_context.pool, claims.user_id, &tag).await?;
+    if updated == 0 {
+        return Err(Error::NotFound);
+    }
+    Ok(Json(TagsUpdated { updated }))
 }
 
 #[debug_handler]
@@ -88,13 +266,87 @@ async fn get_bookmark(
     claims: Claim,
     Extension(app_context): Extension<AppContext>,
     Path(id): Path<String>,
-) -> Result<Json<BookmarkWithUser>> {
-    let maybe_bookmark =
-        bookmark::get_with_user_data(&app_context.pool, claims.user_id, &id).await?;
-    match maybe_bookmark {
-        Some(bookmark) => Ok(Json(bookmark)),
-        None => Err(Error::NotFound),
+) -> Result<Json<BookmarkWithHighlights>> {
+    let bookmark = bookmark::get_with_user_data(&app_context.pool, claims.user_id, &id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let highlights = highlight::get_by_bookmark(&app_context.pool, claims.user_id, &id).await?;
+    Ok(Json(BookmarkWithHighlights {
+        bookmark,
+        highlights,
+    }))
+}
+
+#[debug_handler]
+async fn get_related_bookmarks(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(id): Path<String>,
+    Query(params): Query<RelatedParams>,
+) -> Result<Json<RelatedBookmarks>> {
+    let limit = params
+        .limit
+        .unwrap_or(RELATED_LIMIT)
+        .clamp(1, MAX_RELATED_LIMIT);
+    let bookmarks = bookmark::get_related(&app_context.pool, claims.user_id, &id, limit)
+        .await?
+        .ok_or(Error::NotFound)?;
+    Ok(Json(RelatedBookmarks { bookmarks }))
+}
+
+/// Serves one rendition of the archived content. The Markdown is the `index.md`
+/// written next to the images, bookmarks saved before it existed are converted
+/// on the fly.
+#[debug_handler]
+async fn get_bookmark_content(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(id): Path<String>,
+    Query(params): Query<ContentParams>,
+) -> Result<Response> {
+    if bookmark::get_with_user_data(&app_context.pool, claims.user_id, &id)
+        .await?
+        .is_none()
+    {
+        return Err(Error::NotFound);
     }
+    let bookmark_dir = app_context.config.data_dir.join(&id);
+    let read_index = |name: &'static str| {
+        let path = bookmark_dir.join(name);
+        async move {
+            match tokio::fs::read_to_string(&path).await {
+                Ok(content) => Ok(Some(content)),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(anyhow::Error::from(error)),
+            }
+        }
+    };
+    let (content_type, content) = match params.format {
+        ContentFormat::Html => (
+            "text/html; charset=utf-8",
+            read_index("index.html").await?.ok_or(Error::NotFound)?,
+        ),
+        ContentFormat::Markdown => {
+            let markdown = match read_index("index.md").await? {
+                Some(markdown) => markdown,
+                None => {
+                    let html = read_index("index.html").await?.ok_or(Error::NotFound)?;
+                    tokio::task::spawn_blocking(move || export::html_to_markdown(&html, &id, ""))
+                        .await
+                        .map_err(anyhow::Error::from)??
+                }
+            };
+            ("text/markdown; charset=utf-8", markdown)
+        }
+        ContentFormat::Text => (
+            "text/plain; charset=utf-8",
+            bookmark::get_by_id(&app_context.pool, &id)
+                .await?
+                .ok_or(Error::NotFound)?
+                .text_content,
+        ),
+    };
+    Ok(([(CONTENT_TYPE, content_type)], content).into_response())
 }
 
 #[debug_handler]
@@ -103,9 +355,8 @@ async fn new_bookmark(
     Extension(app_context): Extension<AppContext>,
     Json(input): Json<NewBookmark>,
 ) -> Result<(StatusCode, Json<Task>)> {
-    // FIXME put this validation in a better place
-    let mut tags = input.tags.clone().unwrap_or_default();
-    tags.retain(|t| !t.trim().is_empty());
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
+    let tags = policy.normalize(&input.tags.clone().unwrap_or_default());
     let response = task::create(&app_context.pool, claims.user_id, input.url, tags).await?;
     if let Err(error) = app_context.tx_new_task.send(()) {
         error!(?error, "Fail on notify new task");
@@ -113,6 +364,45 @@ async fn new_bookmark(
     Ok((StatusCode::CREATED, Json(response)))
 }
 
+#[debug_handler]
+async fn update_reading_state(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(bookmark_id): Path<String>,
+    Json(state): Json<ReadingState>,
+) -> Result<Json<BookmarkWithUser>> {
+    if state.progress.is_some_and(|p| !(0.0..=1.0).contains(&p)) {
+        return Err(Error::bad_request([(
+            "progress",
+            "must be between 0 and 1",
+        )]));
+    }
+    let updated =
+        bookmark::update_reading_state(&app_context.pool, claims.user_id, &bookmark_id, &state)
+            .await?
+            .ok_or(Error::NotFound)?;
+    Ok(Json(updated))
+}
+
+/// A blank note removes it.
+#[debug_handler]
+async fn set_note(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(bookmark_id): Path<String>,
+    Json(note): Json<Note>,
+) -> Result<Json<BookmarkWithUser>> {
+    let note = note
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty());
+    let updated = bookmark::update_note(&app_context.pool, claims.user_id, &bookmark_id, note)
+        .await?
+        .ok_or(Error::NotFound)?;
+    Ok(Json(updated))
+}
+
 #[debug_handler]
 async fn set_tags(
     claims: Claim,
@@ -120,11 +410,12 @@ async fn set_tags(
     Path(bookmark_id): Path<String>,
     Json(tags): Json<Tags>,
 ) -> Result<Json<BookmarkWithUser>> {
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
     let updated = bookmark::update_tags(
         &app_context.pool,
         claims.user_id,
         &bookmark_id,
-        &TagOperation::Set(tags.tags),
+        &TagOperation::Set(tags.tags).normalize(&policy),
     )
     .await?;
     Ok(Json(updated))
@@ -137,12 +428,124 @@ async fn append_tags(
     Path(bookmark_id): Path<String>,
     Json(tags): Json<Tags>,
 ) -> Result<Json<BookmarkWithUser>> {
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
+    let updated = bookmark::update_tags(
+        &app_context.pool,
+        claims.user_id,
+        &bookmark_id,
+        &TagOperation::Append(tags.tags).normalize(&policy),
+    )
+    .await?;
+    Ok(Json(updated))
+}
+
+#[debug_handler]
+async fn remove_tags(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(bookmark_id): Path<String>,
+    Json(tags): Json<Tags>,
+) -> Result<Json<BookmarkWithUser>> {
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
+    let updated = bookmark::update_tags(
+        &app_context.pool,
+        claims.user_id,
+        &bookmark_id,
+        &TagOperation::Remove(tags.tags).normalize(&policy),
+    )
+    .await?;
+    Ok(Json(updated))
+}
+
+#[debug_handler]
+async fn bulk_update_tags(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<BulkTags>,
+) -> Result<Json<TagsUpdated>> {
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
+    let operation = input.operation.normalize(&policy);
+    let updated = match (input.bookmark_ids, input.search) {
+        (Some(bookmark_ids), None) => {
+            bookmark::update_tags_by_ids(
+                &app_context.pool,
+                claims.user_id,
+                &bookmark_ids,
+                &operation,
+            )
+            .await?
+        }
+        (None, Some(request)) => {
+            search::update_tags(
+                &app_context.pool,
+                app_context.embedder.as_ref(),
+                claims.user_id,
+                &request,
+                &operation,
+            )
+            .await?
+        }
+        _ => {
+            return Err(Error::bad_request([(
+                "bookmark_ids",
+                "either bookmark_ids or search is required",
+            )]))
+        }
+    };
+    Ok(Json(TagsUpdated { updated }))
+}
+
+/// The pending tag suggestions of the bookmark.
+#[debug_handler]
+async fn get_suggestions(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(bookmark_id): Path<String>,
+) -> Result<Json<TagSuggestions>> {
+    let suggestions =
+        tag_suggestion::get_pending(&app_context.pool, claims.user_id, &bookmark_id).await?;
+    Ok(Json(TagSuggestions { suggestions }))
+}
+
+#[debug_handler]
+async fn accept_suggestion(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path((bookmark_id, tag)): Path<(String, String)>,
+) -> Result<Json<BookmarkWithUser>> {
+    let suggestion = tag_suggestion::resolve(
+        &app_context.pool,
+        claims.user_id,
+        &bookmark_id,
+        &tag,
+        SuggestionStatus::Accepted,
+    )
+    .await?
+    .ok_or(Error::NotFound)?;
     let updated = bookmark::update_tags(
         &app_context.pool,
         claims.user_id,
         &bookmark_id,
-        &TagOperation::Append(tags.tags),
+        &TagOperation::Append(vec![suggestion.tag]),
     )
     .await?;
     Ok(Json(updated))
 }
+
+#[debug_handler]
+async fn dismiss_suggestion(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path((bookmark_id, tag)): Path<(String, String)>,
+) -> Result<StatusCode> {
+    tag_suggestion::resolve(
+        &app_context.pool,
+        claims.user_id,
+        &bookmark_id,
+        &tag,
+        SuggestionStatus::Dismissed,
+    )
+    .await?
+    .ok_or(Error::NotFound)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,232 @@
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{
+    routing::{delete, get, post},
+    Extension, Router,
+};
+use axum_macros::debug_handler;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::bookmark::BookmarkWithUser;
+use crate::db::collection::{self, Collection};
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::AppContext;
+
+use super::Claim;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/collections", get(get_collections).post(new_collection))
+        .route(
+            "/collections/:id",
+            get(get_collection)
+                .patch(update_collection)
+                .delete(delete_collection),
+        )
+        .route("/collections/:id/items", post(add_item).put(reorder_items))
+        .route("/collections/:id/items/:bookmark_id", delete(remove_item))
+}
+
+#[derive(Debug, Deserialize)]
+struct NewCollection {
+    name: String,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionChanges {
+    name: Option<String>,
+    /// A blank description removes it.
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewItem {
+    bookmark_id: String,
+    /// From 0, the bookmark goes last without one.
+    position: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ItemsOrder {
+    bookmark_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Collections {
+    collections: Vec<Collection>,
+}
+
+#[derive(Debug, Serialize)]
+struct CollectionWithItems {
+    #[serde(flatten)]
+    collection: Collection,
+    bookmarks: Vec<BookmarkWithUser>,
+}
+
+fn clean_name(name: &str) -> Result<&str> {
+    match name.trim() {
+        "" => Err(Error::bad_request([("name", "can't be empty")])),
+        name => Ok(name),
+    }
+}
+
+async fn with_items(
+    app_context: &AppContext,
+    user_id: Uuid,
+    collection_id: Uuid,
+) -> Result<CollectionWithItems> {
+    let collection = collection::get(&app_context.pool, user_id, collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let bookmarks = collection::get_items(&app_context.pool, user_id, collection_id).await?;
+    Ok(CollectionWithItems {
+        collection,
+        bookmarks,
+    })
+}
+
+#[debug_handler]
+async fn get_collections(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Json<Collections>> {
+    let collections = collection::get_by_user(&app_context.pool, claims.user_id).await?;
+    Ok(Json(Collections { collections }))
+}
+
+#[debug_handler]
+async fn new_collection(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<NewCollection>,
+) -> Result<(StatusCode, Json<Collection>)> {
+    let description = input
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty());
+    let collection = collection::create(
+        &app_context.pool,
+        claims.user_id,
+        clean_name(&input.name)?,
+        description,
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(collection)))
+}
+
+#[debug_handler]
+async fn get_collection(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(collection_id): Path<Uuid>,
+) -> Result<Json<CollectionWithItems>> {
+    Ok(Json(
+        with_items(&app_context, claims.user_id, collection_id).await?,
+    ))
+}
+
+#[debug_handler]
+async fn update_collection(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(collection_id): Path<Uuid>,
+    Json(changes): Json<CollectionChanges>,
+) -> Result<Json<Collection>> {
+    let name = changes.name.as_deref().map(clean_name).transpose()?;
+    collection::update(
+        &app_context.pool,
+        claims.user_id,
+        collection_id,
+        name,
+        changes.description.as_deref().map(str::trim),
+    )
+    .await?
+    .map(Json)
+    .ok_or(Error::NotFound)
+}
+
+#[debug_handler]
+async fn delete_collection(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(collection_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    if collection::delete(&app_context.pool, claims.user_id, collection_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+#[debug_handler]
+async fn add_item(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(collection_id): Path<Uuid>,
+    Json(item): Json<NewItem>,
+) -> Result<(StatusCode, Json<CollectionWithItems>)> {
+    if item.position.is_some_and(|p| p < 0) {
+        return Err(Error::bad_request([("position", "can't be negative")]));
+    }
+    if !collection::add_item(
+        &app_context.pool,
+        claims.user_id,
+        collection_id,
+        &item.bookmark_id,
+        item.position,
+    )
+    .await?
+    {
+        return Err(Error::NotFound);
+    }
+    let collection = with_items(&app_context, claims.user_id, collection_id).await?;
+    Ok((StatusCode::CREATED, Json(collection)))
+}
+
+/// Takes every bookmark of the collection, in their new order.
+#[debug_handler]
+async fn reorder_items(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(collection_id): Path<Uuid>,
+    Json(order): Json<ItemsOrder>,
+) -> Result<Json<CollectionWithItems>> {
+    if !collection::reorder(
+        &app_context.pool,
+        claims.user_id,
+        collection_id,
+        &order.bookmark_ids,
+    )
+    .await?
+    {
+        return Err(Error::NotFound);
+    }
+    Ok(Json(
+        with_items(&app_context, claims.user_id, collection_id).await?,
+    ))
+}
+
+#[debug_handler]
+async fn remove_item(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path((collection_id, bookmark_id)): Path<(Uuid, String)>,
+) -> Result<StatusCode> {
+    if collection::remove_item(
+        &app_context.pool,
+        claims.user_id,
+        collection_id,
+        &bookmark_id,
+    )
+    .await?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
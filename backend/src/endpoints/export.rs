@@ -0,0 +1,153 @@
+use axum::extract::Path;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use axum::{routing::get, routing::post, Extension, Router};
+use axum_macros::debug_handler;
+use chrono::Utc;
+use serde::Deserialize;
+use std::io::Cursor;
+use uuid::Uuid;
+
+use crate::db::search::{self, SearchRequest};
+use crate::db::{bookmark, collection};
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::{export, AppContext};
+
+use super::Claim;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/export", get(export_account))
+        .route("/export/epub", post(export_epub))
+        .route("/export/markdown", get(export_markdown))
+        .route("/export/collections/:id", get(export_collection))
+}
+
+#[derive(Debug, Deserialize)]
+struct EpubRequest {
+    title: Option<String>,
+    bookmark_ids: Option<Vec<String>>,
+    search: Option<SearchRequest>,
+}
+
+fn attachment_headers(content_type: &str, filename: &str) -> [(axum::http::HeaderName, String); 2] {
+    [
+        (CONTENT_TYPE, content_type.to_owned()),
+        (
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        ),
+    ]
+}
+
+#[debug_handler]
+async fn export_account(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Response> {
+    let bookmarks = bookmark::get_by_user(&app_context.pool, claims.user_id).await?;
+    let tags = bookmark::get_tag_count_by_user(&app_context.pool, claims.user_id).await?;
+    let data_dir = app_context.config.data_dir.clone();
+    let body = export::stream_body(move |writer| {
+        export::write_account_archive(writer, &data_dir, &bookmarks, &tags)
+    });
+    let filename = format!("bookmarks-{}.zip", Utc::now().format("%Y%m%d"));
+    Ok((attachment_headers("application/zip", &filename), body).into_response())
+}
+
+#[debug_handler]
+async fn export_markdown(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Response> {
+    let bookmarks = bookmark::get_by_user(&app_context.pool, claims.user_id).await?;
+    let data_dir = app_context.config.data_dir.clone();
+    let body = export::stream_body(move |writer| {
+        export::write_markdown_archive(writer, &data_dir, &bookmarks)
+    });
+    let filename = format!("bookmarks-markdown-{}.zip", Utc::now().format("%Y%m%d"));
+    Ok((attachment_headers("application/zip", &filename), body).into_response())
+}
+
+#[debug_handler]
+async fn export_collection(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(collection_id): Path<Uuid>,
+) -> Result<Response> {
+    let collection = collection::get(&app_context.pool, claims.user_id, collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let bookmarks = collection::get_items(&app_context.pool, claims.user_id, collection_id).await?;
+    let filename = format!("{}.zip", slug(&collection.name));
+    let data_dir = app_context.config.data_dir.clone();
+    let body = export::stream_body(move |writer| {
+        export::write_collection_archive(writer, &data_dir, &collection, &bookmarks)
+    });
+    Ok((attachment_headers("application/zip", &filename), body).into_response())
+}
+
+#[debug_handler]
+async fn export_epub(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<EpubRequest>,
+) -> Result<Response> {
+    let bookmark_ids: Vec<String> = match (input.bookmark_ids, input.search) {
+        (Some(ids), None) => ids,
+        (None, Some(request)) => search::search(
+            &app_context.pool,
+            app_context.embedder.as_ref(),
+            claims.user_id,
+            &request,
+        )
+        .await?
+        .bookmarks
+        .into_iter()
+        .map(|item| item.bookmark_id)
+        .collect(),
+        _ => {
+            return Err(Error::bad_request([(
+                "bookmark_ids",
+                "either bookmark_ids or search must be given",
+            )]))
+        }
+    };
+    let bookmarks = bookmark::get_by_ids(&app_context.pool, claims.user_id, &bookmark_ids).await?;
+    if bookmarks.is_empty() {
+        return Err(Error::bad_request([(
+            "bookmark_ids",
+            "no bookmarks selected",
+        )]));
+    }
+    let title = input
+        .title
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| format!("Bookmarks {}", Utc::now().format("%Y-%m-%d")));
+    let data_dir = app_context.config.data_dir.clone();
+    let epub_title = title.clone();
+    let epub = tokio::task::spawn_blocking(move || {
+        export::write_epub(Cursor::new(Vec::new()), &data_dir, &epub_title, &bookmarks)
+    })
+    .await
+    .map_err(anyhow::Error::from)??
+    .into_inner();
+    let filename = format!("{}.epub", slug(&title));
+    Ok((attachment_headers("application/epub+zip", &filename), epub).into_response())
+}
+
+fn slug(title: &str) -> String {
+    let slug = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    if slug.is_empty() {
+        "bookmarks".to_owned()
+    } else {
+        slug
+    }
+}
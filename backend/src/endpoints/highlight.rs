@@ -0,0 +1,112 @@
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{
+    routing::{patch, post},
+    Extension, Router,
+};
+use axum_macros::debug_handler;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::highlight::{self, Highlight, NewHighlight};
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::AppContext;
+
+use super::Claim;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/bookmarks/:id/highlights", post(new_highlight))
+        .route(
+            "/bookmarks/:id/highlights/:highlight_id",
+            patch(update_highlight).delete(delete_highlight),
+        )
+}
+
+#[derive(Debug, Deserialize)]
+struct HighlightChanges {
+    note: Option<String>,
+}
+
+fn clean_note(note: Option<&str>) -> Option<&str> {
+    note.map(str::trim).filter(|n| !n.is_empty())
+}
+
+/// The position must span `exact`, as measured by the DOM.
+fn validate(highlight: NewHighlight) -> Result<NewHighlight> {
+    if highlight.exact.trim().is_empty() {
+        return Err(Error::bad_request([("exact", "can't be empty")]));
+    }
+    let length = highlight.exact.encode_utf16().count() as i64;
+    let span = i64::from(highlight.end_offset) - i64::from(highlight.start_offset);
+    if highlight.start_offset < 0 || span != length {
+        return Err(Error::bad_request([(
+            "end_offset",
+            "doesn't match the length of exact",
+        )]));
+    }
+    Ok(NewHighlight {
+        note: clean_note(highlight.note.as_deref()).map(str::to_owned),
+        ..highlight
+    })
+}
+
+#[debug_handler]
+async fn new_highlight(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(bookmark_id): Path<String>,
+    Json(input): Json<NewHighlight>,
+) -> Result<(StatusCode, Json<Highlight>)> {
+    let highlight = highlight::create(
+        &app_context.pool,
+        claims.user_id,
+        &bookmark_id,
+        &validate(input)?,
+    )
+    .await?
+    .ok_or(Error::NotFound)?;
+    Ok((StatusCode::CREATED, Json(highlight)))
+}
+
+/// A blank note removes it.
+#[debug_handler]
+async fn update_highlight(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path((bookmark_id, highlight_id)): Path<(String, Uuid)>,
+    Json(changes): Json<HighlightChanges>,
+) -> Result<Json<Highlight>> {
+    highlight::update_note(
+        &app_context.pool,
+        claims.user_id,
+        &bookmark_id,
+        highlight_id,
+        clean_note(changes.note.as_deref()),
+    )
+    .await?
+    .map(Json)
+    .ok_or(Error::NotFound)
+}
+
+#[debug_handler]
+async fn delete_highlight(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path((bookmark_id, highlight_id)): Path<(String, Uuid)>,
+) -> Result<StatusCode> {
+    if highlight::delete(
+        &app_context.pool,
+        claims.user_id,
+        &bookmark_id,
+        highlight_id,
+    )
+    .await?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
@@ -0,0 +1,98 @@
+use axum::extract::{DefaultBodyLimit, Path, Query};
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{routing::get, Extension, Router};
+use axum_macros::debug_handler;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::db::import::{self, ImportFailure, ImportFormat, ImportJob, ImportProgress};
+use crate::db::settings;
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::AppContext;
+
+use super::Claim;
+
+const IMPORT_MAX_SIZE: usize = 32 * 1024 * 1024;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route(
+            "/imports",
+            get(get_imports)
+                .post(new_import)
+                .layer(DefaultBodyLimit::max(IMPORT_MAX_SIZE)),
+        )
+        .route("/imports/:id", get(get_import))
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportParams {
+    format: ImportFormat,
+}
+
+#[derive(Debug, Serialize)]
+struct Imports {
+    imports: Vec<ImportProgress>,
+}
+
+#[derive(Debug, Serialize)]
+struct ImportReport {
+    #[serde(flatten)]
+    progress: ImportProgress,
+    failures: Vec<ImportFailure>,
+}
+
+#[debug_handler]
+async fn new_import(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Query(params): Query<ImportParams>,
+    content: String,
+) -> Result<(StatusCode, Json<ImportJob>)> {
+    let mut parsed = crate::import::parse(params.format, &content)
+        .map_err(|e| Error::bad_request([("content", e.to_string())]))?;
+    if parsed.entries.is_empty() {
+        return Err(Error::bad_request([("content", "no bookmarks found")]));
+    }
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
+    for entry in parsed.entries.iter_mut() {
+        entry.tags = policy.normalize(&entry.tags);
+    }
+    let job = import::create(
+        &app_context.pool,
+        claims.user_id,
+        params.format,
+        &parsed.entries,
+        &parsed.errors,
+    )
+    .await?;
+    if let Err(error) = app_context.tx_new_task.send(()) {
+        error!(?error, "Fail on notify new task");
+    }
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+#[debug_handler]
+async fn get_imports(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Json<Imports>> {
+    let imports = import::get_by_user(&app_context.pool, claims.user_id).await?;
+    Ok(Json(Imports { imports }))
+}
+
+#[debug_handler]
+async fn get_import(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(import_id): Path<Uuid>,
+) -> Result<Json<ImportReport>> {
+    let progress = import::get_progress(&app_context.pool, claims.user_id, import_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let failures = import::get_failures(&app_context.pool, claims.user_id, import_id).await?;
+    Ok(Json(ImportReport { progress, failures }))
+}
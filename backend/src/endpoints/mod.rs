@@ -19,8 +19,18 @@ use uuid::Uuid;
 
 mod auth;
 mod bookmark;
+mod collection;
+mod export;
+mod highlight;
+mod import;
+mod output_feed;
+mod rule;
+mod saved_search;
 mod search;
+mod settings;
 mod static_content;
+mod subscription;
+mod tag_alias;
 
 pub use static_content::routes as static_content;
 
@@ -45,7 +55,17 @@ pub fn health_check() -> Router {
 pub fn routers_v1() -> Router {
     auth::router()
         .merge(bookmark::routes())
+        .merge(collection::routes())
+        .merge(export::routes())
+        .merge(highlight::routes())
+        .merge(import::routes())
+        .merge(output_feed::routes())
+        .merge(rule::routes())
+        .merge(saved_search::routes())
         .merge(search::routes())
+        .merge(settings::routes())
+        .merge(subscription::routes())
+        .merge(tag_alias::routes())
 }
 
 #[async_trait]
@@ -0,0 +1,230 @@
+use axum::extract::{Path, Query};
+use axum::http::header::{CONTENT_TYPE, HOST};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use axum::{routing::delete, routing::get, Extension, Router};
+use axum_macros::debug_handler;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::bookmark::{self, BookmarkWithUser};
+use crate::db::output_feed::{self, OutputFeed, OutputFeedKind};
+use crate::db::search::{self, SearchRequest};
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::export::{self, AtomEntry};
+use crate::AppContext;
+
+use super::Claim;
+
+const FEED_ENTRIES_LIMIT: i64 = 50;
+const TOKEN_BYTES: usize = 32;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/output-feeds", get(get_output_feeds).post(new_output_feed))
+        .route("/output-feeds/:id", delete(delete_output_feed))
+        .route("/feeds/:token", get(get_feed))
+}
+
+#[derive(Debug, Deserialize)]
+struct NewOutputFeed {
+    title: Option<String>,
+    kind: OutputFeedKind,
+    tag: Option<String>,
+    search: Option<SearchRequest>,
+}
+
+/// An output feed with the path to subscribe to, token included.
+#[derive(Debug, Serialize)]
+struct OutputFeedWithPath {
+    #[serde(flatten)]
+    feed: OutputFeed,
+    path: String,
+}
+
+impl From<OutputFeed> for OutputFeedWithPath {
+    fn from(feed: OutputFeed) -> Self {
+        let path = format!("/api/v1/feeds/{}", feed.token);
+        Self { feed, path }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OutputFeeds {
+    feeds: Vec<OutputFeedWithPath>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FeedParams {
+    /// A `SearchRequest` as base64url encoded JSON, for `search` feeds.
+    q: Option<String>,
+}
+
+#[debug_handler]
+async fn get_output_feeds(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Json<OutputFeeds>> {
+    let feeds = output_feed::get_by_user(&app_context.pool, claims.user_id)
+        .await?
+        .into_iter()
+        .map(OutputFeedWithPath::from)
+        .collect();
+    Ok(Json(OutputFeeds { feeds }))
+}
+
+#[debug_handler]
+async fn new_output_feed(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<NewOutputFeed>,
+) -> Result<(StatusCode, Json<OutputFeedWithPath>)> {
+    let tag = input
+        .tag
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty());
+    let (tag, search) = match input.kind {
+        OutputFeedKind::All => (None, None),
+        OutputFeedKind::Tag => match tag {
+            Some(tag) => (Some(tag), None),
+            None => return Err(Error::bad_request([("tag", "required for tag feeds")])),
+        },
+        OutputFeedKind::Search => {
+            let search = input
+                .search
+                .as_ref()
+                .map(serde_json::to_value)
+                .transpose()
+                .map_err(anyhow::Error::from)?;
+            (None, search)
+        }
+    };
+    let title = match (input.title.as_deref().map(str::trim), tag) {
+        (Some(title), _) if !title.is_empty() => title.to_owned(),
+        (_, Some(tag)) => format!("Bookmarks tagged {tag}"),
+        _ => "Bookmarks".to_owned(),
+    };
+    let token = hex::encode(rand::random::<[u8; TOKEN_BYTES]>());
+    let feed = output_feed::create(
+        &app_context.pool,
+        claims.user_id,
+        &token,
+        &title,
+        input.kind,
+        tag,
+        search.as_ref(),
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(feed.into())))
+}
+
+#[debug_handler]
+async fn delete_output_feed(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(output_feed_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    if output_feed::delete(&app_context.pool, claims.user_id, output_feed_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+/// Public Atom feed, the token in the path stands in for the JWT.
+#[debug_handler]
+async fn get_feed(
+    Extension(app_context): Extension<AppContext>,
+    Path(token): Path<String>,
+    Query(params): Query<FeedParams>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let feed = output_feed::get_by_token(&app_context.pool, &token)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let bookmarks: Vec<BookmarkWithUser> = match feed.kind {
+        OutputFeedKind::All | OutputFeedKind::Tag => {
+            bookmark::get_recent(
+                &app_context.pool,
+                feed.user_id,
+                feed.tag.as_deref(),
+                FEED_ENTRIES_LIMIT,
+            )
+            .await?
+        }
+        OutputFeedKind::Search => {
+            let request = search_request(&params, &feed)?;
+            let ids: Vec<String> = search::search(
+                &app_context.pool,
+                app_context.embedder.as_ref(),
+                feed.user_id,
+                &request,
+            )
+            .await?
+            .bookmarks
+            .into_iter()
+            .map(|item| item.bookmark_id)
+            .collect();
+            let mut bookmarks = bookmark::get_by_ids(&app_context.pool, feed.user_id, &ids).await?;
+            bookmarks.sort_by_key(|b| std::cmp::Reverse(b.user_created_at));
+            bookmarks
+        }
+    };
+
+    let mut entries = Vec::with_capacity(bookmarks.len());
+    for bookmark in bookmarks {
+        let index = app_context
+            .config
+            .data_dir
+            .join(&bookmark.bookmark_id)
+            .join("index.html");
+        let content = tokio::fs::read_to_string(index).await.ok();
+        entries.push(AtomEntry { bookmark, content });
+    }
+
+    let base_url = base_url(&headers);
+    let mut self_url = format!("{base_url}/api/v1/feeds/{token}");
+    if let Some(q) = &params.q {
+        self_url.push_str(&format!("?q={q}"));
+    }
+    let feed_id = format!("tag:bookmark-rs,2024:feed/{}", feed.output_feed_id);
+    let atom = export::render_atom(&feed_id, &feed.title, &base_url, &self_url, &entries);
+    Ok((
+        [(CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+        atom,
+    )
+        .into_response())
+}
+
+/// The search from the url wins over the one saved with the feed.
+fn search_request(params: &FeedParams, feed: &OutputFeed) -> Result<SearchRequest> {
+    if let Some(q) = &params.q {
+        let json = base64_url::decode(q).map_err(|e| Error::bad_request([("q", e.to_string())]))?;
+        return serde_json::from_slice(&json)
+            .map_err(|e| Error::bad_request([("q", e.to_string())]));
+    }
+    match &feed.search {
+        Some(search) => {
+            serde_json::from_value(search.clone()).map_err(|e| Error::from(anyhow::Error::from(e)))
+        }
+        None => Err(Error::bad_request([("q", "a search is required")])),
+    }
+}
+
+/// Origin the feed was requested from, honoring a reverse proxy's headers.
+fn base_url(headers: &HeaderMap) -> String {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    let scheme = header("x-forwarded-proto").unwrap_or_else(|| "http".to_owned());
+    let host = header("x-forwarded-host")
+        .or_else(|| header(HOST.as_str()))
+        .unwrap_or_else(|| "localhost".to_owned());
+    format!("{scheme}://{host}")
+}
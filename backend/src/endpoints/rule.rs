@@ -0,0 +1,216 @@
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{
+    routing::{get, post},
+    Extension, Router,
+};
+use axum_macros::debug_handler;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::rule::{self, Actions, Condition, DryRun, Rule, RuleDefinition};
+use crate::db::settings;
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::AppContext;
+
+use super::Claim;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/rules", get(get_rules).post(new_rule))
+        .route("/rules/dry-run", post(dry_run))
+        .route(
+            "/rules/:id",
+            get(get_rule).patch(update_rule).delete(delete_rule),
+        )
+        .route("/rules/:id/dry-run", get(dry_run_rule))
+        .route("/rules/:id/apply", post(apply_rule))
+}
+
+#[derive(Debug, Deserialize)]
+struct NewRule {
+    name: String,
+    #[serde(flatten)]
+    definition: RuleDefinition,
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleChanges {
+    name: Option<String>,
+    conditions: Option<Vec<Condition>>,
+    actions: Option<Actions>,
+    enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct Rules {
+    rules: Vec<Rule>,
+}
+
+/// The number of bookmarks a rule changed.
+#[derive(Debug, Serialize)]
+struct RuleApplied {
+    updated: u64,
+}
+
+fn clean_name(name: &str) -> Result<&str> {
+    match name.trim() {
+        "" => Err(Error::bad_request([("name", "can't be empty")])),
+        name => Ok(name),
+    }
+}
+
+async fn get_user_rule(app_context: &AppContext, user_id: Uuid, rule_id: Uuid) -> Result<Rule> {
+    rule::get(&app_context.pool, user_id, rule_id)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+#[debug_handler]
+async fn get_rules(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Json<Rules>> {
+    let rules = rule::get_by_user(&app_context.pool, claims.user_id).await?;
+    Ok(Json(Rules { rules }))
+}
+
+#[debug_handler]
+async fn new_rule(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<NewRule>,
+) -> Result<(StatusCode, Json<Rule>)> {
+    let rule = rule::create(
+        &app_context.pool,
+        claims.user_id,
+        clean_name(&input.name)?,
+        &input.definition.validate()?,
+        input.enabled,
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+#[debug_handler]
+async fn get_rule(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<Json<Rule>> {
+    Ok(Json(
+        get_user_rule(&app_context, claims.user_id, rule_id).await?,
+    ))
+}
+
+#[debug_handler]
+async fn update_rule(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(rule_id): Path<Uuid>,
+    Json(changes): Json<RuleChanges>,
+) -> Result<Json<Rule>> {
+    let name = changes.name.as_deref().map(clean_name).transpose()?;
+    // The conditions and actions are validated together.
+    let definition = if changes.conditions.is_some() || changes.actions.is_some() {
+        let current = get_user_rule(&app_context, claims.user_id, rule_id)
+            .await?
+            .definition()?;
+        let definition = RuleDefinition {
+            conditions: changes.conditions.unwrap_or(current.conditions),
+            actions: changes.actions.unwrap_or(current.actions),
+        };
+        Some(definition.validate()?)
+    } else {
+        None
+    };
+    rule::update(
+        &app_context.pool,
+        claims.user_id,
+        rule_id,
+        name,
+        definition.as_ref(),
+        changes.enabled,
+    )
+    .await?
+    .map(Json)
+    .ok_or(Error::NotFound)
+}
+
+#[debug_handler]
+async fn delete_rule(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    if rule::delete(&app_context.pool, claims.user_id, rule_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+/// The bookmarks an unsaved rule would change.
+#[debug_handler]
+async fn dry_run(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(definition): Json<RuleDefinition>,
+) -> Result<Json<DryRun>> {
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
+    let result = rule::dry_run(
+        &app_context.pool,
+        claims.user_id,
+        &definition.validate()?,
+        &policy,
+    )
+    .await?;
+    Ok(Json(result))
+}
+
+/// The existing bookmarks the rule would change when applied.
+#[debug_handler]
+async fn dry_run_rule(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<Json<DryRun>> {
+    let rule = get_user_rule(&app_context, claims.user_id, rule_id).await?;
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
+    let result = rule::dry_run(
+        &app_context.pool,
+        claims.user_id,
+        &rule.definition()?,
+        &policy,
+    )
+    .await?;
+    Ok(Json(result))
+}
+
+/// Applies the rule to every existing bookmark it matches, disabled or not.
+#[debug_handler]
+async fn apply_rule(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<Json<RuleApplied>> {
+    let rule = get_user_rule(&app_context, claims.user_id, rule_id).await?;
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
+    let updated = rule::apply(
+        &app_context.pool,
+        claims.user_id,
+        &rule.definition()?,
+        &policy,
+        None,
+    )
+    .await?;
+    Ok(Json(RuleApplied { updated }))
+}
@@ -0,0 +1,204 @@
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{routing::get, Extension, Router};
+use axum_macros::debug_handler;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::saved_search::{self, SavedSearch};
+use crate::db::search::{self, SearchRequest, SearchResponse};
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::AppContext;
+
+use super::Claim;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route(
+            "/saved-searches",
+            get(get_saved_searches).post(new_saved_search),
+        )
+        .route(
+            "/saved-searches/:id",
+            get(get_saved_search)
+                .patch(update_saved_search)
+                .delete(delete_saved_search),
+        )
+        .route("/saved-searches/:id/results", get(get_results))
+}
+
+#[derive(Debug, Deserialize)]
+struct NewSavedSearch {
+    name: String,
+    search: SearchRequest,
+}
+
+#[derive(Debug, Deserialize)]
+struct SavedSearchChanges {
+    name: Option<String>,
+    search: Option<SearchRequest>,
+}
+
+/// A saved search with the number of bookmarks matching it since its results
+/// were last seen.
+#[derive(Debug, Serialize)]
+struct SavedSearchWithNew {
+    #[serde(flatten)]
+    saved_search: SavedSearch,
+    /// `None` when they couldn't be counted.
+    new_matches: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct SavedSearches {
+    saved_searches: Vec<SavedSearchWithNew>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultsParams {
+    cursor: Option<String>,
+}
+
+fn clean_name(name: &str) -> Result<&str> {
+    match name.trim() {
+        "" => Err(Error::bad_request([("name", "can't be empty")])),
+        name => Ok(name),
+    }
+}
+
+fn to_json(search: SearchRequest) -> Result<serde_json::Value> {
+    let search = search.validate()?;
+    Ok(serde_json::to_value(search).map_err(anyhow::Error::from)?)
+}
+
+/// A failing count doesn't fail the saved search, its `new_matches` is null.
+async fn with_new_matches(
+    app_context: &AppContext,
+    saved_search: SavedSearch,
+) -> SavedSearchWithNew {
+    let new_matches = async {
+        search::count_since(
+            &app_context.pool,
+            saved_search.user_id,
+            &saved_search.request()?,
+            saved_search.last_seen_at,
+        )
+        .await
+    }
+    .await
+    .map_err(|error| {
+        warn!(saved_search_id = %saved_search.saved_search_id, ?error, "Fail to count new matches");
+    })
+    .ok();
+    SavedSearchWithNew {
+        saved_search,
+        new_matches,
+    }
+}
+
+#[debug_handler]
+async fn get_saved_searches(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Json<SavedSearches>> {
+    let saved_searches = saved_search::get_by_user(&app_context.pool, claims.user_id).await?;
+    let saved_searches = join_all(
+        saved_searches
+            .into_iter()
+            .map(|saved_search| with_new_matches(&app_context, saved_search)),
+    )
+    .await;
+    Ok(Json(SavedSearches { saved_searches }))
+}
+
+#[debug_handler]
+async fn new_saved_search(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<NewSavedSearch>,
+) -> Result<(StatusCode, Json<SavedSearch>)> {
+    let saved_search = saved_search::create(
+        &app_context.pool,
+        claims.user_id,
+        clean_name(&input.name)?,
+        &to_json(input.search)?,
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(saved_search)))
+}
+
+#[debug_handler]
+async fn get_saved_search(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(saved_search_id): Path<Uuid>,
+) -> Result<Json<SavedSearchWithNew>> {
+    let saved_search = saved_search::get(&app_context.pool, claims.user_id, saved_search_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    Ok(Json(with_new_matches(&app_context, saved_search).await))
+}
+
+#[debug_handler]
+async fn update_saved_search(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(saved_search_id): Path<Uuid>,
+    Json(changes): Json<SavedSearchChanges>,
+) -> Result<Json<SavedSearch>> {
+    let name = changes.name.as_deref().map(clean_name).transpose()?;
+    let search = changes.search.map(to_json).transpose()?;
+    saved_search::update(
+        &app_context.pool,
+        claims.user_id,
+        saved_search_id,
+        name,
+        search.as_ref(),
+    )
+    .await?
+    .map(Json)
+    .ok_or(Error::NotFound)
+}
+
+#[debug_handler]
+async fn delete_saved_search(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(saved_search_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    if saved_search::delete(&app_context.pool, claims.user_id, saved_search_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+/// Runs the saved search, the first page marks its matches as seen.
+#[debug_handler]
+async fn get_results(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(saved_search_id): Path<Uuid>,
+    Query(params): Query<ResultsParams>,
+) -> Result<Json<SearchResponse>> {
+    let saved_search = saved_search::get(&app_context.pool, claims.user_id, saved_search_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let first_page = params.cursor.is_none();
+    let request = saved_search.request()?.with_cursor(params.cursor);
+    let response = search::search(
+        &app_context.pool,
+        app_context.embedder.as_ref(),
+        claims.user_id,
+        &request,
+    )
+    .await?;
+    if first_page {
+        saved_search::mark_seen(&app_context.pool, claims.user_id, saved_search_id).await?;
+    }
+    Ok(Json(response))
+}
@@ -18,6 +18,12 @@ async fn search_bookmark(
     Extension(app_context): Extension<AppContext>,
     Json(input): Json<SearchRequest>,
 ) -> Result<Json<SearchResponse>> {
-    let result = search(&app_context.pool, claims.user_id, &input).await?;
+    let result = search(
+        &app_context.pool,
+        app_context.embedder.as_ref(),
+        claims.user_id,
+        &input,
+    )
+    .await?;
     Ok(Json(result))
 }
@@ -0,0 +1,67 @@
+use axum::Json;
+use axum::{routing::get, Extension, Router};
+use axum_macros::debug_handler;
+use serde::{Deserialize, Deserializer};
+
+use crate::db::settings::{self, UserSettings};
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::language::Language;
+use crate::tag::TagPolicy;
+use crate::AppContext;
+
+use super::Claim;
+
+pub fn routes() -> Router {
+    Router::new().route("/settings", get(get_settings).patch(update_settings))
+}
+
+#[derive(Debug, Deserialize)]
+struct SettingsUpdate {
+    query_language: Option<Language>,
+    tag_policy: Option<TagPolicy>,
+    /// `Some(None)` stops applying suggestions.
+    #[serde(default, deserialize_with = "present")]
+    tag_suggestion_threshold: Option<Option<f32>>,
+}
+
+/// Tells a field set to null, `Some(None)`, from a missing one.
+fn present<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+#[debug_handler]
+async fn get_settings(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Json<UserSettings>> {
+    let settings = settings::get(&app_context.pool, claims.user_id).await?;
+    Ok(Json(settings))
+}
+
+#[debug_handler]
+async fn update_settings(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<SettingsUpdate>,
+) -> Result<Json<UserSettings>> {
+    if let Some(Some(threshold)) = input.tag_suggestion_threshold {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(Error::bad_request([(
+                "tag_suggestion_threshold",
+                "must be between 0 and 1",
+            )]));
+        }
+    }
+    let settings = settings::update(
+        &app_context.pool,
+        claims.user_id,
+        input.query_language,
+        input.tag_policy.as_ref(),
+        input.tag_suggestion_threshold,
+    )
+    .await?;
+    Ok(Json(settings))
+}
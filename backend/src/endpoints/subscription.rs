@@ -0,0 +1,190 @@
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{routing::get, routing::post, Extension, Router};
+use axum_macros::debug_handler;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use url::Url;
+use uuid::Uuid;
+
+use crate::db::feed::{self, FeedChanges, FeedEntry, FeedSubscription};
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::AppContext;
+
+use super::Claim;
+
+const ENTRIES_LIMIT: i64 = 100;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/subscriptions", get(get_subscriptions).post(subscribe))
+        .route(
+            "/subscriptions/:id",
+            get(get_subscription)
+                .patch(update_subscription)
+                .delete(unsubscribe),
+        )
+        .route("/subscriptions/:id/poll", post(poll_subscription))
+        .route("/subscriptions/:id/entries", get(get_entries))
+        .route("/subscriptions/:id/entries/mute", post(mute_entry))
+}
+
+#[derive(Debug, Deserialize)]
+struct NewSubscription {
+    url: Url,
+    tags: Option<Vec<String>>,
+    mute_keywords: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Subscriptions {
+    subscriptions: Vec<FeedSubscription>,
+}
+
+#[derive(Debug, Serialize)]
+struct Entries {
+    entries: Vec<FeedEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MuteEntry {
+    entry_key: String,
+}
+
+fn clean_list(values: Option<Vec<String>>) -> Option<Vec<String>> {
+    values.map(|values| {
+        values
+            .into_iter()
+            .map(|v| v.trim().to_owned())
+            .filter(|v| !v.is_empty())
+            .collect()
+    })
+}
+
+fn notify_daemon(app_context: &AppContext) {
+    if let Err(error) = app_context.tx_new_task.send(()) {
+        error!(?error, "Fail on notify new task");
+    }
+}
+
+#[debug_handler]
+async fn get_subscriptions(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Json<Subscriptions>> {
+    let subscriptions = feed::get_by_user(&app_context.pool, claims.user_id).await?;
+    Ok(Json(Subscriptions { subscriptions }))
+}
+
+#[debug_handler]
+async fn subscribe(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<NewSubscription>,
+) -> Result<(StatusCode, Json<FeedSubscription>)> {
+    if !matches!(input.url.scheme(), "http" | "https") {
+        return Err(Error::bad_request([(
+            "url",
+            "only http and https feeds are supported",
+        )]));
+    }
+    let subscription = feed::create(
+        &app_context.pool,
+        claims.user_id,
+        input.url.as_str(),
+        &clean_list(input.tags).unwrap_or_default(),
+        &clean_list(input.mute_keywords).unwrap_or_default(),
+    )
+    .await?;
+    notify_daemon(&app_context);
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+#[debug_handler]
+async fn get_subscription(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(feed_id): Path<Uuid>,
+) -> Result<Json<FeedSubscription>> {
+    feed::get(&app_context.pool, claims.user_id, feed_id)
+        .await?
+        .map(Json)
+        .ok_or(Error::NotFound)
+}
+
+#[debug_handler]
+async fn update_subscription(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(feed_id): Path<Uuid>,
+    Json(input): Json<FeedChanges>,
+) -> Result<Json<FeedSubscription>> {
+    let changes = FeedChanges {
+        tags: clean_list(input.tags),
+        mute_keywords: clean_list(input.mute_keywords),
+        ..input
+    };
+    feed::update(&app_context.pool, claims.user_id, feed_id, &changes)
+        .await?
+        .map(Json)
+        .ok_or(Error::NotFound)
+}
+
+#[debug_handler]
+async fn unsubscribe(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(feed_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    if feed::delete(&app_context.pool, claims.user_id, feed_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+#[debug_handler]
+async fn poll_subscription(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(feed_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    if !feed::schedule_now(&app_context.pool, claims.user_id, feed_id).await? {
+        return Err(Error::NotFound);
+    }
+    notify_daemon(&app_context);
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[debug_handler]
+async fn get_entries(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(feed_id): Path<Uuid>,
+) -> Result<Json<Entries>> {
+    if feed::get(&app_context.pool, claims.user_id, feed_id)
+        .await?
+        .is_none()
+    {
+        return Err(Error::NotFound);
+    }
+    let entries =
+        feed::get_entries(&app_context.pool, claims.user_id, feed_id, ENTRIES_LIMIT).await?;
+    Ok(Json(Entries { entries }))
+}
+
+#[debug_handler]
+async fn mute_entry(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(feed_id): Path<Uuid>,
+    Json(input): Json<MuteEntry>,
+) -> Result<StatusCode> {
+    if feed::mute_entry(&app_context.pool, claims.user_id, feed_id, &input.entry_key).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
@@ -0,0 +1,84 @@
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{routing::delete, routing::get, Extension, Router};
+use axum_macros::debug_handler;
+use serde::{Deserialize, Serialize};
+
+use crate::db::settings;
+use crate::db::tag_alias::{self, TagAlias};
+use crate::endpoints::Error;
+use crate::error::Result;
+use crate::AppContext;
+
+use super::Claim;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/tag-aliases", get(get_tag_aliases).post(save_tag_alias))
+        .route("/tag-aliases/:alias", delete(delete_tag_alias))
+}
+
+#[derive(Debug, Deserialize)]
+struct NewTagAlias {
+    alias: String,
+    tag: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TagAliases {
+    aliases: Vec<TagAlias>,
+}
+
+#[debug_handler]
+async fn get_tag_aliases(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+) -> Result<Json<TagAliases>> {
+    let aliases = tag_alias::get_by_user(&app_context.pool, claims.user_id).await?;
+    Ok(Json(TagAliases { aliases }))
+}
+
+/// Aliases point to a canonical tag, never to another alias.
+#[debug_handler]
+async fn save_tag_alias(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Json(input): Json<NewTagAlias>,
+) -> Result<Json<TagAlias>> {
+    let policy = settings::tag_policy(&app_context.pool, claims.user_id).await?;
+    let alias = policy
+        .clean_tag(&input.alias)
+        .ok_or_else(|| Error::bad_request([("alias", "can't be empty")]))?;
+    let tag = policy
+        .clean_tag(&input.tag)
+        .ok_or_else(|| Error::bad_request([("tag", "can't be empty")]))?;
+    if alias == tag {
+        return Err(Error::bad_request([("tag", "can't be the alias itself")]));
+    }
+    if policy.aliases.contains_key(&tag) {
+        return Err(Error::bad_request([("tag", "is an alias")]));
+    }
+    if policy.aliases.values().any(|t| t == &alias) {
+        return Err(Error::bad_request([("alias", "has aliases")]));
+    }
+    let alias = tag_alias::upsert(&app_context.pool, claims.user_id, &alias, &tag).await?;
+    Ok(Json(alias))
+}
+
+#[debug_handler]
+async fn delete_tag_alias(
+    claims: Claim,
+    Extension(app_context): Extension<AppContext>,
+    Path(alias): Path<String>,
+) -> Result<StatusCode> {
+    let alias = settings::tag_policy(&app_context.pool, claims.user_id)
+        .await?
+        .clean_tag(&alias)
+        .ok_or(Error::NotFound)?;
+    if tag_alias::delete(&app_context.pool, claims.user_id, &alias).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::io::{Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::db::bookmark::BookmarkWithUser;
+use crate::db::collection::Collection;
+
+#[derive(Debug, Serialize)]
+struct TagCount<'a> {
+    tag: &'a str,
+    count: i64,
+}
+
+/// Writes everything a user owns as a zip: `bookmarks.json`, `tags.json`, a
+/// Netscape `bookmarks.html`, and the archived content of each bookmark under
+/// `bookmarks/<bookmark_id>/`.
+pub fn write_account_archive<W: Write>(
+    writer: W,
+    data_dir: &Path,
+    bookmarks: &[BookmarkWithUser],
+    tags: &[(String, i64)],
+) -> Result<()> {
+    let mut zip = ZipWriter::new_stream(writer);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("bookmarks.json", options)?;
+    serde_json::to_writer_pretty(&mut zip, bookmarks)?;
+
+    let tags: Vec<TagCount> = tags
+        .iter()
+        .map(|(tag, count)| TagCount { tag, count: *count })
+        .collect();
+    zip.start_file("tags.json", options)?;
+    serde_json::to_writer_pretty(&mut zip, &tags)?;
+
+    zip.start_file("bookmarks.html", options)?;
+    zip.write_all(super::netscape::render(bookmarks).as_bytes())?;
+
+    for bookmark in bookmarks {
+        let prefix = format!("bookmarks/{}", bookmark.bookmark_id);
+        add_bookmark_files(&mut zip, data_dir, &bookmark.bookmark_id, &prefix, true)?;
+    }
+
+    zip.finish()?;
+    tracing::info!(bookmarks = bookmarks.len(), "Account archive written");
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct CollectionExport<'a> {
+    #[serde(flatten)]
+    collection: &'a Collection,
+    /// In the order of the collection.
+    bookmarks: &'a [BookmarkWithUser],
+}
+
+/// Writes a collection as a zip laid out like the account archive:
+/// `collection.json` with its ordered bookmarks, a Netscape `bookmarks.html`,
+/// and the archived content of each bookmark under `bookmarks/<bookmark_id>/`.
+pub fn write_collection_archive<W: Write>(
+    writer: W,
+    data_dir: &Path,
+    collection: &Collection,
+    bookmarks: &[BookmarkWithUser],
+) -> Result<()> {
+    let mut zip = ZipWriter::new_stream(writer);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("collection.json", options)?;
+    serde_json::to_writer_pretty(
+        &mut zip,
+        &CollectionExport {
+            collection,
+            bookmarks,
+        },
+    )?;
+
+    zip.start_file("bookmarks.html", options)?;
+    zip.write_all(super::netscape::render(bookmarks).as_bytes())?;
+
+    for bookmark in bookmarks {
+        let prefix = format!("bookmarks/{}", bookmark.bookmark_id);
+        add_bookmark_files(&mut zip, data_dir, &bookmark.bookmark_id, &prefix, true)?;
+    }
+
+    zip.finish()?;
+    tracing::info!(
+        collection_id = %collection.collection_id,
+        bookmarks = bookmarks.len(),
+        "Collection archive written"
+    );
+    Ok(())
+}
+
+/// Copies the archived images of a bookmark from `data_dir` into the zip under
+/// `prefix`, along with the `index.*` renditions when `with_index` is set.
+/// Images are stored as is, they are already compressed.
+pub(super) fn add_bookmark_files<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    data_dir: &Path,
+    bookmark_id: &str,
+    prefix: &str,
+    with_index: bool,
+) -> Result<()> {
+    let bookmark_dir = data_dir.join(bookmark_id);
+    if !bookmark_dir.is_dir() {
+        tracing::warn!(?bookmark_dir, "Bookmark has no archived content");
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(&bookmark_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_index = name.starts_with("index.");
+        if is_index && !with_index {
+            continue;
+        }
+        let compression = if is_index {
+            CompressionMethod::Deflated
+        } else {
+            CompressionMethod::Stored
+        };
+        zip.start_file(
+            format!("{prefix}/{name}"),
+            SimpleFileOptions::default().compression_method(compression),
+        )?;
+        let mut file = std::fs::File::open(entry.path())?;
+        std::io::copy(&mut file, zip)?;
+    }
+    Ok(())
+}
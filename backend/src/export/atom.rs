@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use std::fmt::Write as _;
+
+use crate::db::bookmark::BookmarkWithUser;
+
+use super::escape_xml;
+
+/// A bookmark with its archived content, as it goes into a feed.
+#[derive(Debug)]
+pub struct AtomEntry {
+    pub bookmark: BookmarkWithUser,
+    pub content: Option<String>,
+}
+
+/// Renders an Atom feed. `base_url` is set as `xml:base` so the `/static/`
+/// image links of the archived content resolve in feed readers.
+pub fn render_atom(
+    feed_id: &str,
+    title: &str,
+    base_url: &str,
+    self_url: &str,
+    entries: &[AtomEntry],
+) -> String {
+    let updated = entries
+        .iter()
+        .map(|entry| entry_updated(&entry.bookmark))
+        .max()
+        .unwrap_or_else(Utc::now);
+    let mut atom = String::new();
+    let _ = write!(
+        atom,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xml:base="{base_url}">
+  <id>{feed_id}</id>
+  <title>{title}</title>
+  <updated>{updated}</updated>
+  <link rel="self" type="application/atom+xml" href="{self_url}"/>
+  <generator>bookmark-rs</generator>
+"#,
+        base_url = escape_xml(base_url),
+        feed_id = escape_xml(feed_id),
+        title = escape_xml(title),
+        updated = updated.to_rfc3339(),
+        self_url = escape_xml(self_url),
+    );
+    for entry in entries {
+        let bookmark = &entry.bookmark;
+        let published = bookmark.user_created_at.unwrap_or(bookmark.created_at);
+        let _ = write!(
+            atom,
+            r#"  <entry>
+    <id>{feed_id}/{bookmark_id}</id>
+    <title>{title}</title>
+    <link rel="alternate" href="{url}"/>
+    <published>{published}</published>
+    <updated>{updated}</updated>
+"#,
+            feed_id = escape_xml(feed_id),
+            bookmark_id = escape_xml(&bookmark.bookmark_id),
+            title = escape_xml(&bookmark.title),
+            url = escape_xml(&bookmark.url),
+            published = published.to_rfc3339(),
+            updated = entry_updated(bookmark).to_rfc3339(),
+        );
+        for tag in bookmark.tags.iter().flatten() {
+            let _ = writeln!(atom, r#"    <category term="{}"/>"#, escape_xml(tag));
+        }
+        match &entry.content {
+            Some(content) => {
+                let _ = writeln!(
+                    atom,
+                    r#"    <content type="html">{}</content>"#,
+                    escape_xml(content)
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    atom,
+                    r#"    <summary>{}</summary>"#,
+                    escape_xml(&bookmark.domain)
+                );
+            }
+        }
+        atom.push_str("  </entry>\n");
+    }
+    atom.push_str("</feed>\n");
+    atom
+}
+
+fn entry_updated(bookmark: &BookmarkWithUser) -> DateTime<Utc> {
+    bookmark
+        .user_updated_at
+        .or(bookmark.user_created_at)
+        .unwrap_or(bookmark.created_at)
+}
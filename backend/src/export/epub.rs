@@ -0,0 +1,293 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{Seek, Write};
+use std::path::Path;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::db::bookmark::BookmarkWithUser;
+
+use super::{escape_xml, xhtml};
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+const STYLE: &str = r#"body { font-family: serif; line-height: 1.5; }
+img { max-width: 100%; height: auto; }
+pre, code { white-space: pre-wrap; font-size: 0.9em; }
+.bookmark-meta { color: #555; font-size: 0.9em; }
+"#;
+
+#[derive(Debug)]
+struct ImageItem {
+    id: String,
+    href: String,
+    media_type: &'static str,
+}
+
+#[derive(Debug)]
+struct Chapter<'a> {
+    id: String,
+    href: String,
+    bookmark: &'a BookmarkWithUser,
+}
+
+/// Builds an EPUB 3 book with one chapter per bookmark, using the archived
+/// `index.html` and the images `rewrite_images` stored next to it. A toc.ncx
+/// is included alongside the navigation document for older e-readers.
+pub fn write_epub<W: Write + Seek>(
+    writer: W,
+    data_dir: &Path,
+    title: &str,
+    bookmarks: &[BookmarkWithUser],
+) -> Result<W> {
+    let mut zip = ZipWriter::new(writer);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default();
+
+    // The mimetype must be the first entry, uncompressed
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(CONTAINER.as_bytes())?;
+
+    zip.start_file("OEBPS/style.css", deflated)?;
+    zip.write_all(STYLE.as_bytes())?;
+
+    let mut chapters: Vec<Chapter> = Vec::with_capacity(bookmarks.len());
+    let mut images: Vec<ImageItem> = Vec::new();
+    for (index, bookmark) in bookmarks.iter().enumerate() {
+        let bookmark_dir = data_dir.join(&bookmark.bookmark_id);
+        let bookmark_images = find_images(&bookmark_dir)?;
+        let mut image_hrefs: HashMap<String, String> = HashMap::new();
+        for (image_index, (image_id, media_type)) in bookmark_images.into_iter().enumerate() {
+            let href = format!("images/{}/{image_id}", bookmark.bookmark_id);
+            zip.start_file(format!("OEBPS/{href}"), stored)?;
+            let mut file = std::fs::File::open(bookmark_dir.join(&image_id))?;
+            std::io::copy(&mut file, &mut zip)?;
+            image_hrefs.insert(image_id, href.clone());
+            images.push(ImageItem {
+                id: format!("image-{index}-{image_index}"),
+                href,
+                media_type,
+            });
+        }
+
+        let content = match std::fs::read_to_string(bookmark_dir.join("index.html")) {
+            Ok(content) => content,
+            Err(error) => {
+                tracing::warn!(?error, bookmark_id = %bookmark.bookmark_id, "Archived content not found");
+                "<p>Content not archived.</p>".to_owned()
+            }
+        };
+        let static_prefix = format!("/static/{}/", bookmark.bookmark_id);
+        let body = xhtml::from_html(&content, |src| {
+            src.strip_prefix(&static_prefix)
+                .and_then(|image_id| image_hrefs.get(image_id))
+                .map(|href| format!("../{href}"))
+        });
+
+        let chapter = Chapter {
+            id: format!("chapter-{index}"),
+            href: format!("chapters/chapter-{index}.xhtml"),
+            bookmark,
+        };
+        zip.start_file(format!("OEBPS/{}", chapter.href), deflated)?;
+        zip.write_all(render_chapter(bookmark, &body).as_bytes())?;
+        chapters.push(chapter);
+    }
+
+    let book_id = Uuid::new_v4();
+    zip.start_file("OEBPS/nav.xhtml", deflated)?;
+    zip.write_all(render_nav(title, &chapters).as_bytes())?;
+    zip.start_file("OEBPS/toc.ncx", deflated)?;
+    zip.write_all(render_ncx(book_id, title, &chapters).as_bytes())?;
+    zip.start_file("OEBPS/content.opf", deflated)?;
+    zip.write_all(render_opf(book_id, title, &chapters, &images).as_bytes())?;
+
+    let writer = zip.finish()?;
+    tracing::info!(
+        chapters = chapters.len(),
+        images = images.len(),
+        "EPUB written"
+    );
+    Ok(writer)
+}
+
+/// Lists the images saved for a bookmark with the media type sniffed from their
+/// first bytes, files that are not an EPUB core media type are left out.
+fn find_images(bookmark_dir: &Path) -> Result<Vec<(String, &'static str)>> {
+    if !bookmark_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut images = Vec::new();
+    for entry in std::fs::read_dir(bookmark_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !entry.file_type()?.is_file() || name.starts_with("index.") {
+            continue;
+        }
+        let bytes = std::fs::read(entry.path())?;
+        match sniff_media_type(&bytes) {
+            Some(media_type) => images.push((name, media_type)),
+            None => tracing::info!(image = %name, "Skipping image with unsupported media type"),
+        }
+    }
+    images.sort();
+    Ok(images)
+}
+
+fn sniff_media_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G']) {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF8") {
+        Some("image/gif")
+    } else if bytes.len() > 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).contains("<svg") {
+        Some("image/svg+xml")
+    } else {
+        None
+    }
+}
+
+fn render_chapter(bookmark: &BookmarkWithUser, body: &str) -> String {
+    let saved_at = bookmark.user_created_at.unwrap_or(bookmark.created_at);
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head>
+  <title>{title}</title>
+  <link rel="stylesheet" type="text/css" href="../style.css"/>
+</head>
+<body>
+  <h1>{title}</h1>
+  <p class="bookmark-meta">{domain} &#183; saved {saved_at} &#183; <a href="{url}">{url}</a></p>
+  {body}
+</body>
+</html>
+"#,
+        title = escape_xml(&bookmark.title),
+        domain = escape_xml(&bookmark.domain),
+        saved_at = saved_at.format("%Y-%m-%d"),
+        url = escape_xml(&bookmark.url),
+    )
+}
+
+fn render_nav(title: &str, chapters: &[Chapter]) -> String {
+    let mut items = String::new();
+    for chapter in chapters {
+        let saved_at = chapter
+            .bookmark
+            .user_created_at
+            .unwrap_or(chapter.bookmark.created_at);
+        let _ = writeln!(
+            items,
+            r#"      <li><a href="{href}">{title} &#8212; {domain}, {saved_at}</a></li>"#,
+            href = chapter.href,
+            title = escape_xml(&chapter.bookmark.title),
+            domain = escape_xml(&chapter.bookmark.domain),
+            saved_at = saved_at.format("%Y-%m-%d"),
+        );
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head>
+  <title>{title}</title>
+  <link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+  <nav epub:type="toc" id="toc">
+    <h1>{title}</h1>
+    <ol>
+{items}    </ol>
+  </nav>
+</body>
+</html>
+"#,
+        title = escape_xml(title),
+    )
+}
+
+fn render_ncx(book_id: Uuid, title: &str, chapters: &[Chapter]) -> String {
+    let mut points = String::new();
+    for (index, chapter) in chapters.iter().enumerate() {
+        let _ = writeln!(
+            points,
+            r#"    <navPoint id="nav-{id}" playOrder="{order}"><navLabel><text>{title}</text></navLabel><content src="{href}"/></navPoint>"#,
+            id = chapter.id,
+            order = index + 1,
+            title = escape_xml(&chapter.bookmark.title),
+            href = chapter.href,
+        );
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+  <head><meta name="dtb:uid" content="urn:uuid:{book_id}"/></head>
+  <docTitle><text>{title}</text></docTitle>
+  <navMap>
+{points}  </navMap>
+</ncx>
+"#,
+        title = escape_xml(title),
+    )
+}
+
+fn render_opf(book_id: Uuid, title: &str, chapters: &[Chapter], images: &[ImageItem]) -> String {
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    for chapter in chapters {
+        let _ = writeln!(
+            manifest,
+            r#"    <item id="{id}" href="{href}" media-type="application/xhtml+xml"/>"#,
+            id = chapter.id,
+            href = chapter.href,
+        );
+        let _ = writeln!(spine, r#"    <itemref idref="{}"/>"#, chapter.id);
+    }
+    for image in images {
+        let _ = writeln!(
+            manifest,
+            r#"    <item id="{id}" href="{href}" media-type="{media_type}"/>"#,
+            id = image.id,
+            href = image.href,
+            media_type = image.media_type,
+        );
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">urn:uuid:{book_id}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:language>en</dc:language>
+    <meta property="dcterms:modified">{modified}</meta>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+    <item id="style" href="style.css" media-type="text/css"/>
+{manifest}  </manifest>
+  <spine toc="ncx">
+{spine}  </spine>
+</package>
+"#,
+        title = escape_xml(title),
+        modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+    )
+}
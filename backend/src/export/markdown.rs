@@ -0,0 +1,158 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use htmd::HtmlToMarkdown;
+use lol_html::{element, rewrite_str, RewriteStrSettings};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::db::bookmark::BookmarkWithUser;
+
+const ATTACHMENTS_DIR: &str = "attachments";
+const NOTE_NAME_MAX_CHARS: usize = 100;
+
+/// Converts archived content to CommonMark. Images saved under
+/// `/static/<bookmark_id>/` are linked as `<image_prefix><image_id>`, an empty
+/// prefix gives links relative to the bookmark directory.
+pub fn html_to_markdown(html: &str, bookmark_id: &str, image_prefix: &str) -> Result<String> {
+    let static_prefix = format!("/static/{bookmark_id}/");
+    let html = rewrite_str(
+        html,
+        RewriteStrSettings {
+            element_content_handlers: vec![element!("img[src]", |el| {
+                let src = el.get_attribute("src").expect("img[src] was required");
+                if let Some(image_id) = src.strip_prefix(&static_prefix) {
+                    el.set_attribute("src", &format!("{image_prefix}{image_id}"))?;
+                }
+                Ok(())
+            })],
+            ..RewriteStrSettings::default()
+        },
+    )?;
+    let converter = HtmlToMarkdown::builder()
+        .skip_tags(vec!["script", "style", "noscript", "iframe", "form"])
+        .build();
+    let markdown = converter.convert(&html)?;
+    Ok(format!("{}\n", markdown.trim_end()))
+}
+
+/// Writes an Obsidian-style vault as a zip: one note per bookmark at the root,
+/// with YAML front matter holding the url and tags, and its images under
+/// `attachments/<bookmark_id>/`.
+pub fn write_markdown_archive<W: Write>(
+    writer: W,
+    data_dir: &Path,
+    bookmarks: &[BookmarkWithUser],
+) -> Result<()> {
+    let mut zip = ZipWriter::new_stream(writer);
+    let options = SimpleFileOptions::default();
+    let mut note_names: HashSet<String> = HashSet::with_capacity(bookmarks.len());
+
+    for bookmark in bookmarks {
+        let image_prefix = format!("{ATTACHMENTS_DIR}/{}/", bookmark.bookmark_id);
+        let body = match std::fs::read_to_string(
+            data_dir.join(&bookmark.bookmark_id).join("index.html"),
+        ) {
+            Ok(content) => html_to_markdown(&content, &bookmark.bookmark_id, &image_prefix)?,
+            Err(error) => {
+                tracing::warn!(?error, bookmark_id = %bookmark.bookmark_id, "Archived content not found");
+                "Content not archived.\n".to_owned()
+            }
+        };
+
+        let note_name = unique_note_name(&mut note_names, bookmark);
+        zip.start_file(format!("{note_name}.md"), options)?;
+        zip.write_all(front_matter(bookmark).as_bytes())?;
+        zip.write_all(body.as_bytes())?;
+
+        super::archive::add_bookmark_files(
+            &mut zip,
+            data_dir,
+            &bookmark.bookmark_id,
+            image_prefix.trim_end_matches('/'),
+            false,
+        )?;
+    }
+
+    zip.finish()?;
+    tracing::info!(bookmarks = bookmarks.len(), "Markdown archive written");
+    Ok(())
+}
+
+fn front_matter(bookmark: &BookmarkWithUser) -> String {
+    let saved_at: DateTime<Utc> = bookmark.user_created_at.unwrap_or(bookmark.created_at);
+    let mut yaml = String::from("---\n");
+    let _ = writeln!(yaml, "title: {}", yaml_string(&bookmark.title));
+    let _ = writeln!(yaml, "url: {}", yaml_string(&bookmark.url));
+    let _ = writeln!(yaml, "domain: {}", yaml_string(&bookmark.domain));
+    match bookmark.tags.as_deref() {
+        Some(tags) if !tags.is_empty() => {
+            yaml.push_str("tags:\n");
+            for tag in tags {
+                let _ = writeln!(yaml, "  - {}", yaml_string(tag));
+            }
+        }
+        _ => yaml.push_str("tags: []\n"),
+    }
+    let _ = writeln!(yaml, "created: {}", saved_at.format("%Y-%m-%dT%H:%M:%SZ"));
+    let _ = writeln!(yaml, "bookmark_id: {}", yaml_string(&bookmark.bookmark_id));
+    yaml.push_str("---\n\n");
+    yaml
+}
+
+fn yaml_string(raw: &str) -> String {
+    let mut quoted = String::with_capacity(raw.len() + 2);
+    quoted.push('"');
+    for c in raw.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04X}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Notes are named after the bookmark title, without the characters Obsidian
+/// refuses in file names. Clashing titles get a numeric suffix.
+fn unique_note_name(taken: &mut HashSet<String>, bookmark: &BookmarkWithUser) -> String {
+    let cleaned = bookmark
+        .title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let base: String = cleaned
+        .trim_start_matches('.')
+        .chars()
+        .take(NOTE_NAME_MAX_CHARS)
+        .collect();
+    let base = if base.trim().is_empty() {
+        bookmark.bookmark_id.clone()
+    } else {
+        base.trim().to_owned()
+    };
+    let mut name = base.clone();
+    let mut counter = 2;
+    while !taken.insert(name.to_lowercase()) {
+        name = format!("{base} ({counter})");
+        counter += 1;
+    }
+    name
+}
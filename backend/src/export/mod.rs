@@ -0,0 +1,75 @@
+use axum::body::{Body, Bytes};
+use std::io::{self, BufWriter, Write};
+use tokio::sync::mpsc;
+
+mod archive;
+mod atom;
+mod epub;
+mod markdown;
+mod netscape;
+mod xhtml;
+
+pub use self::archive::{write_account_archive, write_collection_archive};
+pub use self::atom::{render_atom, AtomEntry};
+pub use self::epub::write_epub;
+pub use self::markdown::{html_to_markdown, write_markdown_archive};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Blocking `Write` half of a streamed response body, every write becomes a
+/// body chunk. Fails with `BrokenPipe` once the client goes away so the
+/// producer stops early.
+pub struct ChannelWriter {
+    tx: mpsc::Sender<io::Result<Bytes>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "response body dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `produce` on the blocking pool and streams whatever it writes as the
+/// response body, so large exports start downloading right away instead of
+/// being built in memory first.
+pub fn stream_body<F>(produce: F) -> Body
+where
+    F: FnOnce(&mut BufWriter<ChannelWriter>) -> anyhow::Result<()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel::<io::Result<Bytes>>(8);
+    let error_tx = tx.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut writer = BufWriter::with_capacity(CHUNK_SIZE, ChannelWriter { tx });
+        let result = produce(&mut writer).and_then(|_| writer.flush().map_err(anyhow::Error::from));
+        if let Err(error) = result {
+            tracing::error!(?error, "Fail to produce streamed body");
+            let _ = error_tx.blocking_send(Err(io::Error::other(error.to_string())));
+        }
+    });
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+    Body::from_stream(stream)
+}
+
+pub(crate) fn escape_xml(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
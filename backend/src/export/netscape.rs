@@ -0,0 +1,38 @@
+use std::fmt::Write;
+
+use crate::db::bookmark::BookmarkWithUser;
+
+use super::escape_xml;
+
+const HEADER: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<!-- This is an automatically generated file.
+     It will be read and overwritten.
+     DO NOT EDIT! -->
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+"#;
+
+/// Renders bookmarks in the Netscape bookmark file format, the one every
+/// browser and bookmark service knows how to import. Tags go in the `TAGS`
+/// attribute, which is also what the importer reads back.
+pub fn render(bookmarks: &[BookmarkWithUser]) -> String {
+    let mut html = String::from(HEADER);
+    for bookmark in bookmarks {
+        let added = bookmark.user_created_at.unwrap_or(bookmark.created_at);
+        let modified = bookmark.user_updated_at.unwrap_or(added);
+        let tags = bookmark.tags.clone().unwrap_or_default().join(",");
+        let _ = writeln!(
+            html,
+            r#"    <DT><A HREF="{href}" ADD_DATE="{added}" LAST_MODIFIED="{modified}" TAGS="{tags}">{title}</A>"#,
+            href = escape_xml(&bookmark.url),
+            added = added.timestamp(),
+            modified = modified.timestamp(),
+            tags = escape_xml(&tags),
+            title = escape_xml(&bookmark.title),
+        );
+    }
+    html.push_str("</DL><p>\n");
+    html
+}
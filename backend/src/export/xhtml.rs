@@ -0,0 +1,78 @@
+use ego_tree::NodeRef;
+use scraper::{Html, Node};
+
+use super::escape_xml;
+
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+const DROPPED_ELEMENTS: [&str; 11] = [
+    "button", "embed", "form", "iframe", "input", "noscript", "object", "script", "select",
+    "style", "textarea",
+];
+
+/// Converts an HTML fragment, like the archived `index.html`, into well-formed
+/// XHTML. Scripts, embeds and form controls are dropped, as are attributes
+/// that are not valid XML names. `rewrite_src` maps every `img[src]` to its new
+/// location, images it returns `None` for are removed.
+pub fn from_html(html: &str, rewrite_src: impl Fn(&str) -> Option<String>) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut output = String::with_capacity(html.len());
+    for child in fragment.root_element().children() {
+        write_node(child, &mut output, &rewrite_src);
+    }
+    output
+}
+
+fn write_node(
+    node: NodeRef<Node>,
+    output: &mut String,
+    rewrite_src: &impl Fn(&str) -> Option<String>,
+) {
+    match node.value() {
+        Node::Text(text) => output.push_str(&escape_xml(text)),
+        Node::Element(element) => {
+            let name = element.name().to_ascii_lowercase();
+            if DROPPED_ELEMENTS.contains(&name.as_str()) || !is_xml_name(&name) {
+                return;
+            }
+            let mut attributes = String::new();
+            for (key, value) in element.attrs() {
+                let key = key.to_ascii_lowercase();
+                if !is_xml_name(&key) || key.starts_with("on") || key.starts_with("xmlns") {
+                    continue;
+                }
+                let value = if name == "img" && key == "src" {
+                    match rewrite_src(value) {
+                        Some(src) => src,
+                        None => return,
+                    }
+                } else {
+                    value.to_owned()
+                };
+                attributes.push_str(&format!(r#" {key}="{}""#, escape_xml(&value)));
+            }
+            if name == "img" && !attributes.contains(" alt=") {
+                attributes.push_str(r#" alt="""#);
+            }
+            if VOID_ELEMENTS.contains(&name.as_str()) {
+                output.push_str(&format!("<{name}{attributes}/>"));
+                return;
+            }
+            output.push_str(&format!("<{name}{attributes}>"));
+            for child in node.children() {
+                write_node(child, output, rewrite_src);
+            }
+            output.push_str(&format!("</{name}>"));
+        }
+        _ => {}
+    }
+}
+
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
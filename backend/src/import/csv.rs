@@ -0,0 +1,78 @@
+use anyhow::Result;
+
+use super::ParsedImport;
+
+const TAG_SEPARATORS: [char; 3] = [',', ';', '|'];
+
+struct Columns {
+    url: usize,
+    tags: Option<usize>,
+    title: Option<usize>,
+    created_at: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(record: &csv::StringRecord) -> Option<Self> {
+        let position = |names: &[&str]| {
+            record.iter().position(|field| {
+                names
+                    .iter()
+                    .any(|name| field.trim().eq_ignore_ascii_case(name))
+            })
+        };
+        let url = position(&["url", "href", "link"])?;
+        Some(Columns {
+            url,
+            tags: position(&["tags", "tag"]),
+            title: position(&["title", "description"]),
+            created_at: position(&["created_at", "time_added", "added", "date"]),
+        })
+    }
+}
+
+/// Parses a plain CSV of urls and tags. Without a header row the first column
+/// is the url and the second the tags; with one, columns are matched by name,
+/// which also covers Pocket's newer `part_000000.csv` export.
+pub fn parse(content: &str) -> Result<ParsedImport> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let mut parsed = ParsedImport::default();
+    let mut columns: Option<Columns> = None;
+    for (line, record) in reader.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                parsed.push_error(format!("line {}", line + 1), error.to_string());
+                continue;
+            }
+        };
+        if line == 0 {
+            if let Some(header) = Columns::from_header(&record) {
+                columns = Some(header);
+                continue;
+            }
+        }
+        let columns = columns.get_or_insert(Columns {
+            url: 0,
+            tags: Some(1),
+            title: None,
+            created_at: None,
+        });
+        let field =
+            |index: Option<usize>| index.and_then(|i| record.get(i)).filter(|f| !f.is_empty());
+        let Some(url) = field(Some(columns.url)) else {
+            parsed.push_error(format!("line {}", line + 1), "missing url");
+            continue;
+        };
+        let tags = field(columns.tags)
+            .map(|t| super::split_tags(t, &TAG_SEPARATORS))
+            .unwrap_or_default();
+        let title = field(columns.title).map(str::to_owned);
+        let created_at = field(columns.created_at).and_then(super::parse_date);
+        parsed.push(url, title, tags, created_at);
+    }
+    Ok(parsed)
+}
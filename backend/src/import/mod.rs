@@ -0,0 +1,148 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use url::Url;
+
+use crate::db::import::ImportFormat;
+
+mod csv;
+mod netscape;
+mod pinboard;
+
+#[derive(Debug, Clone)]
+pub struct ImportEntry {
+    pub url: Url,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportError {
+    pub entry: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub entries: Vec<ImportEntry>,
+    pub errors: Vec<ImportError>,
+}
+
+impl ParsedImport {
+    fn push(
+        &mut self,
+        raw_url: &str,
+        title: Option<String>,
+        tags: Vec<String>,
+        created_at: Option<DateTime<Utc>>,
+    ) {
+        match parse_url(raw_url) {
+            Ok(url) => self.entries.push(ImportEntry {
+                url,
+                title: title.filter(|t| !t.trim().is_empty()),
+                tags,
+                created_at,
+            }),
+            Err(reason) => self.errors.push(ImportError {
+                entry: raw_url.to_owned(),
+                reason,
+            }),
+        }
+    }
+
+    fn push_error(&mut self, entry: impl Into<String>, reason: impl Into<String>) {
+        self.errors.push(ImportError {
+            entry: entry.into(),
+            reason: reason.into(),
+        });
+    }
+}
+
+pub fn parse(format: ImportFormat, content: &str) -> Result<ParsedImport> {
+    let parsed = match format {
+        ImportFormat::Netscape | ImportFormat::Pocket => netscape::parse(content)?,
+        ImportFormat::Pinboard => pinboard::parse(content)?,
+        ImportFormat::Csv => csv::parse(content)?,
+    };
+    Ok(merge_duplicates(parsed))
+}
+
+/// Browser exports list the same URL once per folder, collapse them into a
+/// single entry with the union of tags and the oldest add date.
+fn merge_duplicates(parsed: ParsedImport) -> ParsedImport {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut entries: Vec<ImportEntry> = Vec::with_capacity(parsed.entries.len());
+    for entry in parsed.entries {
+        match index.get(entry.url.as_str()) {
+            Some(&position) => {
+                let existing = &mut entries[position];
+                for tag in entry.tags {
+                    if !existing.tags.contains(&tag) {
+                        existing.tags.push(tag);
+                    }
+                }
+                existing.created_at = match (existing.created_at, entry.created_at) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                if existing.title.is_none() {
+                    existing.title = entry.title;
+                }
+            }
+            None => {
+                index.insert(entry.url.to_string(), entries.len());
+                entries.push(entry);
+            }
+        }
+    }
+    ParsedImport {
+        entries,
+        errors: parsed.errors,
+    }
+}
+
+fn parse_url(raw_url: &str) -> Result<Url, String> {
+    let url = Url::parse(raw_url.trim()).map_err(|error| format!("invalid url: {error}"))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(format!("unsupported url scheme: {scheme}")),
+    }
+}
+
+fn split_tags(raw: &str, separators: &[char]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw.split(separators).map(str::trim) {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_owned());
+        }
+    }
+    tags
+}
+
+/// Exports use either unix timestamps (seconds, sometimes microseconds) or
+/// ISO-8601 dates.
+fn parse_date(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    if let Ok(timestamp) = raw.parse::<i64>() {
+        return if timestamp > 100_000_000_000_000 {
+            DateTime::from_timestamp_micros(timestamp)
+        } else if timestamp > 100_000_000_000 {
+            DateTime::from_timestamp_millis(timestamp)
+        } else {
+            DateTime::from_timestamp(timestamp, 0)
+        }
+        .filter(|date| date.timestamp() > 0);
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(raw) {
+        return Some(date.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc())
+}
@@ -0,0 +1,195 @@
+use anyhow::Result;
+use lol_html::{element, rewrite_str, text, RewriteStrSettings};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::ParsedImport;
+
+#[derive(Debug, Default)]
+struct Anchor {
+    href: String,
+    title: String,
+    tags: Option<String>,
+    add_date: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    folders: Vec<Option<String>>,
+    pending_folder: Option<Option<String>>,
+    folder_title: Option<String>,
+    anchor: Option<Anchor>,
+    parsed: ParsedImport,
+}
+
+/// Parses the Netscape bookmark file format exported by Chrome, Firefox and
+/// Safari. Pocket's `ril_export.html` is a flat variant of the same format,
+/// using `time_added` instead of `add_date`.
+///
+/// Folders (`<H3>` followed by a `<DL>`) become tags for every bookmark they
+/// contain, except the browser's own toolbar and unfiled roots.
+pub fn parse(content: &str) -> Result<ParsedImport> {
+    let state = Rc::new(RefCell::new(State::default()));
+
+    let on_folder = {
+        let state = state.clone();
+        element!("h3", move |el| {
+            let is_root = el.has_attribute("personal_toolbar_folder")
+                || el.has_attribute("unfiled_bookmarks_folder");
+            state.borrow_mut().folder_title = Some(String::new());
+            let state = state.clone();
+            if let Some(handlers) = el.end_tag_handlers() {
+                handlers.push(Box::new(move |_| {
+                    let mut state = state.borrow_mut();
+                    let title = state.folder_title.take().map(|t| decode_entities(t.trim()));
+                    state.pending_folder = Some(title.filter(|t| !is_root && !t.is_empty()));
+                    Ok(())
+                }));
+            }
+            Ok(())
+        })
+    };
+
+    let on_folder_text = {
+        let state = state.clone();
+        text!("h3", move |chunk| {
+            if let Some(title) = state.borrow_mut().folder_title.as_mut() {
+                title.push_str(chunk.as_str());
+            }
+            Ok(())
+        })
+    };
+
+    let on_list = {
+        let state = state.clone();
+        element!("dl", move |el| {
+            {
+                let mut state = state.borrow_mut();
+                let folder = state.pending_folder.take().flatten();
+                state.folders.push(folder);
+            }
+            let state = state.clone();
+            if let Some(handlers) = el.end_tag_handlers() {
+                handlers.push(Box::new(move |_| {
+                    state.borrow_mut().folders.pop();
+                    Ok(())
+                }));
+            }
+            Ok(())
+        })
+    };
+
+    let on_anchor = {
+        let state = state.clone();
+        element!("a[href]", move |el| {
+            let href = el.get_attribute("href").expect("a[href] was required");
+            state.borrow_mut().anchor = Some(Anchor {
+                href: decode_entities(&href),
+                title: String::new(),
+                tags: el.get_attribute("tags"),
+                add_date: el
+                    .get_attribute("add_date")
+                    .or_else(|| el.get_attribute("time_added")),
+            });
+            let state = state.clone();
+            if let Some(handlers) = el.end_tag_handlers() {
+                handlers.push(Box::new(move |_| {
+                    let mut state = state.borrow_mut();
+                    if let Some(anchor) = state.anchor.take() {
+                        let mut tags: Vec<String> =
+                            state.folders.iter().flatten().cloned().collect();
+                        for tag in anchor
+                            .tags
+                            .map(|t| super::split_tags(&decode_entities(&t), &[',']))
+                            .unwrap_or_default()
+                        {
+                            if !tags.contains(&tag) {
+                                tags.push(tag);
+                            }
+                        }
+                        let created_at = anchor.add_date.as_deref().and_then(super::parse_date);
+                        let title = decode_entities(anchor.title.trim());
+                        state
+                            .parsed
+                            .push(&anchor.href, Some(title), tags, created_at);
+                    }
+                    Ok(())
+                }));
+            }
+            Ok(())
+        })
+    };
+
+    let on_anchor_text = {
+        let state = state.clone();
+        text!("a[href]", move |chunk| {
+            if let Some(anchor) = state.borrow_mut().anchor.as_mut() {
+                anchor.title.push_str(chunk.as_str());
+            }
+            Ok(())
+        })
+    };
+
+    let _ = rewrite_str(
+        content,
+        RewriteStrSettings {
+            element_content_handlers: vec![
+                on_folder,
+                on_folder_text,
+                on_list,
+                on_anchor,
+                on_anchor_text,
+            ],
+            ..RewriteStrSettings::default()
+        },
+    )?;
+
+    let state = Rc::try_unwrap(state)
+        .map_err(|_| anyhow::anyhow!("Netscape parser state still borrowed"))?
+        .into_inner();
+    Ok(state.parsed)
+}
+
+fn decode_entities(raw: &str) -> String {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&rest[1..end]).map(|c| (c, end)));
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = if let Some(hex) = entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok()
+            } else {
+                entity.strip_prefix('#').and_then(|dec| dec.parse().ok())
+            };
+            code.and_then(char::from_u32)
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::ParsedImport;
+
+#[derive(Debug, Deserialize)]
+struct PinboardPost {
+    href: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    tags: Option<String>,
+    #[serde(default)]
+    time: Option<String>,
+}
+
+/// Parses the JSON export from `https://api.pinboard.in/v1/posts/all?format=json`,
+/// where tags are a single space separated string.
+pub fn parse(content: &str) -> Result<ParsedImport> {
+    let posts: Vec<serde_json::Value> =
+        serde_json::from_str(content).context("Pinboard export must be a JSON array")?;
+    let mut parsed = ParsedImport::default();
+    for (index, value) in posts.into_iter().enumerate() {
+        match serde_json::from_value::<PinboardPost>(value) {
+            Ok(post) => {
+                let tags = post
+                    .tags
+                    .map(|t| super::split_tags(&t, &[' ']))
+                    .unwrap_or_default();
+                let created_at = post.time.as_deref().and_then(super::parse_date);
+                parsed.push(&post.href, post.description, tags, created_at);
+            }
+            Err(error) => parsed.push_error(format!("post #{index}"), error.to_string()),
+        }
+    }
+    Ok(parsed)
+}
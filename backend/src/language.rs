@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumString, IntoStaticStr};
+use whatlang::Lang;
+
+/// Bookmarks shorter than this are too short to be detected reliably.
+const MIN_DETECTION_CHARS: usize = 64;
+/// Detection only needs the beginning of the text.
+const MAX_DETECTION_CHARS: usize = 16 * 1024;
+
+/// The Postgres text search configurations, named as in `pg_ts_config`.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumString, IntoStaticStr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Language {
+    Simple,
+    Arabic,
+    Armenian,
+    Basque,
+    Catalan,
+    Danish,
+    Dutch,
+    #[default]
+    English,
+    Finnish,
+    French,
+    German,
+    Greek,
+    Hindi,
+    Hungarian,
+    Indonesian,
+    Irish,
+    Italian,
+    Lithuanian,
+    Nepali,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Serbian,
+    Spanish,
+    Swedish,
+    Tamil,
+    Turkish,
+    Yiddish,
+}
+
+impl Language {
+    /// The `regconfig` name.
+    pub fn config(self) -> &'static str {
+        self.into()
+    }
+
+    /// Languages without a stemmer in Postgres are indexed with `simple`.
+    fn from_lang(lang: Lang) -> Self {
+        match lang {
+            Lang::Ara => Language::Arabic,
+            Lang::Cat => Language::Catalan,
+            Lang::Dan => Language::Danish,
+            Lang::Deu => Language::German,
+            Lang::Ell => Language::Greek,
+            Lang::Eng => Language::English,
+            Lang::Fin => Language::Finnish,
+            Lang::Fra => Language::French,
+            Lang::Hin => Language::Hindi,
+            Lang::Hun => Language::Hungarian,
+            Lang::Ind => Language::Indonesian,
+            Lang::Ita => Language::Italian,
+            Lang::Lit => Language::Lithuanian,
+            Lang::Nep => Language::Nepali,
+            Lang::Nld => Language::Dutch,
+            Lang::Nob => Language::Norwegian,
+            Lang::Por => Language::Portuguese,
+            Lang::Ron => Language::Romanian,
+            Lang::Rus => Language::Russian,
+            Lang::Spa => Language::Spanish,
+            Lang::Srp => Language::Serbian,
+            Lang::Swe => Language::Swedish,
+            Lang::Tam => Language::Tamil,
+            Lang::Tur => Language::Turkish,
+            Lang::Yid => Language::Yiddish,
+            _ => Language::Simple,
+        }
+    }
+}
+
+/// Detects the language of a bookmark, falling back to English when the text
+/// is too short or ambiguous.
+pub fn detect(title: &str, text_content: &str) -> Language {
+    let sample: String = title
+        .chars()
+        .chain(['\n'])
+        .chain(text_content.chars())
+        .take(MAX_DETECTION_CHARS)
+        .collect();
+    if sample.chars().count() < MIN_DETECTION_CHARS {
+        return Language::default();
+    }
+    match whatlang::detect(&sample) {
+        Some(info) if info.is_reliable() => Language::from_lang(info.lang()),
+        _ => Language::default(),
+    }
+}
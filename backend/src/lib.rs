@@ -5,18 +5,26 @@ use strum_macros::EnumString;
 use url::Url;
 
 use self::db::PgPool;
+use self::embedding::Embedder;
 
 pub mod daemon;
 pub mod db;
+pub mod embedding;
 pub mod endpoints;
 pub mod error;
+pub mod export;
+pub mod import;
+pub mod language;
 pub mod readability;
+pub mod tag;
 
 #[derive(Clone)]
 pub struct AppContext {
     pub pool: PgPool,
     pub config: Arc<Config>,
     pub tx_new_task: tokio::sync::watch::Sender<()>,
+    /// Loaded when `EMBEDDING_MODEL_DIR` is set, enables semantic search.
+    pub embedder: Option<Arc<Embedder>>,
 }
 
 #[derive(Debug, Clone, EnumString)]
@@ -48,6 +56,9 @@ pub struct Config {
 
     #[arg(long, env = "APP_DATA_DIR")]
     pub data_dir: PathBuf,
+
+    #[arg(long, env = "EMBEDDING_MODEL_DIR")]
+    pub embedding_model_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Args)]
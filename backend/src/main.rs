@@ -2,6 +2,7 @@ use anyhow::bail;
 use axum::{Extension, Router};
 use axum_otel_metrics::HttpMetricsLayerBuilder;
 use backend::db::PgPool;
+use backend::embedding::Embedder;
 use backend::{daemon, db, endpoints, AppContext, Config, Env};
 use clap::Parser;
 use std::collections::HashMap;
@@ -21,9 +22,21 @@ async fn main() -> anyhow::Result<()> {
     let pool = db::get_pool(config.pg.clone()).await?;
     db::run_migrations(&pool).await?;
 
+    let embedder = match config.embedding_model_dir.clone() {
+        Some(dir) => Some(Arc::new(
+            tokio::task::spawn_blocking(move || Embedder::load(&dir)).await??,
+        )),
+        None => None,
+    };
+
     let (tx, rx) = tokio::sync::watch::channel(());
-    let daemon = tokio::spawn(setup_daemon(config.clone(), pool.clone(), rx));
-    let app_server = setup_app(&config, pool.clone(), tx);
+    let daemon = tokio::spawn(setup_daemon(
+        config.clone(),
+        pool.clone(),
+        embedder.clone(),
+        rx,
+    ));
+    let app_server = setup_app(&config, pool.clone(), embedder, tx);
     tokio::select! {
         result = app_server => {
             if let Err(error) = result {
@@ -62,12 +75,14 @@ async fn shutdown_signal() {
 async fn setup_app(
     config: &Config,
     pool: PgPool,
+    embedder: Option<Arc<Embedder>>,
     tx: tokio::sync::watch::Sender<()>,
 ) -> anyhow::Result<()> {
     let app_state = AppContext {
         config: Arc::new(config.clone()),
         pool,
         tx_new_task: tx,
+        embedder,
     };
     let metrics = HttpMetricsLayerBuilder::new()
         .with_service_name("bookmark-rs".to_string())
@@ -92,6 +107,7 @@ async fn setup_app(
 async fn setup_daemon(
     config: Config,
     pool: PgPool,
+    embedder: Option<Arc<Embedder>>,
     rx: tokio::sync::watch::Receiver<()>,
 ) -> anyhow::Result<()> {
     let data_dir = config.data_dir.clone();
@@ -111,7 +127,7 @@ async fn setup_daemon(
         std::fs::remove_file(&test_file)?;
         tracing::info!("Data dir is valid");
     }
-    daemon::run(&pool, &config, rx).await
+    daemon::run(&pool, &config, embedder, rx).await
 }
 
 fn setup_tracing(config: &Config) -> anyhow::Result<()> {
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Separates the levels of a hierarchical tag, as in `lang/rust/async`.
+pub const SEPARATOR: char = '/';
+
+/// How a user's tags are normalized when they are written.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagPolicy {
+    /// Folds tags to lowercase.
+    #[serde(default)]
+    pub lowercase: bool,
+    /// Characters kept besides letters, digits, spaces and the separator,
+    /// `None` keeps any character.
+    #[serde(default)]
+    pub allowed_chars: Option<String>,
+    /// Synonyms replaced with their canonical tag.
+    #[serde(skip)]
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl TagPolicy {
+    /// The normalized tag, `None` when nothing is left of it. Whitespace is
+    /// always trimmed and collapsed, as are the levels of the hierarchy.
+    pub fn normalize_tag(&self, tag: &str) -> Option<String> {
+        self.clean_tag(tag)
+            .map(|tag| self.aliases.get(&tag).cloned().unwrap_or(tag))
+    }
+
+    /// The normalized tag, without replacing aliases.
+    pub fn clean_tag(&self, tag: &str) -> Option<String> {
+        let tag = if self.lowercase {
+            tag.to_lowercase()
+        } else {
+            tag.to_owned()
+        };
+        let tag: String = match &self.allowed_chars {
+            Some(allowed) => tag
+                .chars()
+                .filter(|c| {
+                    c.is_alphanumeric()
+                        || c.is_whitespace()
+                        || *c == SEPARATOR
+                        || allowed.contains(*c)
+                })
+                .collect(),
+            None => tag,
+        };
+        let tag = tag
+            .split(SEPARATOR)
+            .map(|level| level.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|level| !level.is_empty())
+            .collect::<Vec<_>>()
+            .join(&SEPARATOR.to_string());
+        (!tag.is_empty()).then_some(tag)
+    }
+
+    /// The normalized tags without the empty ones and the duplicates, in their
+    /// original order.
+    pub fn normalize(&self, tags: &[String]) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags.iter().filter_map(|t| self.normalize_tag(t)) {
+            if !normalized.contains(&tag) {
+                normalized.push(tag);
+            }
+        }
+        normalized
+    }
+}
+
+/// A level of the tag hierarchy, `count` includes the descendants.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagNode {
+    pub tag: String,
+    /// The last level of `tag`.
+    pub name: String,
+    pub count: i64,
+    pub children: Vec<TagNode>,
+}
+
+impl TagNode {
+    /// Arranges the counts of every tag and ancestor as a tree, siblings keep
+    /// the order of `counts`.
+    pub fn tree(counts: &[(String, i64)]) -> Vec<TagNode> {
+        Self::children(None, counts)
+    }
+
+    fn children(parent: Option<&str>, counts: &[(String, i64)]) -> Vec<TagNode> {
+        counts
+            .iter()
+            .filter(|(tag, _)| tag.rsplit_once(SEPARATOR).map(|(p, _)| p) == parent)
+            .map(|(tag, count)| TagNode {
+                tag: tag.clone(),
+                name: tag.rsplit(SEPARATOR).next().unwrap_or(tag).to_owned(),
+                count: *count,
+                children: Self::children(Some(tag), counts),
+            })
+            .collect()
+    }
+}
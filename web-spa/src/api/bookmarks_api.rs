@@ -14,8 +14,49 @@ pub struct Bookmark {
     pub created_at: DateTime<Utc>,
     pub user_id: Option<Uuid>,
     pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// In minutes.
+    #[serde(default)]
+    pub reading_time: i32,
+    #[serde(default)]
+    pub word_count: i32,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub read_at: Option<DateTime<Utc>>,
+    /// How far the page was read, from 0 to 1.
+    #[serde(default)]
+    pub progress: f32,
     pub user_created_at: DateTime<Utc>,
     pub user_updated_at: Option<DateTime<Utc>>,
+    /// Only returned with a single bookmark.
+    #[serde(default)]
+    pub highlights: Vec<Highlight>,
+}
+
+/// A passage of the archived page, offsets are in UTF-16 code units of the
+/// text of the page.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Highlight {
+    pub highlight_id: Uuid,
+    pub exact: String,
+    pub prefix: String,
+    pub suffix: String,
+    pub start_offset: i32,
+    pub end_offset: i32,
+    pub note: Option<String>,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct NewHighlight {
+    pub exact: String,
+    pub prefix: String,
+    pub suffix: String,
+    pub start_offset: i32,
+    pub end_offset: i32,
 }
 
 #[derive(PartialEq, Clone, Serialize, Deserialize)]
@@ -32,6 +73,41 @@ pub struct NewBookmarkResponse {
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct RelatedBookmarks {
+    bookmarks: Vec<Bookmark>,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct TagSuggestion {
+    pub tag: String,
+    pub confidence: f32,
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TagSuggestions {
+    suggestions: Vec<TagSuggestion>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Note {
+    note: Option<String>,
+}
+
+/// The changes to the reading state, unset fields are kept.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReadingState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favorite: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f32>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Tags {
     tags: Vec<String>,
@@ -83,6 +159,28 @@ pub async fn get_by_id(token: &str, id: &str) -> Result<Option<Bookmark>, Error>
     }
 }
 
+/// The user's other bookmarks most similar to `id`, best first.
+pub async fn get_related(token: &str, id: &str) -> Result<Vec<Bookmark>, Error> {
+    let endpoint = format!("/api/v1/bookmarks/{id}/related");
+    let response = Request::get(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    log::info!("Api get related bookmarks, id={id}");
+    match response.status() {
+        200 => Ok(response.json::<RelatedBookmarks>().await?.bookmarks),
+        404 => Ok(Vec::new()),
+        _ => {
+            let response_body = response.text().await?;
+            log::warn!(
+                "Api get related bookmarks of id={id}, error = unexpected response, status={status}, response={response_body}",
+                status = response.status(),
+            );
+            Err(Error::GlooError("unexpected response".to_owned()))
+        }
+    }
+}
+
 pub async fn set_tags(token: &str, id: &str, tags: Vec<String>) -> Result<Bookmark, Error> {
     let endpoint = format!("/api/v1/bookmarks/{id}/tags");
     let payload = Tags { tags };
@@ -125,3 +223,141 @@ pub async fn get_content(token: &str, id: &str) -> Result<Option<String>, Error>
         }
     }
 }
+
+pub async fn set_note(token: &str, id: &str, note: Option<String>) -> Result<Bookmark, Error> {
+    let endpoint = format!("/api/v1/bookmarks/{id}/note");
+    let request_body = serde_json::to_string(&Note { note }).expect("Serialize should not fail");
+    let response = Request::post(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(request_body)?
+        .send()
+        .await?
+        .json::<Bookmark>()
+        .await?;
+    log::info!("Api set note to bookmark={id}");
+    Ok(response)
+}
+
+pub async fn update_reading_state(
+    token: &str,
+    id: &str,
+    state: &ReadingState,
+) -> Result<Bookmark, Error> {
+    let endpoint = format!("/api/v1/bookmarks/{id}");
+    let request_body = serde_json::to_string(state).expect("Serialize should not fail");
+    let response = Request::patch(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(request_body)?
+        .send()
+        .await?
+        .json::<Bookmark>()
+        .await?;
+    log::info!("Api update reading state of bookmark={id}");
+    Ok(response)
+}
+
+pub async fn add_highlight(
+    token: &str,
+    id: &str,
+    highlight: &NewHighlight,
+) -> Result<Highlight, Error> {
+    let endpoint = format!("/api/v1/bookmarks/{id}/highlights");
+    let request_body = serde_json::to_string(highlight).expect("Serialize should not fail");
+    let response = Request::post(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(request_body)?
+        .send()
+        .await?
+        .json::<Highlight>()
+        .await?;
+    log::info!("Api add highlight to bookmark={id}");
+    Ok(response)
+}
+
+pub async fn set_highlight_note(
+    token: &str,
+    id: &str,
+    highlight_id: Uuid,
+    note: Option<String>,
+) -> Result<Highlight, Error> {
+    let endpoint = format!("/api/v1/bookmarks/{id}/highlights/{highlight_id}");
+    let request_body = serde_json::to_string(&Note { note }).expect("Serialize should not fail");
+    let response = Request::patch(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(request_body)?
+        .send()
+        .await?
+        .json::<Highlight>()
+        .await?;
+    log::info!("Api set note to highlight={highlight_id}");
+    Ok(response)
+}
+
+pub async fn delete_highlight(token: &str, id: &str, highlight_id: Uuid) -> Result<(), Error> {
+    let endpoint = format!("/api/v1/bookmarks/{id}/highlights/{highlight_id}");
+    let response = Request::delete(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    log::info!("Api delete highlight={highlight_id}");
+    match response.status() {
+        204 => Ok(()),
+        status => Err(Error::GlooError(format!(
+            "unexpected response, status={status}"
+        ))),
+    }
+}
+
+/// The pending tag suggestions of the bookmark, most confident first.
+pub async fn get_suggestions(token: &str, id: &str) -> Result<Vec<TagSuggestion>, Error> {
+    let endpoint = format!("/api/v1/bookmarks/{id}/suggestions");
+    let response = Request::get(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?
+        .json::<TagSuggestions>()
+        .await?;
+    log::info!("Api get tag suggestions, id={id}");
+    Ok(response.suggestions)
+}
+
+/// Adds the suggested tag to the bookmark.
+pub async fn accept_suggestion(token: &str, id: &str, tag: &str) -> Result<Bookmark, Error> {
+    let endpoint = suggestion_endpoint(id, tag, "accept");
+    let response = Request::post(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?
+        .json::<Bookmark>()
+        .await?;
+    log::info!("Api accept tag suggestion, id={id}, tag={tag}");
+    Ok(response)
+}
+
+pub async fn dismiss_suggestion(token: &str, id: &str, tag: &str) -> Result<(), Error> {
+    let endpoint = suggestion_endpoint(id, tag, "dismiss");
+    let response = Request::post(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    log::info!("Api dismiss tag suggestion, id={id}, tag={tag}");
+    match response.status() {
+        204 => Ok(()),
+        status => Err(Error::GlooError(format!(
+            "unexpected response, status={status}"
+        ))),
+    }
+}
+
+/// Tags may contain a `/`, they are escaped as a single path segment.
+fn suggestion_endpoint(id: &str, tag: &str, action: &str) -> String {
+    let mut url = url::Url::parse("http://localhost").expect("Valid base url");
+    url.path_segments_mut()
+        .expect("Base url has a path")
+        .extend(["api", "v1", "bookmarks", id, "suggestions", tag, action]);
+    url.path().to_owned()
+}
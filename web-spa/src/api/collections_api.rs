@@ -0,0 +1,178 @@
+use chrono::{DateTime, Utc};
+use gloo_net::http::{Request, Response};
+use gloo_net::Error;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::bookmarks_api::Bookmark;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub collection_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub item_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A collection with its bookmarks, in order.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct CollectionWithItems {
+    #[serde(flatten)]
+    pub collection: Collection,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct CollectionsResponse {
+    pub collections: Vec<Collection>,
+}
+
+#[derive(Debug, Serialize)]
+struct NewCollection<'a> {
+    name: &'a str,
+    description: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct NewItem<'a> {
+    bookmark_id: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct ItemsOrder<'a> {
+    bookmark_ids: &'a [String],
+}
+
+/// The body of a successful response, the error body otherwise.
+async fn expect_status<T: for<'de> Deserialize<'de>>(
+    response: Response,
+    status: u16,
+    action: &str,
+) -> Result<T, Error> {
+    if response.status() == status {
+        return response.json::<T>().await;
+    }
+    let response_body = response.text().await?;
+    log::warn!(
+        "Api {action}, error = unexpected response, status={status}, response={response_body}",
+        status = response.status(),
+    );
+    Err(Error::GlooError(response_body))
+}
+
+pub async fn get_collections(token: &str) -> Result<CollectionsResponse, Error> {
+    const ENDPOINT: &str = "/api/v1/collections";
+    let response = Request::get(ENDPOINT)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?
+        .json::<CollectionsResponse>()
+        .await?;
+    log::info!("Api get collections");
+    Ok(response)
+}
+
+pub async fn get_collection(token: &str, id: Uuid) -> Result<CollectionWithItems, Error> {
+    let endpoint = format!("/api/v1/collections/{id}");
+    let response = Request::get(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?
+        .json::<CollectionWithItems>()
+        .await?;
+    log::info!("Api get collection, id={id}");
+    Ok(response)
+}
+
+pub async fn create_collection(
+    token: &str,
+    name: &str,
+    description: Option<&str>,
+) -> Result<Collection, Error> {
+    const ENDPOINT: &str = "/api/v1/collections";
+    let request_body = serde_json::to_string(&NewCollection { name, description })
+        .expect("Serialize should not fail");
+    let response = Request::post(ENDPOINT)
+        .header("Authorization", &format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(request_body)?
+        .send()
+        .await?;
+    log::info!("Api create collection, name={name}");
+    expect_status(response, 201, "create collection").await
+}
+
+pub async fn delete_collection(token: &str, id: Uuid) -> Result<(), Error> {
+    let endpoint = format!("/api/v1/collections/{id}");
+    Request::delete(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    log::info!("Api delete collection, id={id}");
+    Ok(())
+}
+
+/// Adds the bookmark after the last item.
+pub async fn add_item(
+    token: &str,
+    id: Uuid,
+    bookmark_id: &str,
+) -> Result<CollectionWithItems, Error> {
+    let endpoint = format!("/api/v1/collections/{id}/items");
+    let request_body =
+        serde_json::to_string(&NewItem { bookmark_id }).expect("Serialize should not fail");
+    let response = Request::post(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(request_body)?
+        .send()
+        .await?;
+    log::info!("Api add bookmark={bookmark_id} to collection={id}");
+    expect_status(response, 201, "add to collection").await
+}
+
+/// `bookmark_ids` lists every item of the collection, in their new order.
+pub async fn reorder_items(
+    token: &str,
+    id: Uuid,
+    bookmark_ids: &[String],
+) -> Result<CollectionWithItems, Error> {
+    let endpoint = format!("/api/v1/collections/{id}/items");
+    let request_body =
+        serde_json::to_string(&ItemsOrder { bookmark_ids }).expect("Serialize should not fail");
+    let response = Request::put(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(request_body)?
+        .send()
+        .await?;
+    log::info!("Api reorder collection={id}");
+    expect_status(response, 200, "reorder collection").await
+}
+
+pub async fn remove_item(token: &str, id: Uuid, bookmark_id: &str) -> Result<(), Error> {
+    let endpoint = format!("/api/v1/collections/{id}/items/{bookmark_id}");
+    Request::delete(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    log::info!("Api remove bookmark={bookmark_id} from collection={id}");
+    Ok(())
+}
+
+/// The zip archive of the collection.
+pub async fn export_collection(token: &str, id: Uuid) -> Result<Vec<u8>, Error> {
+    let endpoint = format!("/api/v1/export/collections/{id}");
+    let response = Request::get(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    log::info!("Api export collection, id={id}");
+    if response.ok() {
+        Ok(response.binary().await?)
+    } else {
+        Err(Error::GlooError(response.text().await?))
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use gloo_net::http::Request;
+use gloo_net::Error;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+#[derive(Debug, PartialEq, Default, Clone, Copy, Display, EnumString, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ImportFormat {
+    #[default]
+    Netscape,
+    Pocket,
+    Pinboard,
+    Csv,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ImportParseError {
+    pub entry: String,
+    pub reason: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ImportProgress {
+    pub import_id: Uuid,
+    pub format: ImportFormat,
+    pub total: i32,
+    pub parse_errors: Vec<ImportParseError>,
+    pub created_at: DateTime<Utc>,
+    pub pending: i64,
+    pub done: i64,
+    pub failed: i64,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ImportJob {
+    pub import_id: Uuid,
+    pub format: ImportFormat,
+    pub total: i32,
+    pub parse_errors: Vec<ImportParseError>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImportsResponse {
+    pub imports: Vec<ImportProgress>,
+}
+
+pub async fn import_file(
+    token: &str,
+    format: ImportFormat,
+    content: String,
+) -> Result<ImportJob, Error> {
+    let endpoint = format!("/api/v1/imports?format={format}");
+    let response = Request::post(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .header("Content-Type", "text/plain")
+        .body(content)?
+        .send()
+        .await?;
+    log::info!("Api import file, format={format}");
+    match response.status() {
+        202 => Ok(response.json::<ImportJob>().await?),
+        _ => {
+            let response_body = response.text().await?;
+            log::warn!(
+                "Api import file, error = unexpected response, status={status}, response={response_body}",
+                status = response.status(),
+            );
+            Err(Error::GlooError(response_body))
+        }
+    }
+}
+
+pub async fn get_imports(token: &str) -> Result<ImportsResponse, Error> {
+    const ENDPOINT: &str = "/api/v1/imports";
+    let response = Request::get(ENDPOINT)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?
+        .json::<ImportsResponse>()
+        .await?;
+    log::info!("Api get imports");
+    Ok(response)
+}
@@ -1,4 +1,7 @@
 pub mod auth_api;
 pub mod bookmarks_api;
+pub mod collections_api;
+pub mod imports_api;
+pub mod saved_searches_api;
 pub mod search_api;
 pub mod tags_api;
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use gloo_net::http::Request;
+use gloo_net::Error;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::search_api::{SearchRequest, SearchResponse};
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub saved_search_id: Uuid,
+    pub name: String,
+    pub search: SearchRequest,
+    pub last_seen_at: DateTime<Utc>,
+    /// Bookmarks matching since the results were last seen, `None` when they
+    /// couldn't be counted.
+    #[serde(default)]
+    pub new_matches: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearchesResponse {
+    pub saved_searches: Vec<SavedSearch>,
+}
+
+#[derive(Debug, Serialize)]
+struct NewSavedSearch<'a> {
+    name: &'a str,
+    search: &'a SearchRequest,
+}
+
+pub async fn get_saved_searches(token: &str) -> Result<SavedSearchesResponse, Error> {
+    const ENDPOINT: &str = "/api/v1/saved-searches";
+    let response = Request::get(ENDPOINT)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?
+        .json::<SavedSearchesResponse>()
+        .await?;
+    log::info!("Api get saved searches");
+    Ok(response)
+}
+
+pub async fn create_saved_search(
+    token: &str,
+    name: &str,
+    search: &SearchRequest,
+) -> Result<SavedSearch, Error> {
+    const ENDPOINT: &str = "/api/v1/saved-searches";
+    let request_body =
+        serde_json::to_string(&NewSavedSearch { name, search }).expect("Serialize should not fail");
+    let response = Request::post(ENDPOINT)
+        .header("Authorization", &format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(request_body)?
+        .send()
+        .await?;
+    log::info!("Api create saved search, name={name}");
+    match response.status() {
+        201 => Ok(response.json::<SavedSearch>().await?),
+        _ => {
+            let response_body = response.text().await?;
+            log::warn!(
+                "Api create saved search, error = unexpected response, status={status}, response={response_body}",
+                status = response.status(),
+            );
+            Err(Error::GlooError(response_body))
+        }
+    }
+}
+
+pub async fn delete_saved_search(token: &str, id: Uuid) -> Result<(), Error> {
+    let endpoint = format!("/api/v1/saved-searches/{id}");
+    Request::delete(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    log::info!("Api delete saved search, id={id}");
+    Ok(())
+}
+
+/// Runs the saved search, the first page marks its matches as seen.
+pub async fn get_results(
+    token: &str,
+    id: Uuid,
+    cursor: Option<&str>,
+) -> Result<SearchResponse, Error> {
+    let mut endpoint = format!("/api/v1/saved-searches/{id}/results");
+    if let Some(cursor) = cursor {
+        endpoint.push_str(&format!("?cursor={cursor}"));
+    }
+    let response = Request::get(&endpoint)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?
+        .json::<SearchResponse>()
+        .await?;
+    log::info!("Api get saved search results, id={id}");
+    Ok(response)
+}
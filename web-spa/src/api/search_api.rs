@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use gloo_net::http::Request;
 use gloo_net::Error;
@@ -5,7 +7,7 @@ use serde::{Deserialize, Serialize};
 use strum_macros::EnumString;
 use uuid::Uuid;
 
-use super::tags_api::Tag;
+use super::tags_api::TagNode;
 
 #[derive(Debug, PartialEq, Default, Clone, EnumString, Serialize, Deserialize)]
 pub enum SearchType {
@@ -27,40 +29,155 @@ pub enum TagFilterType {
     And,
 }
 
+/// A range of characters of a snippet, `end` excluded.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The `occurrence`-th case insensitive occurrence of `term` in the page.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Anchor {
+    pub term: String,
+    pub occurrence: usize,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Snippet {
+    pub text: String,
+    pub highlights: Vec<Highlight>,
+    pub position: Option<usize>,
+    pub anchor: Option<Anchor>,
+}
+
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct SearchResultItem {
     pub bookmark_id: String,
     pub url: String,
     pub domain: String,
     pub title: String,
-    pub search_match: Option<String>,
+    #[serde(default)]
+    pub snippets: Vec<Snippet>,
     pub links: Option<Vec<String>>,
     pub created_at: DateTime<Utc>,
     pub user_id: Option<Uuid>,
     pub tags: Option<Vec<String>>,
+    /// In minutes.
+    #[serde(default)]
+    pub reading_time: i32,
+    #[serde(default)]
+    pub word_count: i32,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub read_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub progress: f32,
     pub user_created_at: Option<DateTime<Utc>>,
     pub user_updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum TagFilter {
     And(Vec<String>),
     Or(Vec<String>),
     Any,
     Untagged,
+    SavedSearch(Uuid),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sort {
+    #[default]
+    Relevance,
+    Saved,
+    Updated,
+    Title,
+    Domain,
+    ReadingTime,
+    WordCount,
+    ExtractionRatio,
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    #[default]
+    FullText,
+    Fuzzy,
+    Semantic,
+    Hybrid,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Facet {
+    Domain,
+    Month,
+    Scheme,
+    Tld,
+    State,
+}
+
+impl Facet {
+    pub const ALL: [Facet; 5] = [
+        Facet::Domain,
+        Facet::Month,
+        Facet::Scheme,
+        Facet::Tld,
+        Facet::State,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Facet::Domain => "Domain",
+            Facet::Month => "Month",
+            Facet::Scheme => "Scheme",
+            Facet::Tld => "TLD",
+            Facet::State => "State",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct SearchRequest {
     pub query: Option<String>,
     pub tags_filter: Option<TagFilter>,
+    pub facet_filters: Option<BTreeMap<Facet, Vec<String>>>,
     pub limit: Option<i32>,
+    pub sort: Option<Sort>,
+    pub cursor: Option<String>,
+    pub mode: Option<SearchMode>,
+    pub read: Option<bool>,
+    pub favorite: Option<bool>,
+    pub archived: Option<bool>,
+    pub min_reading_time: Option<i32>,
+    pub max_reading_time: Option<i32>,
+    pub min_word_count: Option<i32>,
+    pub max_word_count: Option<i32>,
+    pub min_extraction_ratio: Option<f32>,
+    pub max_extraction_ratio: Option<f32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResponse {
     pub bookmarks: Vec<SearchResultItem>,
-    pub tags: Vec<Tag>,
+    pub tags: Vec<TagNode>,
+    #[serde(default)]
+    pub facets: BTreeMap<Facet, Vec<FacetCount>>,
+    pub total: u64,
+    pub next_cursor: Option<String>,
+    pub did_you_mean: Option<String>,
 }
 
 pub async fn search(token: &String, request: SearchRequest) -> Result<SearchResponse, Error> {
@@ -8,6 +8,25 @@ pub struct Tag {
     pub count: u32,
 }
 
+/// A level of the tag hierarchy, `count` includes the descendants.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TagNode {
+    pub tag: String,
+    pub name: String,
+    pub count: u32,
+    #[serde(default)]
+    pub children: Vec<TagNode>,
+}
+
+impl TagNode {
+    /// The node followed by its descendants, depth first.
+    pub fn flatten(&self) -> Vec<&TagNode> {
+        let mut nodes = vec![self];
+        nodes.extend(self.children.iter().flat_map(TagNode::flatten));
+        nodes
+    }
+}
+
 #[derive(PartialEq, Serialize, Deserialize)]
 pub struct TagsResponse {
     pub tags: Vec<Tag>,
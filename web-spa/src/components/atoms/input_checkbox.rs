@@ -14,6 +14,8 @@ pub struct Props {
     pub value: String,
     pub on_change: Callback<ItemCheckEvent>,
     #[prop_or_default]
+    pub checked: bool,
+    #[prop_or_default]
     pub class: Classes,
 }
 
@@ -39,6 +41,7 @@ pub fn input_checkbox(props: &Props) -> Html {
             name={props.name.clone()}
             class={props.class.clone()}
             type="checkbox"
+            checked={props.checked}
             onchange={on_change} />
     }
 }
@@ -1,11 +1,20 @@
 use crate::{
-    api::{self, bookmarks_api::Bookmark},
+    api::{
+        self,
+        bookmarks_api::{Bookmark, Highlight, NewHighlight, ReadingState, TagSuggestion},
+        collections_api::Collection,
+        search_api::Anchor,
+    },
     components::atoms::{
         input_text::{InputText, InputType},
         safe_html::ArticleHtml,
     },
+    components::composite::highlight_list::HighlightList,
     user_session::UserSession,
 };
+use uuid::Uuid;
+use wasm_bindgen::JsCast;
+use web_sys::{Document, Element, HtmlSelectElement, HtmlTextAreaElement, Node};
 use yew::platform::spawn_local;
 use yew::prelude::*;
 
@@ -13,8 +22,17 @@ use yew::prelude::*;
 pub struct Props {
     pub user_session: UserSession,
     pub bookmark: Bookmark,
+    /// The match to scroll to once the content is loaded.
+    #[prop_or_default]
+    pub anchor: Option<Anchor>,
     pub on_goback: Callback<()>,
     pub on_new_tags: Callback<Vec<String>>,
+    pub on_new_note: Callback<Option<String>>,
+    /// The bookmark with a suggested tag accepted.
+    pub on_suggestion_accepted: Callback<Bookmark>,
+    /// The bookmark marked read, favorite or archived.
+    pub on_reading_state_changed: Callback<Bookmark>,
+    pub on_related_selected: Callback<Bookmark>,
 }
 
 #[function_component(BookmarkReader)]
@@ -22,7 +40,126 @@ pub fn bookmark_page(props: &Props) -> Html {
     let token = props.user_session.token.clone();
     let state = use_state_eq(|| props.bookmark.tags.clone().unwrap_or_default());
     let tags_as_string = state.clone().join(", ");
+    let note = use_state_eq(|| props.bookmark.note.clone().unwrap_or_default());
     let html_content = use_state(|| None);
+    let article_ref = use_node_ref();
+    {
+        let article_ref = article_ref.clone();
+        let anchor = props.anchor.clone();
+        let progress = props.bookmark.progress;
+        use_effect_with_deps(
+            move |loaded| {
+                match (*loaded, anchor, article_root(&article_ref)) {
+                    (true, Some(anchor), Some(root)) => {
+                        let found = scroll_to(&root, &anchor).is_some();
+                        if !found {
+                            log::info!("Match not found in the page, anchor={anchor:?}");
+                        }
+                    }
+                    // Resumes the reading where it stopped.
+                    (true, None, Some(root)) if progress > 0.0 => resume(&root, progress),
+                    _ => {}
+                }
+                || ()
+            },
+            html_content.is_some(),
+        );
+    }
+    let related = use_state(Vec::<Bookmark>::new);
+    {
+        let related = related.clone();
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match api::bookmarks_api::get_related(&token, &bookmark_id).await {
+                        Ok(bookmarks) => related.set(bookmarks),
+                        Err(error) => {
+                            log::error!(
+                                "Fail to fetch related bookmarks of {bookmark_id}, error={error}"
+                            )
+                        }
+                    }
+                });
+                || ()
+            },
+            (),
+        );
+    }
+    let highlights = use_state(Vec::<Highlight>::new);
+    {
+        let highlights = highlights.clone();
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match api::bookmarks_api::get_by_id(&token, &bookmark_id).await {
+                        Ok(Some(bookmark)) => highlights.set(bookmark.highlights),
+                        Ok(None) => {}
+                        Err(error) => {
+                            log::error!("Fail to fetch highlights of {bookmark_id}, error={error}")
+                        }
+                    }
+                });
+                || ()
+            },
+            (),
+        );
+    }
+    {
+        let article_ref = article_ref.clone();
+        use_effect_with_deps(
+            move |(loaded, highlights)| {
+                if let (true, Some(root)) = (*loaded, article_root(&article_ref)) {
+                    apply_highlights(&root, highlights);
+                }
+                || ()
+            },
+            (html_content.is_some(), (*highlights).clone()),
+        );
+    }
+    let collections = use_state(Vec::<Collection>::new);
+    let collection_added = use_state(|| None::<String>);
+    {
+        let collections = collections.clone();
+        let token = token.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match api::collections_api::get_collections(&token).await {
+                        Ok(response) => collections.set(response.collections),
+                        Err(error) => log::error!("Fail to fetch collections, error={error}"),
+                    }
+                });
+                || ()
+            },
+            (),
+        );
+    }
+    let suggestions = use_state(Vec::<TagSuggestion>::new);
+    {
+        let suggestions = suggestions.clone();
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match api::bookmarks_api::get_suggestions(&token, &bookmark_id).await {
+                        Ok(result) => suggestions.set(result),
+                        Err(error) => {
+                            log::error!(
+                                "Fail to fetch tag suggestions of {bookmark_id}, error={error}"
+                            )
+                        }
+                    }
+                });
+                || ()
+            },
+            (),
+        );
+    }
     {
         let html_contentt = html_content.clone();
         let token = token.clone();
@@ -52,12 +189,223 @@ pub fn bookmark_page(props: &Props) -> Html {
 
     let on_goback = {
         let callback = props.on_goback.clone();
+        let article_ref = article_ref.clone();
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
         Callback::from(move |event: MouseEvent| {
             event.prevent_default();
+            if let Some(progress) = article_root(&article_ref).and_then(|root| progress(&root)) {
+                let token = token.clone();
+                let bookmark_id = bookmark_id.clone();
+                spawn_local(async move {
+                    let state = ReadingState {
+                        progress: Some(progress),
+                        ..ReadingState::default()
+                    };
+                    if let Err(error) =
+                        api::bookmarks_api::update_reading_state(&token, &bookmark_id, &state).await
+                    {
+                        log::warn!("Fail to save progress of {bookmark_id}, error={error}");
+                    }
+                });
+            }
             callback.emit(());
         })
     };
 
+    let on_reading_state = {
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
+        let callback = props.on_reading_state_changed.clone();
+        Callback::from(move |state: ReadingState| {
+            let token = token.clone();
+            let bookmark_id = bookmark_id.clone();
+            let callback = callback.clone();
+            spawn_local(async move {
+                match api::bookmarks_api::update_reading_state(&token, &bookmark_id, &state).await {
+                    Ok(bookmark) => callback.emit(bookmark),
+                    Err(error) => {
+                        log::error!("Fail to update bookmark={bookmark_id}, error={error}")
+                    }
+                }
+            });
+        })
+    };
+    let toggle = |label: &'static str, active: bool, change: ReadingState| {
+        let on_reading_state = on_reading_state.clone();
+        let onclick = Callback::from(move |_: MouseEvent| on_reading_state.emit(change.clone()));
+        let class = if active {
+            "btn btn-sm btn-secondary"
+        } else {
+            "btn btn-sm btn-outline-secondary"
+        };
+        html! { <button {class} type="button" {onclick}>{label}</button> }
+    };
+    let bookmark = &props.bookmark;
+    let reading_toggles = html! {
+        <div class="btn-group" role="group">
+            {toggle("Read", bookmark.read_at.is_some(), ReadingState {
+                read: Some(bookmark.read_at.is_none()),
+                ..ReadingState::default()
+            })}
+            {toggle("Favorite", bookmark.favorite, ReadingState {
+                favorite: Some(!bookmark.favorite),
+                ..ReadingState::default()
+            })}
+            {toggle("Archived", bookmark.archived, ReadingState {
+                archived: Some(!bookmark.archived),
+                ..ReadingState::default()
+            })}
+        </div>
+    };
+
+    let on_suggestion = {
+        let suggestions = suggestions.clone();
+        let state = state.clone();
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
+        let callback = props.on_suggestion_accepted.clone();
+        Callback::from(move |(tag, accept): (String, bool)| {
+            let suggestions = suggestions.clone();
+            let state = state.clone();
+            let token = token.clone();
+            let bookmark_id = bookmark_id.clone();
+            let callback = callback.clone();
+            spawn_local(async move {
+                let result = if accept {
+                    api::bookmarks_api::accept_suggestion(&token, &bookmark_id, &tag)
+                        .await
+                        .map(|bookmark| {
+                            state.set(bookmark.tags.clone().unwrap_or_default());
+                            callback.emit(bookmark);
+                        })
+                } else {
+                    api::bookmarks_api::dismiss_suggestion(&token, &bookmark_id, &tag).await
+                };
+                match result {
+                    Ok(()) => suggestions.set(
+                        suggestions
+                            .iter()
+                            .filter(|s| s.tag != tag)
+                            .cloned()
+                            .collect(),
+                    ),
+                    Err(error) => {
+                        log::error!(
+                            "Fail to resolve suggestion {tag} of {bookmark_id}, error={error}"
+                        )
+                    }
+                }
+            });
+        })
+    };
+
+    // On mouse down, before the click clears the selection.
+    let on_highlight = {
+        let highlights = highlights.clone();
+        let article_ref = article_ref.clone();
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
+        Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            let Some(new_highlight) = article_root(&article_ref).and_then(|r| selected(&r)) else {
+                return;
+            };
+            let highlights = highlights.clone();
+            let token = token.clone();
+            let bookmark_id = bookmark_id.clone();
+            spawn_local(async move {
+                match api::bookmarks_api::add_highlight(&token, &bookmark_id, &new_highlight).await
+                {
+                    Ok(highlight) => {
+                        let mut list = (*highlights).clone();
+                        list.push(highlight);
+                        list.sort_by_key(|h| h.start_offset);
+                        highlights.set(list);
+                    }
+                    Err(error) => {
+                        log::error!("Fail to add highlight to {bookmark_id}, error={error}")
+                    }
+                }
+            });
+        })
+    };
+
+    let on_highlight_note = {
+        let highlights = highlights.clone();
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
+        Callback::from(move |(highlight_id, note): (Uuid, Option<String>)| {
+            let highlights = highlights.clone();
+            let token = token.clone();
+            let bookmark_id = bookmark_id.clone();
+            spawn_local(async move {
+                match api::bookmarks_api::set_highlight_note(
+                    &token,
+                    &bookmark_id,
+                    highlight_id,
+                    note,
+                )
+                .await
+                {
+                    Ok(updated) => highlights.set(
+                        highlights
+                            .iter()
+                            .map(|h| {
+                                if h.highlight_id == highlight_id {
+                                    updated.clone()
+                                } else {
+                                    h.clone()
+                                }
+                            })
+                            .collect(),
+                    ),
+                    Err(error) => {
+                        log::error!("Fail to set note to highlight={highlight_id}, error={error}")
+                    }
+                }
+            });
+        })
+    };
+
+    let on_highlight_delete = {
+        let highlights = highlights.clone();
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
+        Callback::from(move |highlight_id: Uuid| {
+            let highlights = highlights.clone();
+            let token = token.clone();
+            let bookmark_id = bookmark_id.clone();
+            spawn_local(async move {
+                match api::bookmarks_api::delete_highlight(&token, &bookmark_id, highlight_id).await
+                {
+                    Ok(()) => highlights.set(
+                        highlights
+                            .iter()
+                            .filter(|h| h.highlight_id != highlight_id)
+                            .cloned()
+                            .collect(),
+                    ),
+                    Err(error) => {
+                        log::error!("Fail to delete highlight={highlight_id}, error={error}")
+                    }
+                }
+            });
+        })
+    };
+
+    let on_highlight_selected = {
+        let article_ref = article_ref.clone();
+        Callback::from(move |highlight_id: Uuid| {
+            let selector = format!("mark[data-highlight-id=\"{highlight_id}\"]");
+            if let Some(mark) = article_root(&article_ref)
+                .and_then(|root| root.query_selector(&selector).ok().flatten())
+            {
+                mark.scroll_into_view_with_bool(true);
+            }
+        })
+    };
+
     let on_save_tags = {
         let callback = props.on_new_tags.clone();
         Callback::from(move |_: MouseEvent| {
@@ -65,6 +413,72 @@ pub fn bookmark_page(props: &Props) -> Html {
         })
     };
 
+    let on_note_change = {
+        let note = note.clone();
+        Callback::from(move |event: Event| {
+            let target = event.target().expect("Fail to get event target");
+            note.set(target.unchecked_into::<HtmlTextAreaElement>().value());
+        })
+    };
+
+    let on_save_note = {
+        let callback = props.on_new_note.clone();
+        let note = note.clone();
+        Callback::from(move |_: MouseEvent| {
+            let text = note.trim();
+            callback.emit((!text.is_empty()).then(|| text.to_owned()));
+        })
+    };
+
+    let on_add_to_collection = {
+        let token = token.clone();
+        let bookmark_id = props.bookmark.bookmark_id.clone();
+        let collection_added = collection_added.clone();
+        Callback::from(move |event: Event| {
+            let target = event.target().expect("Fail to get event target");
+            let select = target.unchecked_into::<HtmlSelectElement>();
+            let Ok(collection_id) = Uuid::parse_str(&select.value()) else {
+                return;
+            };
+            select.set_value("");
+            let token = token.clone();
+            let bookmark_id = bookmark_id.clone();
+            let collection_added = collection_added.clone();
+            spawn_local(async move {
+                match api::collections_api::add_item(&token, collection_id, &bookmark_id).await {
+                    Ok(collection) => collection_added.set(Some(collection.collection.name)),
+                    Err(error) => {
+                        log::error!("Fail to add {bookmark_id} to collection={collection_id}, error={error}")
+                    }
+                }
+            });
+        })
+    };
+
+    let collection_picker = if collections.is_empty() {
+        html! {}
+    } else {
+        let options = collections
+            .iter()
+            .map(|collection| {
+                html! {
+                    <option value={collection.collection_id.to_string()}>{collection.name.clone()}</option>
+                }
+            })
+            .collect::<Html>();
+        html! {
+            <div class="d-flex align-items-center mt-3">
+                <select class="form-select form-select-sm w-auto" onchange={on_add_to_collection}>
+                    <option value="" selected=true>{"Add to collection…"}</option>
+                    {options}
+                </select>
+                if let Some(name) = (*collection_added).clone() {
+                    <small class="text-muted ms-2">{format!("Added to {name}")}</small>
+                }
+            </div>
+        }
+    };
+
     let article = if let Some(data) = (*html_content).clone() {
         html! {
             <ArticleHtml html={data} />
@@ -73,10 +487,77 @@ pub fn bookmark_page(props: &Props) -> Html {
         html! { "Loading..." }
     };
 
+    let suggestions_panel = if suggestions.is_empty() {
+        html! {}
+    } else {
+        let items = suggestions.iter().map(|suggestion| {
+            let on_accept = {
+                let on_suggestion = on_suggestion.clone();
+                let tag = suggestion.tag.clone();
+                Callback::from(move |_: MouseEvent| on_suggestion.emit((tag.clone(), true)))
+            };
+            let on_dismiss = {
+                let on_suggestion = on_suggestion.clone();
+                let tag = suggestion.tag.clone();
+                Callback::from(move |_: MouseEvent| on_suggestion.emit((tag.clone(), false)))
+            };
+            let title = format!(
+                "{:.0}% from {}",
+                suggestion.confidence * 100.0,
+                suggestion.sources.join(", ")
+            );
+            html! {
+                <span class="btn-group btn-group-sm me-2 mb-2" {title}>
+                    <button class="btn btn-outline-primary" type="button" onclick={on_accept}>
+                        {"+ "}{suggestion.tag.clone()}
+                    </button>
+                    <button class="btn btn-outline-secondary" type="button" onclick={on_dismiss}>
+                        {"×"}
+                    </button>
+                </span>
+            }
+        });
+        html! {
+            <div class="mt-2">
+                <small class="text-muted d-block mb-1">{"Suggested tags"}</small>
+                { for items }
+            </div>
+        }
+    };
+
+    let related_panel = if related.is_empty() {
+        html! {}
+    } else {
+        let items = related.iter().cloned().map(|bookmark| {
+            let callback = props.on_related_selected.clone();
+            let title = bookmark.title.clone();
+            let domain = bookmark.domain.clone();
+            let onclick = Callback::from(move |event: MouseEvent| {
+                event.prevent_default();
+                callback.emit(bookmark.clone());
+            });
+            html! {
+                <a href="#" class="list-group-item list-group-item-action" {onclick}>
+                    {title}
+                    <small class="text-muted ms-2">{domain}</small>
+                </a>
+            }
+        });
+        html! {
+            <div class="card mt-4">
+                <div class="card-header">{"Related"}</div>
+                <div class="list-group list-group-flush">
+                    { for items }
+                </div>
+            </div>
+        }
+    };
+
     html! {
       <div class="container mt-5">
-          <div class="mb-3">
+          <div class="mb-3 d-flex justify-content-between">
               <a href="#" class="btn btn-secondary" onclick={on_goback}>{"< Back to Home"}</a>
+              {reading_toggles}
           </div>
           <div class="card">
               <div class="card-body">
@@ -84,6 +565,11 @@ pub fn bookmark_page(props: &Props) -> Html {
                       <div class="d-flex justify-content-between align-items-center">
                           <p class="mb-0"><strong>{"ID:"}</strong> {props.bookmark.bookmark_id.clone()}</p>
                           <p class="mb-0"><strong>{"Created at:"}</strong> {props.bookmark.user_created_at}</p>
+                          if props.bookmark.word_count > 0 {
+                              <p class="mb-0 text-muted">
+                                  {format!("{} words, {} min read", props.bookmark.word_count, props.bookmark.reading_time)}
+                              </p>
+                          }
                       </div>
                       <p class="mb-2">
                           <strong>{"Original URL:"}</strong>
@@ -103,14 +589,270 @@ pub fn bookmark_page(props: &Props) -> Html {
                               value={tags_as_string} />
                           <button onclick={on_save_tags} class="btn btn-primary" type="button">{"Save"}</button>
                       </div>
+                      {suggestions_panel}
+                      <div class="mt-3">
+                          <label for="note" class="form-label">{"Note:"}</label>
+                          <textarea
+                              id="note"
+                              name="note"
+                              class="form-control"
+                              rows="3"
+                              placeholder="Private note, searchable"
+                              value={(*note).clone()}
+                              onchange={on_note_change} />
+                          <button onclick={on_save_note} class="btn btn-primary mt-2" type="button">{"Save note"}</button>
+                      </div>
+                      {collection_picker}
                   </div>
               </div>
           </div>
-          <br/>
-          <div class="article-content">
+          <div class="d-flex justify-content-end mt-3">
+              <button onmousedown={on_highlight} class="btn btn-sm btn-outline-warning" type="button">
+                  {"Highlight selection"}
+              </button>
+          </div>
+          <div class="article-content" ref={article_ref}>
               <h1 class="mb-4">{ props.bookmark.title.clone() }</h1>
               {article}
           </div>
+          <HighlightList
+              highlights={(*highlights).clone()}
+              on_note={on_highlight_note}
+              on_delete={on_highlight_delete}
+              on_selected={on_highlight_selected} />
+          {related_panel}
       </div>
     }
 }
+
+/// How far the page of `root` is scrolled, from 0 to 1.
+fn progress(root: &Element) -> Option<f32> {
+    let page = root.owner_document()?.document_element()?;
+    let scrollable = page.scroll_height() - page.client_height();
+    if scrollable <= 0 {
+        return None;
+    }
+    Some((page.scroll_top() as f32 / scrollable as f32).clamp(0.0, 1.0))
+}
+
+fn resume(root: &Element, progress: f32) {
+    if let Some(page) = root.owner_document().and_then(|d| d.document_element()) {
+        let scrollable = page.scroll_height() - page.client_height();
+        page.set_scroll_top((scrollable as f32 * progress) as i32);
+    }
+}
+
+/// Context kept around a highlight to find it again if the page changes.
+const HIGHLIGHT_CONTEXT: usize = 32;
+
+/// Marks the `anchor.occurrence`-th occurrence of `anchor.term` in the text of
+/// `root` and scrolls to it.
+fn scroll_to(root: &Element, anchor: &Anchor) -> Option<()> {
+    const SHOW_TEXT: u32 = 4;
+    let document = root.owner_document()?;
+    let walker = document
+        .create_tree_walker_with_what_to_show(root, SHOW_TEXT)
+        .ok()?;
+    let term = anchor.term.to_lowercase();
+    let mut remaining = anchor.occurrence;
+    while let Ok(Some(node)) = walker.next_node() {
+        let text = node.text_content().unwrap_or_default().to_lowercase();
+        for (index, _) in text.match_indices(&term) {
+            if remaining > 0 {
+                remaining -= 1;
+                continue;
+            }
+            let start = text[..index].encode_utf16().count() as u32;
+            let end = start + term.encode_utf16().count() as u32;
+            return mark(&document, &node, start, end);
+        }
+    }
+    None
+}
+
+fn mark(document: &web_sys::Document, node: &Node, start: u32, end: u32) -> Option<()> {
+    let range = document.create_range().ok()?;
+    range.set_start(node, start).ok()?;
+    range.set_end(node, end).ok()?;
+    let mark = document.create_element("mark").ok()?;
+    range.surround_contents(&mark).ok()?;
+    mark.scroll_into_view_with_bool(true);
+    Some(())
+}
+
+/// The archived page, without the title above it.
+fn article_root(article_ref: &NodeRef) -> Option<Element> {
+    article_ref
+        .cast::<Element>()?
+        .query_selector("article")
+        .ok()
+        .flatten()
+}
+
+/// The text nodes of `root` in document order, with their offset in the text
+/// of `root`, in UTF-16 code units as the DOM counts them.
+fn text_nodes(root: &Element) -> Option<Vec<(Node, usize, Vec<u16>)>> {
+    const SHOW_TEXT: u32 = 4;
+    let walker = root
+        .owner_document()?
+        .create_tree_walker_with_what_to_show(root, SHOW_TEXT)
+        .ok()?;
+    let mut nodes = Vec::new();
+    let mut offset = 0;
+    while let Ok(Some(node)) = walker.next_node() {
+        let text: Vec<u16> = node
+            .text_content()
+            .unwrap_or_default()
+            .encode_utf16()
+            .collect();
+        let length = text.len();
+        nodes.push((node, offset, text));
+        offset += length;
+    }
+    Some(nodes)
+}
+
+/// The selection in `root` as a highlight to save.
+fn selected(root: &Element) -> Option<NewHighlight> {
+    let document = root.owner_document()?;
+    let range = document.get_selection().ok()??.get_range_at(0).ok()?;
+    if range.collapsed() || !root.contains(Some(&range.common_ancestor_container().ok()?)) {
+        return None;
+    }
+    let before = document.create_range().ok()?;
+    before.select_node_contents(root).ok()?;
+    before
+        .set_end(&range.start_container().ok()?, range.start_offset().ok()?)
+        .ok()?;
+    let start = String::from(before.to_string()).encode_utf16().count();
+    let end = start + String::from(range.to_string()).encode_utf16().count();
+    let text: Vec<u16> = text_nodes(root)?
+        .into_iter()
+        .flat_map(|(_, _, t)| t)
+        .collect();
+    let exact = String::from_utf16(text.get(start..end)?).ok()?;
+    if exact.trim().is_empty() {
+        return None;
+    }
+    document.get_selection().ok()??.remove_all_ranges().ok()?;
+    Some(NewHighlight {
+        exact,
+        prefix: String::from_utf16_lossy(&text[start.saturating_sub(HIGHLIGHT_CONTEXT)..start]),
+        suffix: String::from_utf16_lossy(&text[end..(end + HIGHLIGHT_CONTEXT).min(text.len())]),
+        start_offset: start as i32,
+        end_offset: end as i32,
+    })
+}
+
+/// Marks the highlights in `root`, replacing the marks of a previous call.
+fn apply_highlights(root: &Element, highlights: &[Highlight]) {
+    if let Ok(marks) = root.query_selector_all("mark.highlight") {
+        for index in 0..marks.length() {
+            if let Some(mark) = marks.item(index) {
+                unwrap_node(&mark);
+            }
+        }
+        root.normalize();
+    }
+    let Some(document) = root.owner_document() else {
+        return;
+    };
+    for highlight in highlights {
+        let Some(nodes) = text_nodes(root) else {
+            return;
+        };
+        let text: Vec<u16> = nodes
+            .iter()
+            .flat_map(|(_, _, t)| t.iter().copied())
+            .collect();
+        match locate(&text, highlight) {
+            Some((start, end)) => mark_highlight(&document, &nodes, start, end, highlight),
+            None => log::info!(
+                "Highlight not found in the page, highlight_id={}",
+                highlight.highlight_id
+            ),
+        }
+    }
+}
+
+/// Replaces `node` with its children.
+fn unwrap_node(node: &Node) {
+    let Some(parent) = node.parent_node() else {
+        return;
+    };
+    while let Some(child) = node.first_child() {
+        let _ = parent.insert_before(&child, Some(node));
+    }
+    let _ = parent.remove_child(node);
+}
+
+/// Where the highlight is in `text`: at its position while the quote is still
+/// there, or else at the occurrence of the quote with the most of its context
+/// around it, the closest to its position.
+fn locate(text: &[u16], highlight: &Highlight) -> Option<(usize, usize)> {
+    let exact: Vec<u16> = highlight.exact.encode_utf16().collect();
+    if exact.is_empty() || exact.len() > text.len() {
+        return None;
+    }
+    let start = highlight.start_offset.max(0) as usize;
+    if text.get(start..start + exact.len()) == Some(&exact[..]) {
+        return Some((start, start + exact.len()));
+    }
+    let prefix: Vec<u16> = highlight.prefix.encode_utf16().collect();
+    let suffix: Vec<u16> = highlight.suffix.encode_utf16().collect();
+    let context = |at: usize| {
+        let before = text[..at]
+            .iter()
+            .rev()
+            .zip(prefix.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let after = text[at + exact.len()..]
+            .iter()
+            .zip(suffix.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        before + after
+    };
+    (0..=text.len() - exact.len())
+        .filter(|&at| text[at..at + exact.len()] == exact[..])
+        .max_by_key(|&at| (context(at), usize::MAX - at.abs_diff(start)))
+        .map(|at| (at, at + exact.len()))
+}
+
+/// Wraps the text from `start` to `end` in marks, one per text node as a mark
+/// can't span elements.
+fn mark_highlight(
+    document: &Document,
+    nodes: &[(Node, usize, Vec<u16>)],
+    start: usize,
+    end: usize,
+    highlight: &Highlight,
+) {
+    for (node, offset, text) in nodes {
+        let (from, to) = (start.max(*offset), end.min(offset + text.len()));
+        if from >= to
+            || String::from_utf16_lossy(&text[from - offset..to - offset])
+                .trim()
+                .is_empty()
+        {
+            continue;
+        }
+        let wrap = || -> Option<()> {
+            let range = document.create_range().ok()?;
+            range.set_start(node, (from - offset) as u32).ok()?;
+            range.set_end(node, (to - offset) as u32).ok()?;
+            let mark = document.create_element("mark").ok()?;
+            mark.set_class_name("highlight");
+            mark.set_attribute("data-highlight-id", &highlight.highlight_id.to_string())
+                .ok()?;
+            if let Some(note) = &highlight.note {
+                mark.set_attribute("title", note).ok()?;
+            }
+            range.surround_contents(&mark).ok()
+        };
+        if wrap().is_none() {
+            log::info!("Fail to mark highlight_id={}", highlight.highlight_id);
+        }
+    }
+}
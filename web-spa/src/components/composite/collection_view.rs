@@ -0,0 +1,229 @@
+use gloo_file::{Blob, ObjectUrl};
+use uuid::Uuid;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::{
+    api::{
+        bookmarks_api::Bookmark,
+        collections_api::{self, CollectionWithItems},
+    },
+    user_session::UserSession,
+};
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub user_session: UserSession,
+    pub collection_id: Uuid,
+    pub on_bookmark_selected: Callback<Bookmark>,
+    /// The items or the collection itself changed.
+    pub on_changed: Callback<()>,
+    pub on_deleted: Callback<()>,
+}
+
+/// The items of a collection in their order, to reorder, remove and export.
+#[function_component(CollectionView)]
+pub fn collection_view(props: &Props) -> Html {
+    let token = props.user_session.token.clone();
+    let id = props.collection_id;
+    let collection = use_state(|| None::<CollectionWithItems>);
+    let root_ref = use_node_ref();
+    // Kept alive until the next export, revoking it cancels the download.
+    let download = use_mut_ref(|| None::<ObjectUrl>);
+
+    {
+        let token = token.clone();
+        let collection = collection.clone();
+        use_effect_with_deps(
+            move |id| {
+                let id = *id;
+                spawn_local(async move {
+                    match collections_api::get_collection(&token, id).await {
+                        Ok(result) => collection.set(Some(result)),
+                        Err(error) => log::warn!("Fail to fetch collection={id}, error: {error}"),
+                    }
+                });
+                || ()
+            },
+            id,
+        );
+    }
+
+    let Some(current) = (*collection).clone() else {
+        return html! { "Loading..." };
+    };
+
+    let reorder = {
+        let token = token.clone();
+        let collection = collection.clone();
+        let bookmark_ids: Vec<String> = current
+            .bookmarks
+            .iter()
+            .map(|bookmark| bookmark.bookmark_id.clone())
+            .collect();
+        move |from: usize, to: usize| {
+            let token = token.clone();
+            let collection = collection.clone();
+            let mut bookmark_ids = bookmark_ids.clone();
+            bookmark_ids.swap(from, to);
+            Callback::from(move |_: MouseEvent| {
+                let token = token.clone();
+                let collection = collection.clone();
+                let bookmark_ids = bookmark_ids.clone();
+                spawn_local(async move {
+                    match collections_api::reorder_items(&token, id, &bookmark_ids).await {
+                        Ok(result) => collection.set(Some(result)),
+                        Err(error) => log::warn!("Fail to reorder collection={id}, error: {error}"),
+                    }
+                });
+            })
+        }
+    };
+
+    let on_remove = {
+        let token = token.clone();
+        let collection = collection.clone();
+        let on_changed = props.on_changed.clone();
+        Callback::from(move |bookmark_id: String| {
+            let token = token.clone();
+            let collection = collection.clone();
+            let on_changed = on_changed.clone();
+            spawn_local(async move {
+                match collections_api::remove_item(&token, id, &bookmark_id).await {
+                    Ok(()) => {
+                        if let Some(mut current) = (*collection).clone() {
+                            current.bookmarks.retain(|b| b.bookmark_id != bookmark_id);
+                            current.collection.item_count = current.bookmarks.len() as i64;
+                            collection.set(Some(current));
+                        }
+                        on_changed.emit(());
+                    }
+                    Err(error) => log::warn!("Fail to remove from collection={id}, error: {error}"),
+                }
+            });
+        })
+    };
+
+    let on_export = {
+        let token = token.clone();
+        let root_ref = root_ref.clone();
+        let name = current.collection.name.clone();
+        Callback::from(move |_: MouseEvent| {
+            let token = token.clone();
+            let root_ref = root_ref.clone();
+            let download = download.clone();
+            let filename = format!("{name}.zip");
+            spawn_local(async move {
+                match collections_api::export_collection(&token, id).await {
+                    Ok(bytes) => {
+                        let url = ObjectUrl::from(Blob::new_with_options(
+                            bytes.as_slice(),
+                            Some("application/zip"),
+                        ));
+                        if let Some(root) = root_ref.cast::<Element>() {
+                            save_as(&root, &url, &filename);
+                        }
+                        *download.borrow_mut() = Some(url);
+                    }
+                    Err(error) => log::warn!("Fail to export collection={id}, error: {error}"),
+                }
+            });
+        })
+    };
+
+    let on_delete = {
+        let token = token.clone();
+        let on_deleted = props.on_deleted.clone();
+        Callback::from(move |_: MouseEvent| {
+            let token = token.clone();
+            let on_deleted = on_deleted.clone();
+            spawn_local(async move {
+                match collections_api::delete_collection(&token, id).await {
+                    Ok(()) => on_deleted.emit(()),
+                    Err(error) => log::warn!("Fail to delete collection={id}, error: {error}"),
+                }
+            });
+        })
+    };
+
+    let last = current.bookmarks.len().saturating_sub(1);
+    let items = current
+        .bookmarks
+        .iter()
+        .enumerate()
+        .map(|(index, bookmark)| {
+            let on_select = {
+                let callback = props.on_bookmark_selected.clone();
+                let bookmark = bookmark.clone();
+                Callback::from(move |event: MouseEvent| {
+                    event.prevent_default();
+                    callback.emit(bookmark.clone());
+                })
+            };
+            let on_remove = {
+                let on_remove = on_remove.clone();
+                let bookmark_id = bookmark.bookmark_id.clone();
+                Callback::from(move |_: MouseEvent| on_remove.emit(bookmark_id.clone()))
+            };
+            html! {
+                <li class="list-group-item d-flex align-items-center" key={bookmark.bookmark_id.clone()}>
+                    <span class="text-muted me-3">{index + 1}</span>
+                    <a href="#" class="flex-grow-1 text-reset" onclick={on_select}>
+                        {bookmark.title.clone()}
+                        <small class="text-muted ms-2">{bookmark.domain.clone()}</small>
+                    </a>
+                    <div class="btn-group btn-group-sm">
+                        <button class="btn btn-outline-secondary" type="button" title="Move up"
+                            disabled={index == 0}
+                            onclick={reorder(index, index.saturating_sub(1))}>{"↑"}</button>
+                        <button class="btn btn-outline-secondary" type="button" title="Move down"
+                            disabled={index == last}
+                            onclick={reorder(index, (index + 1).min(last))}>{"↓"}</button>
+                        <button class="btn btn-outline-danger" type="button" title="Remove"
+                            onclick={on_remove}>{"×"}</button>
+                    </div>
+                </li>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div ref={root_ref}>
+            <div class="d-flex justify-content-between align-items-center mb-2">
+                <h4 class="mb-0">{current.collection.name.clone()}</h4>
+                <div>
+                    <button class="btn btn-sm btn-outline-primary me-2" type="button" onclick={on_export}>
+                        {"Export"}
+                    </button>
+                    <button class="btn btn-sm btn-outline-danger" type="button" onclick={on_delete}>
+                        {"Delete"}
+                    </button>
+                </div>
+            </div>
+            if let Some(description) = current.collection.description.clone() {
+                <p class="text-muted">{description}</p>
+            }
+            if current.bookmarks.is_empty() {
+                <p class="text-muted">{"No bookmarks yet, add them from the reader."}</p>
+            } else {
+                <ol class="list-group mb-3">{items}</ol>
+            }
+        </div>
+    }
+}
+
+/// Downloads `url` through a temporary link.
+fn save_as(root: &Element, url: &str, filename: &str) {
+    let Some(document) = root.owner_document() else {
+        return;
+    };
+    if let Ok(link) = document.create_element("a") {
+        let _ = link.set_attribute("href", url);
+        let _ = link.set_attribute("download", filename);
+        if let Ok(link) = link.dyn_into::<HtmlElement>() {
+            link.click();
+        }
+    }
+}
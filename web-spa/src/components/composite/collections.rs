@@ -0,0 +1,127 @@
+use uuid::Uuid;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::{
+    api::collections_api::{self, Collection},
+    components::atoms::input_text::{InputText, InputType},
+    user_session::UserSession,
+};
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub user_session: UserSession,
+    /// The collection shown instead of the search results.
+    pub selected: Option<Uuid>,
+    /// Reloads the list when changed.
+    pub version: u32,
+    /// `None` goes back to the search results.
+    pub on_selected: Callback<Option<Uuid>>,
+}
+
+#[function_component(Collections)]
+pub fn collections(props: &Props) -> Html {
+    let token = props.user_session.token.clone();
+    let collections = use_state(Vec::<Collection>::new);
+    let name = use_state(String::new);
+
+    {
+        let token = token.clone();
+        let collections = collections.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match collections_api::get_collections(&token).await {
+                        Ok(response) => collections.set(response.collections),
+                        Err(error) => log::warn!("Fail to fetch collections, error: {error}"),
+                    }
+                });
+                || ()
+            },
+            props.version,
+        );
+    }
+
+    let on_name_change = {
+        let name = name.clone();
+        Callback::from(move |text: String| name.set(text))
+    };
+
+    let on_create = {
+        let token = token.clone();
+        let collections = collections.clone();
+        let name = name.clone();
+        let callback = props.on_selected.clone();
+        Callback::from(move |_: MouseEvent| {
+            let text = name.trim().to_owned();
+            if text.is_empty() {
+                return;
+            }
+            let token = token.clone();
+            let collections = collections.clone();
+            let name = name.clone();
+            let callback = callback.clone();
+            spawn_local(async move {
+                match collections_api::create_collection(&token, &text, None).await {
+                    Ok(collection) => {
+                        let id = collection.collection_id;
+                        let mut all = (*collections).clone();
+                        all.push(collection);
+                        all.sort_by(|a, b| a.name.cmp(&b.name));
+                        collections.set(all);
+                        name.set(String::new());
+                        callback.emit(Some(id));
+                    }
+                    Err(error) => log::warn!("Fail to create collection, error: {error}"),
+                }
+            });
+        })
+    };
+
+    let items = collections
+        .iter()
+        .map(|collection| {
+            let id = collection.collection_id;
+            let on_select = {
+                let callback = props.on_selected.clone();
+                let selected = props.selected;
+                Callback::from(move |event: MouseEvent| {
+                    event.prevent_default();
+                    // A second click goes back to the search results.
+                    callback.emit((selected != Some(id)).then_some(id));
+                })
+            };
+            let active = props.selected == Some(id);
+            html! {
+                <a href="#" key={id.to_string()}
+                    class={classes!("list-group-item", "list-group-item-action", "d-flex", "justify-content-between", "align-items-center", active.then_some("active"))}
+                    onclick={on_select}>
+                    {collection.name.clone()}
+                    <span class="badge bg-secondary rounded-pill">{collection.item_count}</span>
+                </a>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="mb-4">
+            <h6 class="text-muted">{"Collections"}</h6>
+            if !collections.is_empty() {
+                <div class="list-group mb-2">{items}</div>
+            }
+            <div class="input-group input-group-sm">
+                <InputText
+                    id="collection-name"
+                    name="collection-name"
+                    placeholder="Name"
+                    class={classes!("form-control")}
+                    input_type={InputType::Text}
+                    on_change={on_name_change}
+                    value={Some((*name).clone())} />
+                <button class="btn btn-outline-primary" type="button" onclick={on_create}>
+                    {"New collection"}
+                </button>
+            </div>
+        </div>
+    }
+}
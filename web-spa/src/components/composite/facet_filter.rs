@@ -0,0 +1,89 @@
+use yew::prelude::*;
+
+use crate::components::atoms::input_checkbox::{InputCheckbox, ItemCheckEvent};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FacetCheckedEvent {
+    Checked(String),
+    Unchecked(String),
+}
+
+/// One value of a facet with its number of matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacetValue {
+    pub value: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    /// Tells the checkboxes of the facets apart.
+    pub name: String,
+    #[prop_or_default]
+    pub label: Option<String>,
+    pub values: Vec<FacetValue>,
+    #[prop_or_default]
+    pub checked: Vec<String>,
+    pub on_checked: Callback<FacetCheckedEvent>,
+}
+
+fn render_value(
+    name: &str,
+    on_checked: Callback<FacetCheckedEvent>,
+    value: FacetValue,
+    checked: bool,
+) -> Html {
+    let on_change = {
+        let value = value.value.clone();
+        Callback::from(move |event: ItemCheckEvent| match event {
+            ItemCheckEvent::Checked(_) => {
+                on_checked.emit(FacetCheckedEvent::Checked(value.clone()))
+            }
+            ItemCheckEvent::Unchecked(_) => {
+                on_checked.emit(FacetCheckedEvent::Unchecked(value.clone()))
+            }
+        })
+    };
+    let id = format!("{name}-{}", value.value);
+
+    html! {
+        <li key={id.clone()}>
+            <div class="form-check">
+                <InputCheckbox
+                    id={id.clone()}
+                    name={name.to_owned()}
+                    value={value.value.clone()}
+                    checked={checked}
+                    class={classes!("form-check-input")}
+                    on_change={on_change} />
+                <label class="form-check-label" for={id}>
+                    {value.value} <span class="badge bg-secondary">{value.count}</span>
+                </label>
+            </div>
+        </li>
+    }
+}
+
+#[function_component(FacetFilter)]
+pub fn facet_filter(props: &Props) -> Html {
+    let values = props
+        .values
+        .clone()
+        .into_iter()
+        .map(|value| {
+            let checked = props.checked.contains(&value.value);
+            render_value(&props.name, props.on_checked.clone(), value, checked)
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="d-flex flex-wrap align-items-baseline gap-3">
+            if let Some(label) = props.label.clone() {
+                <strong>{label}</strong>
+            }
+            <ul class="list-unstyled d-flex flex-wrap gap-3 mb-2">
+                {values}
+            </ul>
+        </div>
+    }
+}
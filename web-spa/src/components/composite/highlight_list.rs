@@ -0,0 +1,103 @@
+use uuid::Uuid;
+use yew::prelude::*;
+
+use crate::{
+    api::bookmarks_api::Highlight,
+    components::atoms::input_text::{InputText, InputType},
+};
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub highlights: Vec<Highlight>,
+    /// A blank note removes it.
+    pub on_note: Callback<(Uuid, Option<String>)>,
+    pub on_delete: Callback<Uuid>,
+    /// Scrolls to the highlight in the page.
+    pub on_selected: Callback<Uuid>,
+}
+
+#[function_component(HighlightList)]
+pub fn highlight_list(props: &Props) -> Html {
+    if props.highlights.is_empty() {
+        return html! {};
+    }
+    let items = props.highlights.iter().map(|highlight| {
+        html! {
+            <HighlightItem
+                key={highlight.highlight_id.to_string()}
+                highlight={highlight.clone()}
+                on_note={props.on_note.clone()}
+                on_delete={props.on_delete.clone()}
+                on_selected={props.on_selected.clone()} />
+        }
+    });
+    html! {
+        <div class="card mt-4">
+            <div class="card-header">{"Highlights"}</div>
+            <ul class="list-group list-group-flush">
+                { for items }
+            </ul>
+        </div>
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+struct ItemProps {
+    highlight: Highlight,
+    on_note: Callback<(Uuid, Option<String>)>,
+    on_delete: Callback<Uuid>,
+    on_selected: Callback<Uuid>,
+}
+
+#[function_component(HighlightItem)]
+fn highlight_item(props: &ItemProps) -> Html {
+    let id = props.highlight.highlight_id;
+    let note = use_state_eq(|| props.highlight.note.clone().unwrap_or_default());
+
+    let on_note_change = {
+        let note = note.clone();
+        Callback::from(move |text: String| note.set(text))
+    };
+
+    let on_save_note = {
+        let callback = props.on_note.clone();
+        let note = note.clone();
+        Callback::from(move |_: MouseEvent| {
+            let text = note.trim();
+            callback.emit((id, (!text.is_empty()).then(|| text.to_owned())));
+        })
+    };
+
+    let on_delete = {
+        let callback = props.on_delete.clone();
+        Callback::from(move |_: MouseEvent| callback.emit(id))
+    };
+
+    let on_select = {
+        let callback = props.on_selected.clone();
+        Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            callback.emit(id);
+        })
+    };
+
+    html! {
+        <li class="list-group-item">
+            <a href="#" class="text-reset" onclick={on_select}>
+                <mark>{props.highlight.exact.clone()}</mark>
+            </a>
+            <div class="input-group input-group-sm mt-2">
+                <InputText
+                    id={format!("highlight-note-{id}")}
+                    name="highlight-note"
+                    placeholder="Note"
+                    class={classes!("form-control")}
+                    input_type={InputType::Text}
+                    on_change={on_note_change}
+                    value={(*note).clone()} />
+                <button onclick={on_save_note} class="btn btn-outline-primary" type="button">{"Save"}</button>
+                <button onclick={on_delete} class="btn btn-outline-danger" type="button">{"Delete"}</button>
+            </div>
+        </li>
+    }
+}
@@ -0,0 +1,194 @@
+use std::str::FromStr;
+
+use gloo_file::futures::read_as_text;
+use wasm_bindgen::JsCast;
+use web_sys::{EventTarget, HtmlInputElement, HtmlSelectElement};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::{
+    api::imports_api::{self, ImportFormat, ImportProgress},
+    user_session::UserSession,
+};
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub user_session: UserSession,
+}
+
+fn render_import(import: &ImportProgress) -> Html {
+    let finished = import.done + import.failed;
+    let errors = import
+        .parse_errors
+        .iter()
+        .map(|error| html! { <li><code>{&error.entry}</code>{": "}{&error.reason}</li> })
+        .collect::<Html>();
+    html! {
+        <li class="list-group-item">
+            <div class="d-flex justify-content-between">
+                <span><strong>{import.format.to_string()}</strong>{" "}{import.created_at}</span>
+                <span>{format!("{finished}/{}", import.total)}</span>
+            </div>
+            <progress class="w-100" value={finished.to_string()} max={import.total.to_string()} />
+            <small class="text-muted">
+                {format!("{} pending, {} done, {} failed", import.pending, import.done, import.failed)}
+            </small>
+            if !import.parse_errors.is_empty() {
+                <details>
+                    <summary>{format!("{} entries skipped", import.parse_errors.len())}</summary>
+                    <ul class="small">{errors}</ul>
+                </details>
+            }
+        </li>
+    }
+}
+
+#[function_component(ImportModal)]
+pub fn import_modal(props: &Props) -> Html {
+    let token = props.user_session.token.clone();
+    let format_state = use_state(ImportFormat::default);
+    let file_state = use_state(|| None::<web_sys::File>);
+    let imports_state = use_state(Vec::<ImportProgress>::new);
+    let error_state = use_state(|| None::<String>);
+
+    let refresh = {
+        let token = token.clone();
+        let imports_state = imports_state.clone();
+        Callback::from(move |_: ()| {
+            let token = token.clone();
+            let imports_state = imports_state.clone();
+            spawn_local(async move {
+                match imports_api::get_imports(&token).await {
+                    Ok(response) => imports_state.set(response.imports),
+                    Err(error) => log::warn!("Fail to fetch imports, error: {error}"),
+                }
+            });
+        })
+    };
+
+    {
+        let refresh = refresh.clone();
+        use_effect_with_deps(
+            move |_| {
+                refresh.emit(());
+                || ()
+            },
+            (),
+        );
+    }
+
+    let on_format_change = {
+        let format_state = format_state.clone();
+        Callback::from(move |event: Event| {
+            let target: EventTarget = event.target().expect("Fail to cast to EventTarget");
+            let value = target.unchecked_into::<HtmlSelectElement>().value();
+            format_state.set(ImportFormat::from_str(&value).unwrap_or_default());
+        })
+    };
+
+    let on_file_change = {
+        let file_state = file_state.clone();
+        Callback::from(move |event: Event| {
+            let target: EventTarget = event.target().expect("Fail to cast to EventTarget");
+            let files = target.unchecked_into::<HtmlInputElement>().files();
+            file_state.set(files.and_then(|files| files.get(0)));
+        })
+    };
+
+    let on_form_submit = {
+        let token = token.clone();
+        let format_state = format_state.clone();
+        let file_state = file_state.clone();
+        let error_state = error_state.clone();
+        let refresh = refresh.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            let Some(file) = (*file_state).clone() else {
+                error_state.set(Some("Select a file to import".to_owned()));
+                return;
+            };
+            let token = token.clone();
+            let format = *format_state;
+            let error_state = error_state.clone();
+            let refresh = refresh.clone();
+            spawn_local(async move {
+                let content = match read_as_text(&gloo_file::File::from(file)).await {
+                    Ok(content) => content,
+                    Err(error) => {
+                        error_state.set(Some(format!("Fail to read file: {error}")));
+                        return;
+                    }
+                };
+                match imports_api::import_file(&token, format, content).await {
+                    Ok(job) => {
+                        log::info!("Import started, import_id={}", job.import_id);
+                        error_state.set(None);
+                        refresh.emit(());
+                    }
+                    Err(error) => error_state.set(Some(format!("Import failed: {error}"))),
+                }
+            });
+        })
+    };
+
+    let on_refresh = {
+        let refresh = refresh.clone();
+        Callback::from(move |_: MouseEvent| refresh.emit(()))
+    };
+
+    let formats = [
+        (
+            ImportFormat::Netscape,
+            "Browser bookmarks.html (Chrome, Firefox, Safari)",
+        ),
+        (ImportFormat::Pocket, "Pocket export (HTML)"),
+        (ImportFormat::Pinboard, "Pinboard JSON"),
+        (ImportFormat::Csv, "CSV of urls and tags"),
+    ]
+    .into_iter()
+    .map(|(format, label)| {
+        html! {
+            <option value={format.to_string()} selected={*format_state == format}>{label}</option>
+        }
+    })
+    .collect::<Html>();
+
+    let imports = imports_state.iter().map(render_import).collect::<Html>();
+
+    html! {
+        <div class="modal fade" id="import-modal" tabindex="-1">
+            <div class="modal-dialog modal-lg">
+                <div class="modal-content">
+                    <div class="modal-header">
+                        <h5 class="modal-title">{"Import Bookmarks"}</h5>
+                        <button type="button" class="btn-close" data-bs-dismiss="modal" aria-label="Close"></button>
+                    </div>
+                    <div class="modal-body">
+                        <form onsubmit={on_form_submit}>
+                            <div class="mb-3">
+                                <label for="import-format" class="form-label">{"Format"}</label>
+                                <select id="import-format" class="form-select" onchange={on_format_change}>
+                                    {formats}
+                                </select>
+                            </div>
+                            <div class="mb-3">
+                                <label for="import-file" class="form-label">{"File"}</label>
+                                <input id="import-file" type="file" class="form-control" onchange={on_file_change} />
+                            </div>
+                            if let Some(error) = (*error_state).clone() {
+                                <div class="alert alert-danger">{error}</div>
+                            }
+                            <input type="submit" class="btn btn-primary" value="Import" />
+                        </form>
+                        <hr/>
+                        <div class="d-flex justify-content-between align-items-center mb-2">
+                            <h6 class="mb-0">{"Imports"}</h6>
+                            <button type="button" class="btn btn-sm btn-outline-secondary" onclick={on_refresh}>{"Refresh"}</button>
+                        </div>
+                        <ul class="list-group">{imports}</ul>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
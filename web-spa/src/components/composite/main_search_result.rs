@@ -1,25 +1,89 @@
 use yew::prelude::*;
 
-use crate::{api::search_api::SearchResultItem, components::atoms::safe_html::BlockquoteHtml};
+use crate::api::search_api::{Anchor, SearchResultItem, Snippet};
 
 #[derive(Debug, Clone, PartialEq, Properties)]
 pub struct Props {
     pub results: Vec<SearchResultItem>,
     pub on_item_selected: Callback<SearchResultItem>,
+    /// Opens the item scrolled to the match of a snippet.
+    pub on_snippet_selected: Callback<(SearchResultItem, Anchor)>,
 }
 
-fn article(callback: Callback<SearchResultItem>, item: SearchResultItem) -> Html {
+/// The text of the snippet with its highlights in `<mark>`.
+fn render_snippet(snippet: &Snippet) -> Html {
+    let chars: Vec<char> = snippet.text.chars().collect();
+    let slice = |start: usize, end: usize| -> String {
+        chars[start.min(chars.len())..end.min(chars.len())]
+            .iter()
+            .collect()
+    };
+    let mut nodes = Vec::new();
+    let mut cursor = 0;
+    for highlight in &snippet.highlights {
+        if highlight.start < cursor {
+            continue;
+        }
+        nodes.push(html! { {slice(cursor, highlight.start)} });
+        nodes.push(html! { <mark>{slice(highlight.start, highlight.end)}</mark> });
+        cursor = highlight.end;
+    }
+    nodes.push(html! { {slice(cursor, chars.len())} });
+    nodes.into_iter().collect()
+}
+
+fn article(
+    callback: Callback<SearchResultItem>,
+    on_snippet_selected: Callback<(SearchResultItem, Anchor)>,
+    item: SearchResultItem,
+) -> Html {
     let item_for_event = item.clone();
     let tags = item
         .tags
+        .clone()
         .unwrap_or_default()
         .into_iter()
         .map(|tag| html! { <span class="badge bg-primary me-1">{tag}</span> })
         .collect::<Html>();
-    let search_match = match item.search_match.clone() {
-        Some(html) => html! { <BlockquoteHtml html={html} /> },
-        None => html! { <></>},
-    };
+    let snippets = item
+        .snippets
+        .iter()
+        .map(|snippet| {
+            let text = render_snippet(snippet);
+            match snippet.anchor.clone() {
+                Some(anchor) => {
+                    let callback = on_snippet_selected.clone();
+                    let item = item.clone();
+                    let onclick = Callback::from(move |event: MouseEvent| {
+                        event.prevent_default();
+                        callback.emit((item.clone(), anchor.clone()));
+                    });
+                    html! {
+                        <blockquote class="mb-1">
+                            <a href="#" class="text-reset text-decoration-none" {onclick}>
+                                {"… "}{text}{" …"}
+                            </a>
+                        </blockquote>
+                    }
+                }
+                None => html! { <blockquote class="mb-1">{"… "}{text}{" …"}</blockquote> },
+            }
+        })
+        .collect::<Html>();
+    let state = [
+        (item.favorite, "★ Favorite", "bg-warning text-dark"),
+        (item.archived, "Archived", "bg-secondary"),
+        (item.read_at.is_none(), "Unread", "bg-info text-dark"),
+    ]
+    .into_iter()
+    .filter(|(shown, _, _)| *shown)
+    .map(|(_, label, color)| {
+        html! { <span class={classes!("badge", "me-1", color)}>{label}</span> }
+    })
+    .collect::<Html>();
+    let progress = (item.read_at.is_none() && item.progress > 0.0).then(|| {
+        html! { <small class="text-muted ms-1">{format!("{:.0}% read", item.progress * 100.0)}</small> }
+    });
     let on_click = Callback::from(move |_| {
         callback.emit(item_for_event.clone());
     });
@@ -28,9 +92,15 @@ fn article(callback: Callback<SearchResultItem>, item: SearchResultItem) -> Html
         <div class="card mb-3">
             <div class="card-body">
                 <h5 class="card-title">{item.title.clone()}</h5>
-                <p class="card-text">{search_match}</p>
+                <div class="mb-1">{state}{progress}</div>
+                <div class="card-text">{snippets}</div>
                 <div>{tags}</div>
-                <small class="text-muted">{"Created at:"} {item.created_at}</small>
+                <small class="text-muted">
+                    {"Created at:"} {item.created_at}
+                    if item.word_count > 0 {
+                        {format!(" · {} min read", item.reading_time)}
+                    }
+                </small>
                 <a onclick={on_click} class="btn btn-link mt-2 d-block">{"Read more..."}</a>
             </div>
         </div>
@@ -46,7 +116,7 @@ pub fn main_search_result(props: &Props) -> Html {
                 results.into_iter().map(|bookmark| {
                     html! {
                         <>
-                            {article(props.on_item_selected.clone(), bookmark)}
+                            {article(props.on_item_selected.clone(), props.on_snippet_selected.clone(), bookmark)}
                         </>
                     }
                 }).collect::<Html>()
@@ -1,7 +1,12 @@
 pub mod add_bookmark_modal;
 pub mod bookmark_reader;
+pub mod collection_view;
+pub mod collections;
+pub mod facet_filter;
+pub mod highlight_list;
+pub mod import_modal;
 pub mod login_form;
 pub mod main_search_result;
 pub mod navigation_bar;
+pub mod saved_searches;
 pub mod search_bar;
-pub mod tags_filter;
@@ -18,6 +18,11 @@ pub fn navigation_bar(props: &Props) -> Html {
                                 {"Add Bookmark"}
                             </button>
                         </li>
+                        <li class="nav-item ms-2">
+                            <button class="btn btn-outline-primary" data-bs-toggle="modal" data-bs-target="#import-modal">
+                                {"Import"}
+                            </button>
+                        </li>
                         <li class="nav-item">
                             <span class="nav-link">{&props.email}</span>
                         </li>
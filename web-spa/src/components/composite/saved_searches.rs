@@ -0,0 +1,153 @@
+use uuid::Uuid;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::{
+    api::saved_searches_api::{self, SavedSearch},
+    components::atoms::input_text::{InputText, InputType},
+    user_session::UserSession,
+};
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub user_session: UserSession,
+    /// The saved search the results come from.
+    pub selected: Option<Uuid>,
+    /// Reloads the list when changed.
+    pub version: u32,
+    /// `None` goes back to all the bookmarks.
+    pub on_selected: Callback<Option<Uuid>>,
+    /// Saves the current search under the given name.
+    pub on_save: Callback<String>,
+}
+
+#[function_component(SavedSearches)]
+pub fn saved_searches(props: &Props) -> Html {
+    let token = props.user_session.token.clone();
+    let saved_searches = use_state(Vec::<SavedSearch>::new);
+    let name = use_state(String::new);
+
+    {
+        let token = token.clone();
+        let saved_searches = saved_searches.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match saved_searches_api::get_saved_searches(&token).await {
+                        Ok(response) => saved_searches.set(response.saved_searches),
+                        Err(error) => log::warn!("Fail to fetch saved searches, error: {error}"),
+                    }
+                });
+                || ()
+            },
+            props.version,
+        );
+    }
+
+    let on_name_change = {
+        let name = name.clone();
+        Callback::from(move |text: String| name.set(text))
+    };
+
+    let on_save = {
+        let callback = props.on_save.clone();
+        let name = name.clone();
+        Callback::from(move |_: MouseEvent| {
+            let text = name.trim();
+            if !text.is_empty() {
+                callback.emit(text.to_owned());
+                name.set(String::new());
+            }
+        })
+    };
+
+    let on_all = {
+        let callback = props.on_selected.clone();
+        Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            callback.emit(None);
+        })
+    };
+
+    let items = saved_searches
+        .iter()
+        .map(|saved_search| {
+            let id = saved_search.saved_search_id;
+            let on_select = {
+                let callback = props.on_selected.clone();
+                Callback::from(move |event: MouseEvent| {
+                    event.prevent_default();
+                    callback.emit(Some(id));
+                })
+            };
+            let on_delete = {
+                let token = token.clone();
+                let saved_searches = saved_searches.clone();
+                let callback = props.on_selected.clone();
+                let selected = props.selected;
+                Callback::from(move |event: MouseEvent| {
+                    event.stop_propagation();
+                    let token = token.clone();
+                    let saved_searches = saved_searches.clone();
+                    let callback = callback.clone();
+                    spawn_local(async move {
+                        match saved_searches_api::delete_saved_search(&token, id).await {
+                            Ok(()) => {
+                                let mut remaining = (*saved_searches).clone();
+                                remaining.retain(|s| s.saved_search_id != id);
+                                saved_searches.set(remaining);
+                                if selected == Some(id) {
+                                    callback.emit(None);
+                                }
+                            }
+                            Err(error) => log::warn!("Fail to delete saved search, error: {error}"),
+                        }
+                    });
+                })
+            };
+            let active = props.selected == Some(id);
+            html! {
+                <a href="#" key={id.to_string()}
+                    class={classes!("list-group-item", "list-group-item-action", "d-flex", "justify-content-between", "align-items-center", active.then_some("active"))}
+                    onclick={on_select}>
+                    {saved_search.name.clone()}
+                    <span>
+                        if let Some(new_matches) = saved_search.new_matches.filter(|n| *n > 0) {
+                            <span class="badge bg-primary rounded-pill me-2" title="New matches">
+                                {new_matches}
+                            </span>
+                        }
+                        <button type="button" class="btn-close btn-sm" aria-label="Delete" onclick={on_delete} />
+                    </span>
+                </a>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="mb-4">
+            <h6 class="text-muted">{"Saved searches"}</h6>
+            <div class="list-group mb-2">
+                <a href="#"
+                    class={classes!("list-group-item", "list-group-item-action", props.selected.is_none().then_some("active"))}
+                    onclick={on_all}>
+                    {"All bookmarks"}
+                </a>
+                {items}
+            </div>
+            <div class="input-group input-group-sm">
+                <InputText
+                    id="saved-search-name"
+                    name="saved-search-name"
+                    placeholder="Name"
+                    class={classes!("form-control")}
+                    input_type={InputType::Text}
+                    on_change={on_name_change}
+                    value={Some((*name).clone())} />
+                <button class="btn btn-outline-primary" type="button" onclick={on_save}>
+                    {"Save search"}
+                </button>
+            </div>
+        </div>
+    }
+}
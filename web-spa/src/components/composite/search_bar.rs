@@ -3,24 +3,45 @@ use yew::prelude::*;
 
 use crate::{
     api::search_api::SearchType,
-    components::atoms::input_text::{InputText, InputType},
+    components::atoms::{
+        input_switch::InputSwitch,
+        input_text::{InputText, InputType},
+    },
 };
 
 #[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
 pub struct SearchInputSubmit {
     pub input: String,
     pub search_type: SearchType,
+    pub fuzzy: bool,
 }
 
 #[derive(PartialEq, Properties)]
 pub struct Props {
     pub on_submit: Callback<SearchInputSubmit>,
+    /// Replaces the input, e.g. with an accepted suggestion.
+    #[prop_or_default]
+    pub value: Option<String>,
 }
 
 #[function_component(SearchBar)]
 pub fn search_bar(props: &Props) -> Html {
     let state = use_state(SearchInputSubmit::default);
 
+    {
+        let state = state.clone();
+        use_effect_with_deps(
+            move |value| {
+                if let Some(value) = value {
+                    let mut data = (*state).clone();
+                    data.input = value.clone();
+                    state.set(data);
+                }
+            },
+            props.value.clone(),
+        );
+    }
+
     let on_input_search_change = {
         let state = state.clone();
         Callback::from(move |text: String| {
@@ -30,8 +51,18 @@ pub fn search_bar(props: &Props) -> Html {
         })
     };
 
+    let on_fuzzy_change = {
+        let state = state.clone();
+        Callback::from(move |fuzzy: bool| {
+            let mut data = (*state).clone();
+            data.fuzzy = fuzzy;
+            state.set(data);
+        })
+    };
+
     let on_submit = {
         let on_search = props.on_submit.clone();
+        let state = state.clone();
         Callback::from(move |event: SubmitEvent| {
             event.prevent_default();
             let data = (*state).clone();
@@ -47,12 +78,14 @@ pub fn search_bar(props: &Props) -> Html {
                         id="search"
                         name="search"
                         placeholder="search query"
+                        value={props.value.clone()}
                         input_type={InputType::Search}
                         class={classes!("form-control", "me-2")}
                         on_change={on_input_search_change} />
                     <input class="btn btn-outline-success" type="submit" value="Search" />
                 </form>
             </div>
+            <InputSwitch name="fuzzy" label="Tolerate typos" on_change={on_fuzzy_change} />
         </>
     }
 }
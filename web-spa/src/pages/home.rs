@@ -1,32 +1,98 @@
+use std::collections::BTreeMap;
+
+use gloo_net::Error;
+use uuid::Uuid;
 use yew::{platform::spawn_local, prelude::*};
 
 use crate::{
     api::{
         bookmarks_api::{self, Bookmark},
-        search_api::{self, SearchRequest, SearchResultItem, TagFilter},
-        tags_api::Tag,
+        saved_searches_api,
+        search_api::{
+            self, Anchor, Facet, FacetCount, SearchMode, SearchRequest, SearchResponse,
+            SearchResultItem, TagFilter,
+        },
+        tags_api::TagNode,
     },
     components::composite::{
         add_bookmark_modal::{AddBookmarkData, AddBookmarkModal},
         bookmark_reader::BookmarkReader,
+        collection_view::CollectionView,
+        collections::Collections,
+        facet_filter::{FacetCheckedEvent, FacetFilter, FacetValue},
+        import_modal::ImportModal,
         main_search_result::MainSearchResult,
         navigation_bar::NavigationBar,
+        saved_searches::SavedSearches,
         search_bar::{SearchBar, SearchInputSubmit},
-        tags_filter::{TagCheckedEvent, TagsFilter},
     },
     user_session::UserSession,
 };
 
+/// Narrows the results by their reading state.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum ReadingFilter {
+    #[default]
+    All,
+    Unread,
+    Favorites,
+    Archived,
+}
+
+impl ReadingFilter {
+    const ALL: [ReadingFilter; 4] = [
+        ReadingFilter::All,
+        ReadingFilter::Unread,
+        ReadingFilter::Favorites,
+        ReadingFilter::Archived,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ReadingFilter::All => "All",
+            ReadingFilter::Unread => "Unread",
+            ReadingFilter::Favorites => "Favorites",
+            ReadingFilter::Archived => "Archived",
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Default, Debug)]
 pub struct HomeState {
     pub user_session: UserSession,
     pub bookmarks: Vec<SearchResultItem>,
-    pub tags: Vec<Tag>,
+    pub tags: Vec<TagNode>,
     pub tags_filter: Vec<String>,
+    pub facets: BTreeMap<Facet, Vec<FacetCount>>,
+    pub facet_filters: BTreeMap<Facet, Vec<String>>,
     pub search_input: String,
     pub new_bookmark_url: String,
     pub new_bookmark_tags: Vec<String>,
     pub bookmark_read: Option<Bookmark>,
+    /// The match to scroll to in the bookmark read.
+    pub read_anchor: Option<Anchor>,
+    pub next_cursor: Option<String>,
+    pub fuzzy: bool,
+    pub reading_filter: ReadingFilter,
+    pub did_you_mean: Option<String>,
+    /// Restricts the results to the matches of a saved search.
+    pub saved_search: Option<Uuid>,
+    pub saved_searches_version: u32,
+    /// The collection shown instead of the search results.
+    pub collection: Option<Uuid>,
+    pub collections_version: u32,
+}
+
+impl HomeState {
+    /// The saved search when nothing refines it, its results are then paged
+    /// with the saved sort.
+    fn saved_search_only(&self) -> Option<Uuid> {
+        let refined = !self.search_input.is_empty()
+            || !self.tags_filter.is_empty()
+            || !self.facet_filters.is_empty()
+            || self.reading_filter != ReadingFilter::All;
+        self.saved_search.filter(|_| !refined)
+    }
 }
 
 impl From<HomeState> for SearchRequest {
@@ -36,15 +102,39 @@ impl From<HomeState> for SearchRequest {
         } else {
             Some(value.search_input)
         };
-        let tags_filter: Option<TagFilter> = if value.tags_filter.is_empty() {
+        let tags_filter: Option<TagFilter> = if !value.tags_filter.is_empty() {
+            Some(TagFilter::Or(value.tags_filter))
+        } else {
+            value.saved_search.map(TagFilter::SavedSearch)
+        };
+        let facet_filters = if value.facet_filters.is_empty() {
             None
         } else {
-            Some(TagFilter::Or(value.tags_filter))
+            Some(value.facet_filters)
+        };
+        let (read, favorite, archived) = match value.reading_filter {
+            ReadingFilter::All => (None, None, None),
+            ReadingFilter::Unread => (Some(false), None, None),
+            ReadingFilter::Favorites => (None, Some(true), None),
+            ReadingFilter::Archived => (None, None, Some(true)),
         };
         SearchRequest {
             query,
             tags_filter,
+            facet_filters,
             limit: Some(20),
+            sort: None,
+            cursor: value.next_cursor,
+            mode: value.fuzzy.then_some(SearchMode::Fuzzy),
+            read,
+            favorite,
+            archived,
+            min_reading_time: None,
+            max_reading_time: None,
+            min_word_count: None,
+            max_word_count: None,
+            min_extraction_ratio: None,
+            max_extraction_ratio: None,
         }
     }
 }
@@ -81,21 +171,43 @@ pub fn home(props: &Props) -> Html {
         let state = state.clone();
         let token = token.clone();
         Callback::from(move |event: SearchInputSubmit| {
+            let mut home = (*state).clone();
+            home.search_input = event.input;
+            home.fuzzy = event.fuzzy;
+            spawn_local(new_search(state.clone(), token.clone(), home));
+        })
+    };
+
+    let on_suggestion = {
+        let state = state.clone();
+        let token = token.clone();
+        Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            let mut home = (*state).clone();
+            if let Some(suggestion) = home.did_you_mean.take() {
+                home.search_input = suggestion;
+                spawn_local(new_search(state.clone(), token.clone(), home));
+            }
+        })
+    };
+
+    let on_load_more = {
+        let state = state.clone();
+        let token = token.clone();
+        Callback::from(move |_: MouseEvent| {
             let state = state.clone();
             let token = token.clone();
             spawn_local(async move {
                 let mut home = (*state).clone();
-                home.search_input = event.input.clone();
-                match search_api::search(&token, home.clone().into()).await {
+                match fetch(&token, &home).await {
                     Ok(result) => {
-                        log::info!("result={:?}", result);
-                        home.bookmarks = result.bookmarks;
-                        home.tags = result.tags;
+                        home.bookmarks.extend(result.bookmarks);
+                        home.next_cursor = result.next_cursor;
                         state.set(home);
                     }
                     Err(error) => {
                         // FIXME notify user
-                        log::warn!("Fail to search bookmarks, error: {}", error);
+                        log::warn!("Fail to load more bookmarks, error: {}", error);
                     }
                 }
             })
@@ -104,53 +216,138 @@ pub fn home(props: &Props) -> Html {
 
     let on_tag_checked = {
         let state = state.clone();
-        Callback::from(move |event: TagCheckedEvent| match event {
-            TagCheckedEvent::Checked(tag) => {
-                if !state.tags_filter.contains(&tag.tag) {
-                    let mut home = (*state).clone();
-                    home.tags_filter.push(tag.tag);
-                    state.set(home);
-                }
+        let token = token.clone();
+        Callback::from(move |event: FacetCheckedEvent| {
+            let mut home = (*state).clone();
+            toggle(&mut home.tags_filter, event);
+            spawn_local(new_search(state.clone(), token.clone(), home));
+        })
+    };
+
+    let facet_filters = Facet::ALL
+        .into_iter()
+        .filter_map(|facet| {
+            let values = state.facets.get(&facet).cloned().unwrap_or_default();
+            if values.is_empty() {
+                return None;
             }
-            TagCheckedEvent::Unchecked(tag) => {
-                if let Some(index_of) = state.tags_filter.iter().position(|e| e == &tag.tag) {
+            let on_checked = {
+                let state = state.clone();
+                let token = token.clone();
+                Callback::from(move |event: FacetCheckedEvent| {
                     let mut home = (*state).clone();
-                    home.tags_filter.remove(index_of);
-                    state.set(home);
-                }
-            }
+                    let checked = home.facet_filters.entry(facet).or_default();
+                    toggle(checked, event);
+                    if checked.is_empty() {
+                        home.facet_filters.remove(&facet);
+                    }
+                    spawn_local(new_search(state.clone(), token.clone(), home));
+                })
+            };
+            let values = values
+                .into_iter()
+                .map(|FacetCount { value, count }| FacetValue { value, count })
+                .collect::<Vec<_>>();
+            let checked = state.facet_filters.get(&facet).cloned().unwrap_or_default();
+            Some(html! {
+                <FacetFilter
+                    name={facet.label().to_lowercase()}
+                    label={Some(facet.label().to_owned())}
+                    {values}
+                    {checked}
+                    {on_checked} />
+            })
+        })
+        .collect::<Html>();
+
+    let reading_filters = ReadingFilter::ALL
+        .into_iter()
+        .map(|filter| {
+            let onclick = {
+                let state = state.clone();
+                let token = token.clone();
+                Callback::from(move |_: MouseEvent| {
+                    let mut home = (*state).clone();
+                    home.reading_filter = filter;
+                    spawn_local(new_search(state.clone(), token.clone(), home));
+                })
+            };
+            let class = if state.reading_filter == filter {
+                "btn btn-sm btn-secondary"
+            } else {
+                "btn btn-sm btn-outline-secondary"
+            };
+            html! { <button {class} type="button" {onclick}>{filter.label()}</button> }
+        })
+        .collect::<Html>();
+
+    let on_saved_search_selected = {
+        let state = state.clone();
+        let token = token.clone();
+        Callback::from(move |saved_search: Option<Uuid>| {
+            let mut home = (*state).clone();
+            home.saved_search = saved_search;
+            home.search_input = String::new();
+            home.tags_filter = Vec::new();
+            home.facet_filters = BTreeMap::new();
+            home.fuzzy = false;
+            home.reading_filter = ReadingFilter::All;
+            spawn_local(new_search(state.clone(), token.clone(), home));
         })
     };
 
-    let on_item_selected = {
+    let on_save_search = {
         let state = state.clone();
         let token = token.clone();
-        Callback::from(move |event: SearchResultItem| {
+        Callback::from(move |name: String| {
             let state = state.clone();
             let token = token.clone();
             spawn_local(async move {
-                match bookmarks_api::get_by_id(&token, &event.bookmark_id).await {
-                    Ok(Some(bookmark)) => {
+                let mut request = SearchRequest::from((*state).clone());
+                request.cursor = None;
+                request.limit = None;
+                match saved_searches_api::create_saved_search(&token, &name, &request).await {
+                    Ok(saved_search) => {
                         let mut home = (*state).clone();
-                        home.bookmark_read = Some(bookmark);
+                        home.saved_searches_version += 1;
                         state.set(home);
+                        log::info!("Search saved, id={}", saved_search.saved_search_id);
                     }
-                    Ok(None) => {
-                        log::warn!("Weird, bookmark not found in backend, item={:?}", event);
-                    }
-                    Err(error) => {
-                        log::error!("Fail to fetch bookmark, item={:?}, error={error}", event);
-                    }
+                    Err(error) => log::warn!("Fail to save search, error: {error}"),
                 }
             });
         })
     };
 
+    let on_item_selected = {
+        let state = state.clone();
+        let token = token.clone();
+        Callback::from(move |event: SearchResultItem| {
+            spawn_local(open_bookmark(state.clone(), token.clone(), event, None));
+        })
+    };
+
+    let on_snippet_selected = {
+        let state = state.clone();
+        let token = token.clone();
+        Callback::from(move |(item, anchor): (SearchResultItem, Anchor)| {
+            spawn_local(open_bookmark(
+                state.clone(),
+                token.clone(),
+                item,
+                Some(anchor),
+            ));
+        })
+    };
+
     let on_goback = {
         let state = state.clone();
         Callback::from(move |_| {
             let mut home = (*state).clone();
             home.bookmark_read = None;
+            home.read_anchor = None;
+            // The bookmark may have been added to a collection.
+            home.collections_version += 1;
             state.set(home);
         })
     };
@@ -177,24 +374,164 @@ pub fn home(props: &Props) -> Html {
         })
     };
 
+    let on_new_note = {
+        let state = state.clone();
+        let token = token.clone();
+        Callback::from(move |note: Option<String>| {
+            let token = token.clone();
+            let state = state.clone();
+            let bookmark_id = state.bookmark_read.clone().expect("not none").bookmark_id;
+            spawn_local(async move {
+                match bookmarks_api::set_note(&token, &bookmark_id, note).await {
+                    Ok(bookmark) => {
+                        let mut home = (*state).clone();
+                        home.bookmark_read = Some(bookmark);
+                        state.set(home);
+                    }
+                    Err(error) => {
+                        log::error!("Fail to set note to bookmark={bookmark_id}, error={error}");
+                    }
+                }
+            });
+        })
+    };
+
+    let on_suggestion_accepted = {
+        let state = state.clone();
+        Callback::from(move |bookmark: Bookmark| {
+            let mut home = (*state).clone();
+            home.bookmark_read = Some(bookmark);
+            state.set(home);
+        })
+    };
+
+    let on_reading_state_changed = {
+        let state = state.clone();
+        Callback::from(move |bookmark: Bookmark| {
+            let mut home = (*state).clone();
+            if let Some(item) = home
+                .bookmarks
+                .iter_mut()
+                .find(|item| item.bookmark_id == bookmark.bookmark_id)
+            {
+                item.favorite = bookmark.favorite;
+                item.archived = bookmark.archived;
+                item.read_at = bookmark.read_at;
+            }
+            home.bookmark_read = Some(bookmark);
+            state.set(home);
+        })
+    };
+
+    let on_collection_selected = {
+        let state = state.clone();
+        Callback::from(move |collection: Option<Uuid>| {
+            let mut home = (*state).clone();
+            home.collection = collection;
+            state.set(home);
+        })
+    };
+
+    let on_collection_changed = {
+        let state = state.clone();
+        Callback::from(move |_| {
+            let mut home = (*state).clone();
+            home.collections_version += 1;
+            state.set(home);
+        })
+    };
+
+    let on_collection_deleted = {
+        let state = state.clone();
+        Callback::from(move |_| {
+            let mut home = (*state).clone();
+            home.collection = None;
+            home.collections_version += 1;
+            state.set(home);
+        })
+    };
+
+    let on_related_selected = {
+        let state = state.clone();
+        Callback::from(move |bookmark: Bookmark| {
+            let mut home = (*state).clone();
+            home.bookmark_read = Some(bookmark);
+            home.read_anchor = None;
+            state.set(home);
+        })
+    };
+
     let bookmark_read = state.bookmark_read.clone();
 
     let content = if let Some(bookmark) = bookmark_read {
         html! {
             <BookmarkReader
+                key={bookmark.bookmark_id.clone()}
                 user_session={props.user_session.clone()}
-                bookmark={bookmark}
+                bookmark={bookmark.clone()}
+                anchor={state.read_anchor.clone()}
                 on_goback={on_goback}
-                on_new_tags={on_new_tags} />
+                on_new_tags={on_new_tags}
+                on_new_note={on_new_note}
+                on_suggestion_accepted={on_suggestion_accepted}
+                on_reading_state_changed={on_reading_state_changed}
+                on_related_selected={on_related_selected} />
         }
     } else {
         html! {
-            <>
-                <SearchBar on_submit={on_search_submit} />
-                <TagsFilter tags={state.tags.clone()} on_tag_checked={on_tag_checked} />
-                <MainSearchResult on_item_selected={on_item_selected} results={state.bookmarks.clone()} />
-                <AddBookmarkModal on_submit={on_new_bookmark} />
-            </>
+            <div class="row">
+                <div class="col-md-3">
+                    <SavedSearches
+                        user_session={props.user_session.clone()}
+                        selected={state.saved_search}
+                        version={state.saved_searches_version}
+                        on_selected={on_saved_search_selected}
+                        on_save={on_save_search} />
+                    <Collections
+                        user_session={props.user_session.clone()}
+                        selected={state.collection}
+                        version={state.collections_version}
+                        on_selected={on_collection_selected} />
+                </div>
+                <div class="col-md-9">
+                    if let Some(collection_id) = state.collection {
+                        <CollectionView
+                            key={collection_id.to_string()}
+                            user_session={props.user_session.clone()}
+                            {collection_id}
+                            on_bookmark_selected={on_related_selected}
+                            on_changed={on_collection_changed}
+                            on_deleted={on_collection_deleted} />
+                    } else {
+                        <SearchBar on_submit={on_search_submit} value={Some(state.search_input.clone())} />
+                        if let Some(suggestion) = state.did_you_mean.clone() {
+                            <p class="text-muted">
+                                {"Did you mean "}<a href="#" onclick={on_suggestion}>{suggestion}</a>{"?"}
+                            </p>
+                        }
+                        <div class="btn-group mb-3" role="group">{reading_filters}</div>
+                        <FacetFilter
+                            name="tag"
+                            values={state.tags.iter().flat_map(TagNode::flatten).map(|tag| FacetValue { value: tag.tag.clone(), count: tag.count }).collect::<Vec<_>>()}
+                            checked={state.tags_filter.clone()}
+                            on_checked={on_tag_checked} />
+                        {facet_filters}
+                        <MainSearchResult
+                            on_item_selected={on_item_selected}
+                            on_snippet_selected={on_snippet_selected}
+                            results={state.bookmarks.clone()} />
+                        if state.next_cursor.is_some() {
+                            <div class="d-grid my-3">
+                                <button type="button" class="btn btn-outline-secondary" onclick={on_load_more}>
+                                    {"Load more"}
+                                </button>
+                            </div>
+                        }
+                    }
+                    <AddBookmarkModal on_submit={on_new_bookmark} />
+                    <ImportModal user_session={props.user_session.clone()} />
+                </div>
+            </div>
         }
     };
 
@@ -207,3 +544,70 @@ pub fn home(props: &Props) -> Html {
         </>
     }
 }
+
+/// Runs the search of `home` from its first page.
+async fn new_search(state: UseStateHandle<HomeState>, token: String, mut home: HomeState) {
+    home.next_cursor = None;
+    match fetch(&token, &home).await {
+        Ok(result) => {
+            if home.saved_search_only().is_some() {
+                // The badge of new matches is gone.
+                home.saved_searches_version += 1;
+            }
+            log::info!("result={:?}", result);
+            home.bookmarks = result.bookmarks;
+            home.tags = result.tags;
+            home.facets = result.facets;
+            home.next_cursor = result.next_cursor;
+            home.did_you_mean = result.did_you_mean;
+            state.set(home);
+        }
+        Err(error) => {
+            // FIXME notify user
+            log::warn!("Fail to search bookmarks, error: {}", error);
+        }
+    }
+}
+
+/// Opens the bookmark of `item` in the reader, scrolled to `anchor`.
+async fn open_bookmark(
+    state: UseStateHandle<HomeState>,
+    token: String,
+    item: SearchResultItem,
+    anchor: Option<Anchor>,
+) {
+    match bookmarks_api::get_by_id(&token, &item.bookmark_id).await {
+        Ok(Some(bookmark)) => {
+            let mut home = (*state).clone();
+            home.bookmark_read = Some(bookmark);
+            home.read_anchor = anchor;
+            state.set(home);
+        }
+        Ok(None) => {
+            log::warn!("Weird, bookmark not found in backend, item={:?}", item);
+        }
+        Err(error) => {
+            log::error!("Fail to fetch bookmark, item={:?}, error={error}", item);
+        }
+    }
+}
+
+/// The next page of the search of `home`.
+async fn fetch(token: &str, home: &HomeState) -> Result<SearchResponse, Error> {
+    match home.saved_search_only() {
+        Some(id) => saved_searches_api::get_results(token, id, home.next_cursor.as_deref()).await,
+        None => search_api::search(&token.to_owned(), home.clone().into()).await,
+    }
+}
+
+/// Adds or removes the value of `event` from the `checked` values.
+fn toggle(checked: &mut Vec<String>, event: FacetCheckedEvent) {
+    match event {
+        FacetCheckedEvent::Checked(value) => {
+            if !checked.contains(&value) {
+                checked.push(value);
+            }
+        }
+        FacetCheckedEvent::Unchecked(value) => checked.retain(|e| e != &value),
+    }
+}